@@ -3,7 +3,7 @@ use glam::{Quat, Vec3, Vec4};
 use std::hint::black_box;
 use tracks_rs::base_provider_context::BaseProviderContext;
 use tracks_rs::base_value::BaseValue;
-use tracks_rs::providers::{AbstractValueProvider, UpdateableValues};
+use tracks_rs::providers::AbstractValueProvider;
 
 fn seed_context(ctx: &mut BaseProviderContext) {
     ctx.set_values(
@@ -63,13 +63,47 @@ fn bench_provider_cache_and_creation(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_base_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("base_provider_read");
+
+    for base in ["baseCombo", "baseHeadPosition", "baseRightHandRotation"] {
+        group.bench_with_input(BenchmarkId::new("by_name", base), &base, |b, &base| {
+            let mut ctx = BaseProviderContext::new();
+            seed_context(&mut ctx);
+            b.iter(|| {
+                black_box(ctx.get_values(black_box(base)));
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("by_slot", base), &base, |b, &base| {
+            let mut ctx = BaseProviderContext::new();
+            seed_context(&mut ctx);
+            let slot = ctx.base_slot(base).unwrap();
+            b.iter(|| {
+                black_box(ctx.get_slot(black_box(slot)));
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("provider", base), &base, |b, &base| {
+            let mut ctx = BaseProviderContext::new();
+            seed_context(&mut ctx);
+            let provider = ctx.get_value_provider(base);
+            b.iter(|| {
+                black_box(provider.values(&ctx));
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn bench_provider_swizzle_update(c: &mut Criterion) {
     let mut group = c.benchmark_group("base_provider_swizzle_update");
 
     group.bench_function("vec3_swizzle_read", |b| {
         let mut ctx = BaseProviderContext::new();
         seed_context(&mut ctx);
-        let mut provider = ctx.get_value_provider("baseHeadPosition.zyx");
+        let provider = ctx.get_value_provider("baseHeadPosition.zyx");
 
         b.iter(|| {
             ctx.update_providers(0.016);
@@ -85,7 +119,7 @@ fn bench_provider_swizzle_update(c: &mut Criterion) {
                 let provider = ctx.get_value_provider("baseHeadPosition.zyx.s0_5");
                 (ctx, provider)
             },
-            |(mut ctx, provider)| {
+            |(ctx, provider)| {
                 for _ in 0..60 {
                     ctx.update_providers(0.016);
                     black_box(provider.values(&ctx));
@@ -103,7 +137,7 @@ fn bench_provider_swizzle_update(c: &mut Criterion) {
                 let provider = ctx.get_value_provider("baseHeadRotation.s0_5");
                 (ctx, provider)
             },
-            |(mut ctx, provider)| {
+            |(ctx, provider)| {
                 for _ in 0..60 {
                     ctx.update_providers(0.016);
                     black_box(provider.values(&ctx));
@@ -121,7 +155,7 @@ fn bench_provider_swizzle_update(c: &mut Criterion) {
                 let provider = ctx.get_value_provider("baseSongTime.s0_5.x");
                 (ctx, provider)
             },
-            |(mut ctx, provider)| {
+            |(ctx, provider)| {
                 for _ in 0..120 {
                     ctx.update_providers(1.0 / 120.0);
                     black_box(provider.values(&ctx));
@@ -137,6 +171,7 @@ fn bench_provider_swizzle_update(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_provider_cache_and_creation,
+    bench_base_read,
     bench_provider_swizzle_update
 );
 criterion_main!(benches);
@@ -3,6 +3,7 @@ use serde_json::json;
 use std::hint::black_box;
 use tracks_rs::{
     base_provider_context::BaseProviderContext,
+    point_definition::{PointDefinitionLike, vector3_point_definition::Vector3PointDefinition},
     test_helpers::{
         parse_float_point_definition, parse_quaternion_point_definition,
         parse_vector3_point_definition, parse_vector4_point_definition,
@@ -138,5 +139,61 @@ fn bench_json_point_parsing(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_json_point_parsing);
+// A long animation, as found in large maps: parsing from a `Value` against parsing the text
+// directly.
+fn bench_large_point_definition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("json_large_point_definition");
+
+    let points: Vec<_> = (0..10_000)
+        .map(|i| {
+            let t = i as f32 / 10_000.0;
+            json!([t * 2.0, t.sin(), t.cos(), t, "easeInOutSine"])
+        })
+        .collect();
+    let text = serde_json::to_string(&points).unwrap();
+
+    group.bench_function("vec3_from_value", |b| {
+        b.iter_batched(
+            || {
+                (
+                    BaseProviderContext::new(),
+                    serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+                )
+            },
+            |(mut context, value)| {
+                black_box(Vector3PointDefinition::try_parse(&value, &mut context).unwrap());
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("vec3_text_via_value", |b| {
+        b.iter_batched(
+            BaseProviderContext::new,
+            |mut context| {
+                let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+                black_box(Vector3PointDefinition::try_parse(&value, &mut context).unwrap());
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("vec3_from_str", |b| {
+        b.iter_batched(
+            BaseProviderContext::new,
+            |mut context| {
+                black_box(Vector3PointDefinition::try_parse_str(&text, &mut context).unwrap());
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_json_point_parsing,
+    bench_large_point_definition
+);
 criterion_main!(benches);
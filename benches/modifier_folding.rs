@@ -0,0 +1,113 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use glam::{Quat, Vec3};
+use serde_json::{Value, json};
+use std::hint::black_box;
+use tracks_rs::{
+    base_provider_context::BaseProviderContext,
+    base_value::BaseValue,
+    test_helpers::{
+        interpolate_quaternion_point_definition, interpolate_vector3_point_definition,
+        parse_quaternion_point_definition, parse_vector3_point_definition,
+    },
+};
+
+/// A modifier with `depth` levels of nested modifiers, each level scaling, nesting the next and
+/// offsetting. With a static leaf of one this comes out at `(-1.25, -0.5, 0.25)`.
+fn modifier_tree(leaf: Value, depth: usize) -> Value {
+    (0..depth).fold(leaf, |inner, _| {
+        json!([
+            1,
+            2,
+            3,
+            [0.5, 0.5, 0.5, "opMul"],
+            [2, 2, 2, "opDiv"],
+            inner,
+            [1, 1, 1, "opSub"],
+            "opAdd"
+        ])
+    })
+}
+
+fn vec3_definitions() -> [(&'static str, Value); 3] {
+    let tree = |leaf: Value| {
+        let mut point = json!([0, 0, 0, 0]);
+        let points = point.as_array_mut().unwrap();
+        for _ in 0..4 {
+            points.insert(3, modifier_tree(leaf.clone(), 3));
+        }
+        json!([point, [1, 1, 1, 1, "easeInOutSine"]])
+    };
+
+    [
+        (
+            "flat",
+            json!([[-5, -2, 1, 0], [1, 1, 1, 1, "easeInOutSine"]]),
+        ),
+        ("static_tree", tree(json!([1, 1, 1, "opAdd"]))),
+        ("dynamic_tree", tree(json!(["baseHeadPosition", "opAdd"]))),
+    ]
+}
+
+fn quat_definitions() -> [(&'static str, Value); 3] {
+    let tree = |leaf: Value| {
+        json!([
+            [0, 0, 0, 0, modifier_tree(leaf, 3), [0, 10, 0, "opAdd"]],
+            [0, 90, 0, 1]
+        ])
+    };
+
+    [
+        ("flat", json!([[-1.25, 9.5, 0.25, 0], [0, 90, 0, 1]])),
+        ("static_tree", tree(json!([1, 1, 1, "opAdd"]))),
+        ("dynamic_tree", tree(json!(["baseHeadRotation", "opAdd"]))),
+    ]
+}
+
+fn bench_interpolate<D, T>(
+    c: &mut Criterion,
+    group: &str,
+    definitions: [(&'static str, Value); 3],
+    parse: impl Fn(Value, &mut BaseProviderContext) -> D,
+    interpolate: impl Fn(&D, f32, &BaseProviderContext) -> T,
+) {
+    let mut group = c.benchmark_group(group);
+    let mut context = BaseProviderContext::new();
+    context.set_values("baseHeadPosition", BaseValue::from(Vec3::ONE));
+    context.set_values("baseHeadRotation", BaseValue::from(Quat::IDENTITY));
+
+    for (name, json) in definitions {
+        let definition = parse(json, &mut context);
+        group.bench_with_input(
+            BenchmarkId::new("interpolate", name),
+            &definition,
+            |b, d| {
+                b.iter(|| {
+                    for i in 0..=100 {
+                        black_box(interpolate(d, black_box(i as f32 / 100.0), &context));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    bench_interpolate(
+        c,
+        "modifier_folding_vec3",
+        vec3_definitions(),
+        parse_vector3_point_definition,
+        interpolate_vector3_point_definition,
+    );
+    bench_interpolate(
+        c,
+        "modifier_folding_quat",
+        quat_definitions(),
+        parse_quaternion_point_definition,
+        interpolate_quaternion_point_definition,
+    );
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
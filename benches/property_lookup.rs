@@ -3,6 +3,7 @@ use std::hint::black_box;
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use tracks_rs::animation::property::{PathProperty, ValueProperty};
 use tracks_rs::animation::track::{PathPropertyHandle, Track, V2_POSITION, ValuePropertyHandle};
+use tracks_rs::base_provider_context::BaseProviderContext;
 use tracks_rs::base_value::{BaseValue, WrapBaseValueType};
 
 fn make_track(custom_count: usize) -> Track {
@@ -103,5 +104,67 @@ fn bench_property_lookup(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_property_lookup);
+fn make_context(custom_count: usize) -> BaseProviderContext {
+    let mut ctx = BaseProviderContext::new();
+
+    for i in 0..custom_count {
+        ctx.register_base(
+            &format!("baseCustom{i}"),
+            WrapBaseValueType::Float,
+            BaseValue::Float(i as f32),
+        )
+        .unwrap();
+    }
+
+    ctx
+}
+
+fn bench_base_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("base_lookup");
+
+    for &size in &[0usize, 32usize, 256usize] {
+        group.bench_with_input(BenchmarkId::new("get_builtin_str", size), &size, |b, &n| {
+            let ctx = make_context(n);
+            b.iter(|| {
+                black_box(ctx.get_values(black_box("baseRightHandRotation")));
+            });
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("get_builtin_slot", size),
+            &size,
+            |b, &n| {
+                let ctx = make_context(n);
+                let slot = ctx.base_slot("baseRightHandRotation").unwrap();
+                b.iter(|| {
+                    black_box(ctx.get_slot(black_box(slot)));
+                });
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("set_builtin_str", size), &size, |b, &n| {
+            let mut ctx = make_context(n);
+            b.iter(|| {
+                ctx.set_values(black_box("baseSongTime"), BaseValue::Float(black_box(1.0)));
+            });
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("set_builtin_slot", size),
+            &size,
+            |b, &n| {
+                let mut ctx = make_context(n);
+                let slot = ctx.base_slot("baseSongTime").unwrap();
+                b.iter(|| {
+                    ctx.set_slot(black_box(slot), BaseValue::Float(black_box(1.0)))
+                        .unwrap();
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_property_lookup, bench_base_lookup);
 criterion_main!(benches);
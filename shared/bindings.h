@@ -118,6 +118,37 @@ typedef enum JsonValueType {
   Array,
 } JsonValueType;
 
+/**
+ * Why parsing JSON text failed. Mirrors `ParseErrorKind`.
+ */
+typedef enum ParseErrorCode {
+  Success = 0,
+  /**
+   * A required pointer argument was null.
+   */
+  NullArgument,
+  /**
+   * The requested value type is `Unknown`.
+   */
+  UnknownValueType,
+  InvalidJson,
+  NotAnArray,
+  EmptyArray,
+  WrongComponentCount,
+  MissingTime,
+  MissingValues,
+  MissingOperation,
+  UnknownOperation,
+  UnknownBase,
+  RotationOnlyOperation,
+  WrongControlCount,
+  UnsupportedSpline,
+  /**
+   * A `base*` provider couldn't be created for a reason other than an unknown name.
+   */
+  InvalidBase,
+} ParseErrorCode;
+
 typedef struct BaseFFIProviderValues BaseFFIProviderValues;
 
 /**
@@ -134,6 +165,11 @@ typedef struct BasePointDefinition BasePointDefinition;
  *
  * This context is passed to the value providers
  * to get the current base values
+ *
+ * Every context starts with the bases Heck sends (`baseCombo`, `baseHeadPosition`, ...).
+ * Hosts can add their own with [`BaseProviderContext::register_base`], or compute them on demand
+ * with [`BaseProviderContext::register_custom_provider`]. Both can be used in point definitions,
+ * swizzled and smoothed like the built-in ones.
  */
 typedef struct BaseProviderContext BaseProviderContext;
 
@@ -143,8 +179,13 @@ typedef struct BasicPointDefinition_f32 BasicPointDefinition_f32;
 
 typedef struct CoroutineManager CoroutineManager;
 
+/**
+ * An `AnimateTrack` or `AssignPathAnimation` event for a single track and property.
+ */
 typedef struct EventData EventData;
 
+typedef struct Option_BaseFFIProvider Option_BaseFFIProvider;
+
 /**
  * A structure to manage interpolation between two point definitions over time.
  */
@@ -203,6 +244,24 @@ typedef struct WrapBaseValue {
   union WrapBaseValueUnion value;
 } WrapBaseValue;
 
+/**
+ * Counts of the providers a [`BaseProviderContext`] keeps track of.
+ */
+typedef struct ProviderStats {
+  /**
+   * Provider keys in the cache, including smoothing providers that have since been dropped.
+   */
+  uintptr_t cached;
+  /**
+   * Smoothing providers still in use, which `update_providers` ticks.
+   */
+  uintptr_t updatable_alive;
+  /**
+   * Registered smoothing providers, including dropped ones that haven't been pruned yet.
+   */
+  uintptr_t updatable_registered;
+} ProviderStats;
+
 typedef union CEventPropertyId {
   const char *property_str;
   PropertyNames property_name;
@@ -247,6 +306,18 @@ typedef struct FFIJsonValue {
   union JsonValueData data;
 } FFIJsonValue;
 
+/**
+ * Error reported by the functions parsing JSON text.
+ */
+typedef struct FFIParseError {
+  enum ParseErrorCode code;
+  /**
+   * Null-terminated description including the JSON path of the offending value, or null on
+   * success. Free it with `tracks_free_parse_error`.
+   */
+  char *message;
+} FFIParseError;
+
 typedef struct BasicPointDefinition_f32 FloatPointDefinition;
 
 typedef struct FloatInterpolationResult {
@@ -395,12 +466,19 @@ struct BaseProviderContext *base_provider_context_create(void);
 
 /**
  * Destroy a `BaseProviderContext` previously returned by `base_provider_context_create`.
+ *
+ * # Safety
+ * - `ctx` must be a pointer previously returned by `base_provider_context_create` and not already freed.
+ * - Passing a null pointer is a no-op.
  */
 void base_provider_context_destroy(struct BaseProviderContext *ctx);
 
 /**
  * Create a `BaseFFIProviderValues` wrapper from a C function pointer and user value.
  *
+ * Nothing over FFI takes the returned wrapper. Register the callback as a base with
+ * `base_provider_context_register_ffi_provider` instead.
+ *
  * # Safety
  * - `func` must be a valid pointer to a `BaseFFIProvider` function table and not null.
  * - `user_value` is passed through as-is and its ownership remains with the caller.
@@ -410,35 +488,177 @@ struct BaseFFIProviderValues *tracks_make_base_ffi_provider(const BaseFFIProvide
 
 /**
  * Set a base provider value by name. `value` is a `WrapBaseValue` (C layout) converted into `BaseValue`.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `base` must be null or a valid null-terminated C string.
  */
 void base_provider_context_set_value(struct BaseProviderContext *ctx,
                                      const char *base,
                                      struct WrapBaseValue value);
 
+/**
+ * Register a custom base named `base` holding values of type `ty`, starting at `default`.
+ * Point definitions parsed afterwards can reference it, swizzle it and smooth it like a built-in base.
+ *
+ * Returns false if the name is taken or invalid, or if `default` isn't of type `ty`.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `base` must be null or a valid null-terminated C string.
+ */
+bool base_provider_context_register_base(struct BaseProviderContext *ctx,
+                                         const char *base,
+                                         WrapBaseValueType ty,
+                                         struct WrapBaseValue default_);
+
+/**
+ * Register a base named `base` whose values come from calling `fetch` with `user_data` every
+ * time it is sampled. Point definitions can then use it like any other base, e.g. `"baseMyThing.s1"`.
+ *
+ * `fetch` returns `component_count` values (1, 3 or 4); rotations return 3 Euler angles in degrees.
+ * Returns false if the name is taken or invalid, or the component count isn't supported.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `base` must be null or a valid null-terminated C string.
+ * - `fetch` must return a pointer to at least `length` floats that stays valid until it is called again,
+ *   or a null pointer.
+ * - `user_data` is passed through as-is and must stay valid for as long as `ctx` lives.
+ * - With the `sync` feature, `fetch` may be called from any thread sampling `ctx`.
+ */
+bool base_provider_context_register_ffi_provider(struct BaseProviderContext *ctx,
+                                                 const char *base,
+                                                 struct Option_BaseFFIProvider fetch,
+                                                 void *user_data,
+                                                 uintptr_t component_count,
+                                                 bool is_rotation);
+
+/**
+ * Resolve a base name to its slot index for `base_provider_context_set_slot_value` and
+ * `base_provider_context_get_slot_value`. Returns -1 if the base isn't registered.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `base` must be null or a valid null-terminated C string.
+ */
+intptr_t base_provider_context_get_slot(const struct BaseProviderContext *ctx, const char *base);
+
+/**
+ * Set a base provider value by the slot returned from `base_provider_context_get_slot`.
+ *
+ * Returns false if the slot is invalid or `value` has a different type than the base.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ */
+bool base_provider_context_set_slot_value(struct BaseProviderContext *ctx,
+                                          intptr_t slot,
+                                          struct WrapBaseValue value);
+
+/**
+ * Get a base provider value by the slot returned from `base_provider_context_get_slot`.
+ * Returns a zeroed value if the slot is invalid.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ */
+struct WrapBaseValue base_provider_context_get_slot_value(const struct BaseProviderContext *ctx,
+                                                          intptr_t slot);
+
 /**
  * Get a base provider value by name as a `WrapBaseValue`.
- * The returned `WrapBaseValue` points into data owned by `ctx` (via slice pointer), callers must not free it.
+ * Returns a zeroed value if the base isn't registered.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `base` must be null or a valid null-terminated C string.
  */
 struct WrapBaseValue base_provider_context_get_value(const struct BaseProviderContext *ctx,
                                                      const char *base);
 
 /**
  * Get base provider values as a pointer+length pair. The returned `WrappedValues` borrows data from `ctx`.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `base` must be null or a valid null-terminated C string.
+ * - The returned pointer is only valid while `ctx` is alive and not mutated.
  */
 struct WrappedValues base_provider_context_get_values_array(const struct BaseProviderContext *ctx,
                                                             const char *base);
 
 /**
- * Get the type of the base provider value for `base` (Vec3/Quat/Vec4/Float)
+ * Get the type of the base provider value for `base` (Vec3/Quat/Vec4/Float), or `Unknown` if it isn't registered
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `base` must be null or a valid null-terminated C string.
  */
 WrapBaseValueType base_provider_context_get_type(const struct BaseProviderContext *ctx,
                                                  const char *base);
 
 /**
  * Call `update_providers` on the `BaseProviderContext` with a delta time.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
  */
 void base_provider_context_update(struct BaseProviderContext *ctx, float delta);
 
+/**
+ * Forget every provider created so far, e.g. when unloading a map. Point definitions parsed before
+ * keep working, but their smoothing providers are no longer updated.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ */
+void base_provider_context_clear_providers(struct BaseProviderContext *ctx);
+
+/**
+ * Remove smoothing providers that are no longer used by any point definition.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ */
+void base_provider_context_prune_providers(struct BaseProviderContext *ctx);
+
+/**
+ * Get counts of the cached and updatable providers. Returns zeroes if `ctx` is null.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ */
+struct ProviderStats base_provider_context_get_provider_stats(const struct BaseProviderContext *ctx);
+
+/**
+ * Start recording every base write and `base_provider_context_update` call, e.g. to
+ * attach to a bug report. Restarts the recording if one is running.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ */
+void base_provider_context_start_recording(struct BaseProviderContext *ctx);
+
+/**
+ * Stop recording and return the encoded recording, writing its length to `out_len`. Returns null
+ * if nothing was being recorded. Free the bytes with `tracks_free_recording`.
+ *
+ * # Safety
+ * - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+ * - `out_len` must be a valid pointer to a `usize`.
+ */
+uint8_t *base_provider_context_stop_recording(struct BaseProviderContext *ctx, uintptr_t *out_len);
+
+/**
+ * Free bytes returned by `base_provider_context_stop_recording`.
+ *
+ * # Safety
+ * - `data` must be null or a pointer returned by `base_provider_context_stop_recording`, and
+ *   `len` the length it wrote. It must not be freed twice.
+ */
+void tracks_free_recording(uint8_t *data, uintptr_t len);
+
 /**
  * Creates a new CoroutineManager instance and returns a raw pointer to it.
  * The caller is responsible for freeing the memory using destroy_coroutine_manager.
@@ -547,14 +767,26 @@ struct FFIJsonValue tracks_create_json_array(const struct FFIJsonValue *elements
                                              uintptr_t length);
 
 /**
- * Free an `FFIJsonValue` previously produced that may contain heap allocations.
+ * Free the arrays allocated by `tracks_create_json_array` for an `FFIJsonValue`, including the
+ * ones nested inside its elements.
  *
  * # Safety
- * - `json_value` must point to an `FFIJsonValue` produced by the corresponding create functions and not already freed.
- * - This implementation only frees the top-level `JsonArray` if present; nested structures are not recursively freed.
+ * - `json_value` must be null or point to an `FFIJsonValue` built with the create functions whose
+ *   arrays have not already been freed.
+ * - The `FFIJsonValue` itself and the element buffers passed to `tracks_create_json_array` stay
+ *   owned by the caller. The top-level array pointer is reset to null, so freeing twice is a no-op.
  */
 void tracks_free_json_value(struct FFIJsonValue *json_value);
 
+/**
+ * Free the message of an `FFIParseError` filled in by a parsing function.
+ *
+ * # Safety
+ * - `error` must be null or point to an `FFIParseError` written by this library.
+ * - The message pointer is reset to null, so freeing twice is a no-op.
+ */
+void tracks_free_parse_error(struct FFIParseError *error);
+
 /**
  * BASE POINT DEFINITION
  *
@@ -568,6 +800,28 @@ struct BasePointDefinition *tracks_make_base_point_definition(const struct FFIJs
                                                               WrapBaseValueType ty,
                                                               struct BaseProviderContext *context);
 
+/**
+ * Parse a base point definition from JSON text.
+ *
+ * `json` points to `len` bytes of UTF-8 JSON and does not need to be null-terminated, so hosts
+ * can pass a slice of a larger buffer such as the beatmap file. Prefer this over building an
+ * `FFIJsonValue` tree for `tracks_make_base_point_definition`.
+ *
+ * Returns null on failure. `out_error` then receives the error code and a message that must be
+ * freed with `tracks_free_parse_error`; on success it is set to `Success` with a null message.
+ *
+ * # Safety
+ * - `context` must be a valid, non-null pointer to a live `BaseProviderContext` for the duration of this call.
+ * - `json` must point to at least `len` readable bytes.
+ * - `out_error` may be null; if non-null it must be valid for writes.
+ * - The returned pointer is owned by the caller and must be freed by calling `base_point_definition_free`.
+ */
+struct BasePointDefinition *tracks_parse_base_point_definition_str(struct BaseProviderContext *context,
+                                                                   const char *json,
+                                                                   uintptr_t len,
+                                                                   WrapBaseValueType ty,
+                                                                   struct FFIParseError *out_error);
+
 /**
  * BASE POINT DEFINITION FREE
  *
@@ -596,7 +850,7 @@ struct WrapBaseValue tracks_interpolate_base_point_definition(const struct BaseP
 /**
  * Return number of points in the point definition.
  *
- * Safety:
+ * # Safety
  * - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
  */
 uintptr_t tracks_base_point_definition_count(const struct BasePointDefinition *point_definition);
@@ -604,14 +858,25 @@ uintptr_t tracks_base_point_definition_count(const struct BasePointDefinition *p
 /**
  * Check whether the point definition references a base provider.
  *
- * Safety:
+ * # Safety
  * - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
  */
 bool tracks_base_point_definition_has_base_provider(const struct BasePointDefinition *point_definition);
 
+/**
+ * Check whether sampling the point definition reads `base`, directly or through swizzles,
+ * smoothing or velocity. Hosts can skip updating bases no point definition reads.
+ *
+ * # Safety
+ * - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
+ * - `base` must be null or a valid null-terminated C string.
+ */
+bool tracks_base_point_definition_reads_base(const struct BasePointDefinition *point_definition,
+                                             const char *base);
+
 /**
  * Get the `WrapBaseValueType` of the point definition.
- * Safety:
+ * # Safety
  * - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
  */
 WrapBaseValueType tracks_base_point_definition_get_type(const struct BasePointDefinition *point_definition);
@@ -750,8 +1015,16 @@ bool tracks_vector4_has_base_provider(const Vector4PointDefinition *point_defini
 
 PathProperty *path_property_create(void);
 
+/**
+ * # Safety
+ * - `ptr` must be null or a valid pointer to a `PathProperty`.
+ */
 void path_property_finish(PathProperty *ptr);
 
+/**
+ * # Safety
+ * - `ptr` must be null or a valid null-terminated C string.
+ */
 PropertyNames string_to_property_name(const char *ptr);
 
 /**
@@ -969,7 +1242,7 @@ PathProperty *track_get_path_property(struct Track *track, const char *id);
 /**
  * Return a `CPropertiesMap` with pointers into the track's registered properties.
  *
- * Safety:
+ * # Safety
  * - `track` must be a valid, non-null pointer to a `Track`.
  * - The returned pointers are valid only while the `Track` is alive and not mutated in a way that moves or removes the properties.
  * - Do not retain these pointers across calls that might mutate the track.
@@ -979,7 +1252,7 @@ struct CPropertiesMap track_get_properties_map(struct Track *track);
 /**
  * Return a `CPathPropertiesMap` with pointers into the track's path properties.
  *
- * Safety:
+ * # Safety
  * - `track` must be a valid, non-null pointer to a `Track`.
  * - Returned pointers are valid only while the track's path properties remain in-place.
  */
@@ -987,7 +1260,7 @@ struct CPathPropertiesMap track_get_path_properties_map(struct Track *track);
 
 /**
  * Return a `CPropertiesValues` with the current values of the track's properties.
- * Safety:
+ * # Safety
  * - `track` must be a valid, non-null pointer to a `Track
  * - The returned struct contains copies of the current property values.
  */
@@ -995,7 +1268,7 @@ struct CPropertiesValues track_get_properties_values(struct Track *track);
 
 /**
  * Return a `CPathPropertiesValues` with the interpolated values of the track's path properties at the given time.
- * Safety:
+ * # Safety
  * - `track` must be a valid, non-null pointer to a `Track`.
  * - `ctx` must be a valid, non-null pointer to a `BaseProviderContext`.
  *
@@ -1009,7 +1282,7 @@ struct CPathPropertiesValues track_get_path_properties_values(struct Track *trac
 /**
  * Register a C callback to be invoked when a game object is added/removed.
  *
- * Safety:
+ * # Safety
  * - `track` must be a valid pointer to a `Track`.
  * - `callback` and `user_data` must remain valid for as long as the callback may be invoked.
  * - The returned pointer is an opaque handle to the stored Rust closure; it must be removed with `track_remove_game_object_callback`.
@@ -1024,7 +1297,7 @@ void (**track_register_game_object_callback(struct Track *track,
 /**
  * Remove a previously registered game object callback.
  *
- * Safety:
+ * # Safety
  * - `track` must be a valid pointer to a `Track`.
  * - `callback` must be a pointer previously returned by `track_register_game_object_callback`.
  * - After calling this function the `callback` pointer must not be used again.
@@ -1039,39 +1312,67 @@ struct TracksHolder *tracks_holder_create(void);
 
 /**
  * Destroy a `TracksHolder` previously returned by `tracks_holder_create`.
+ *
+ * # Safety
+ * - `holder` must be a pointer previously returned by `tracks_holder_create` and not already freed.
+ * - Passing a null pointer is a no-op.
  */
 void tracks_holder_destroy(struct TracksHolder *holder);
 
 /**
  * Add a `Track` to the holder. Takes ownership of the `Track` pointer passed in.
  * Returns a `TrackKeyFFI` identifying the inserted track, or null-equivalent on error.
+ *
+ * # Safety
+ * - `holder` must be null or a valid pointer to a `TracksHolder`.
+ * - `track` must be null or a pointer to a heap allocated `Track` (e.g. from `track_create_named`); ownership is transferred to the holder.
  */
-struct TrackKeyFFI tracks_holder_add_track(struct TracksHolder *holder, struct Track *track);
+struct TrackKeyFFI tracks_holder_add_track(struct TracksHolder *holder,
+                                           struct Track *track);
 
 /**
  * Get an immutable pointer to a `Track` by `TrackKeyFFI`.
+ *
+ * # Safety
+ * - `holder` must be null or a valid pointer to a `TracksHolder`.
+ * - The returned pointer is only valid while the track remains in the holder.
  */
 const struct Track *tracks_holder_get_track(const struct TracksHolder *holder,
                                             struct TrackKeyFFI key);
 
 /**
  * Get a mutable pointer to a `Track` by `TrackKeyFFI`.
+ *
+ * # Safety
+ * - `holder` must be null or a valid pointer to a `TracksHolder`.
+ * - The returned pointer is only valid while the track remains in the holder.
  */
 struct Track *tracks_holder_get_track_mut(struct TracksHolder *holder, struct TrackKeyFFI key);
 
 /**
  * Look up a track by name and return a pointer to it (const).
+ *
+ * # Safety
+ * - `holder` must be null or a valid pointer to a `TracksHolder`.
+ * - `name` must be null or a valid null-terminated C string.
  */
 const struct Track *tracks_holder_get_track_by_name(const struct TracksHolder *holder,
                                                     const char *name);
 
 /**
  * Get the `TrackKeyFFI` for a track with the given name, or null-equivalent if not found.
+ *
+ * # Safety
+ * - `holder` must be null or a valid pointer to a `TracksHolder`.
+ * - `name` must be null or a valid null-terminated C string.
  */
 struct TrackKeyFFI tracks_holder_get_track_key(struct TracksHolder *holder, const char *name);
 
 /**
  * Return number of tracks in the holder.
+ *
+ * # Safety
+ * - `holder` must be null or a valid pointer to a `TracksHolder`.
  */
 uintptr_t tracks_holder_count(const struct TracksHolder *holder);
 
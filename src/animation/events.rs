@@ -7,6 +7,13 @@ use crate::{
     point_definition::base_point_definition::{self},
 };
 
+#[cfg(feature = "serde")]
+use crate::{
+    base_provider_context::BaseProviderContext, base_value::WrapBaseValueType,
+    point_definition::PointDefinitionLike,
+};
+
+/// An `AnimateTrack` or `AssignPathAnimation` event for a single track and property.
 #[derive(Debug, Clone)]
 pub struct EventData {
     /// duration in beatmap time
@@ -22,7 +29,112 @@ pub struct EventData {
 }
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventType {
     AnimateTrack(ValuePropertyHandle),
     AssignPathAnimation(PathPropertyHandle),
 }
+
+/// Deserializes an [`EventData`], registering its providers in the given context.
+#[cfg(feature = "serde")]
+pub struct EventDataSeed<'a>(pub &'a mut BaseProviderContext);
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "EventData")]
+struct EventDataRepr {
+    raw_duration: f32,
+    easing: Functions,
+    repeat: u32,
+    start_song_time: f32,
+    property: EventType,
+    track_key: TrackKey,
+    point_data: Option<PointDefinitionRepr>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "PointDefinition")]
+struct PointDefinitionRepr {
+    r#type: WrapBaseValueType,
+    points: serde_json::Value,
+}
+
+#[cfg(feature = "serde")]
+impl EventDataRepr {
+    fn into_event_data(self, context: &mut BaseProviderContext) -> Result<EventData, String> {
+        let point_data = match self.point_data {
+            Some(PointDefinitionRepr {
+                r#type: WrapBaseValueType::Unknown,
+                ..
+            }) => return Err("point definition has Unknown type".to_string()),
+            Some(repr) => Some(
+                base_point_definition::BasePointDefinition::try_parse_typed(
+                    &repr.points,
+                    repr.r#type,
+                    context,
+                )
+                .map_err(|e| format!("point definition: {e}"))?,
+            ),
+            None => None,
+        };
+
+        Ok(EventData {
+            raw_duration: self.raw_duration,
+            easing: self.easing,
+            repeat: self.repeat,
+            start_song_time: self.start_song_time,
+            property: self.property,
+            track_key: self.track_key,
+            point_data,
+        })
+    }
+}
+
+/// The point definition is serialized as Heck JSON together with its value type.
+#[cfg(feature = "serde")]
+impl serde::Serialize for EventData {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EventDataRepr {
+            raw_duration: self.raw_duration,
+            easing: self.easing,
+            repeat: self.repeat,
+            start_song_time: self.start_song_time,
+            property: self.property.clone(),
+            track_key: self.track_key,
+            point_data: self.point_data.as_ref().map(|p| PointDefinitionRepr {
+                r#type: p.get_type(),
+                points: p.to_json(),
+            }),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Parses the point definition against a fresh [`BaseProviderContext`]. Use [`EventDataSeed`] to
+/// parse it against the host's context instead, so smoothed providers are updated by
+/// [`BaseProviderContext::update_providers`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EventData {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::DeserializeSeed;
+
+        EventDataSeed(&mut BaseProviderContext::new()).deserialize(deserializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::DeserializeSeed<'de> for EventDataSeed<'_> {
+    type Value = EventData;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<EventData, D::Error> {
+        use serde::Deserialize;
+
+        EventDataRepr::deserialize(deserializer)?
+            .into_event_data(self.0)
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -6,6 +6,12 @@ pub struct GameObject {
     pub ptr: *const c_void,
 }
 
+// SAFETY: the pointer is an opaque handle owned by the host, tracks never dereference it
+#[cfg(feature = "sync")]
+unsafe impl Send for GameObject {}
+#[cfg(feature = "sync")]
+unsafe impl Sync for GameObject {}
+
 impl From<*const c_void> for GameObject {
     fn from(value: *const c_void) -> Self {
         GameObject { ptr: value }
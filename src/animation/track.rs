@@ -1,8 +1,9 @@
-use std::{fmt::Display, rc::Rc, str::FromStr};
+use std::{fmt::Display, str::FromStr};
 
 use crate::{
     animation::property::{PathProperty, ValueProperty},
     base_value::WrapBaseValueType,
+    shared::Shared,
 };
 
 use super::game_object::GameObject;
@@ -47,12 +48,14 @@ pub const V2_HEIGHT_FOG_START_Y: &str = "_heightFogStartY";
 pub const V2_HEIGHT_FOG_HEIGHT: &str = "_heightFogHeight";
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValuePropertyHandle {
     ByName(String),
     ById(PropertyNames),
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PathPropertyHandle {
     ByName(String),
     ById(PropertyNames),
@@ -154,7 +157,11 @@ pub struct PropertiesMap {
 
 /// A GameObjectCallback is a function that gets called when a game object is added or removed from a Track.
 /// bool is true if added, false if removed.
+#[cfg(not(feature = "sync"))]
 pub trait GameObjectCallback = Fn(GameObject, bool);
+/// With the `sync` feature it must also be `Send + Sync`.
+#[cfg(feature = "sync")]
+pub trait GameObjectCallback = Fn(GameObject, bool) + Send + Sync;
 
 /// A Track represents a collection of properties and path properties associated with game objects.
 /// It allows registering, retrieving, and managing properties and game objects.
@@ -167,10 +174,18 @@ pub struct Track {
 
     // hashset but must be insertion ordered
     game_objects: Vec<GameObject>,
-    game_object_callbacks: Vec<Rc<dyn GameObjectCallback>>,
+    game_object_callbacks: Vec<Shared<dyn GameObjectCallback>>,
 }
 
 impl Track {
+    /// An empty track with the given name.
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
     pub fn register_property(&mut self, id: String, property: ValueProperty) {
         self.properties.insert(id, property);
     }
@@ -189,7 +204,7 @@ impl Track {
             .for_each(|callback| callback(game_object, true));
     }
 
-    pub fn register_game_object_callback(&mut self, callback: Rc<dyn GameObjectCallback>) {
+    pub fn register_game_object_callback(&mut self, callback: Shared<dyn GameObjectCallback>) {
         self.game_object_callbacks.push(callback);
     }
 
@@ -222,10 +237,10 @@ impl Track {
             .for_each(|callback| callback(*game_object, false));
     }
 
-    pub fn remove_game_object_callback(&mut self, callback: Rc<dyn GameObjectCallback>) {
-        let callback_ref: Rc<dyn GameObjectCallback> = callback;
+    pub fn remove_game_object_callback(&mut self, callback: Shared<dyn GameObjectCallback>) {
+        let callback_ref: Shared<dyn GameObjectCallback> = callback;
         self.game_object_callbacks
-            .retain(|cb| !Rc::ptr_eq(cb, &callback_ref));
+            .retain(|cb| !Shared::ptr_eq(cb, &callback_ref));
     }
 
     pub fn reset(&mut self) {
@@ -529,6 +544,26 @@ impl Display for PropertyNames {
     }
 }
 
+/// Serialized as its [`Display`] name, e.g. `"offsetPosition"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PropertyNames {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PropertyNames {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "Unknown" => Ok(PropertyNames::UnknownPropertyName),
+            _ => PropertyNames::from_str(&name)
+                .map_err(|_| serde::de::Error::custom(format!("unknown property \"{name}\""))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{thread::sleep, time::Duration};
@@ -695,12 +730,13 @@ mod tests {
     #[test]
     fn game_object_register_remove_and_callbacks() {
         use crate::animation::game_object::GameObject;
-        use std::cell::RefCell;
+        use crate::shared::{Shared, StateCell};
         use std::ffi::c_void;
-        use std::rc::Rc;
 
-        let mut track = Track::default();
-        track.name = "g_track".to_string();
+        let mut track = Track {
+            name: "g_track".to_string(),
+            ..Default::default()
+        };
 
         // create a unique pointer for GameObject
         let p = Box::into_raw(Box::new(42)) as *const c_void;
@@ -710,9 +746,9 @@ mod tests {
         assert!(!track.game_objects.contains(&go));
 
         // callback tracking (add/remove)
-        let called = Rc::new(RefCell::new((false, false)));
+        let called = Shared::new(StateCell::new((false, false)));
         let called_cb = called.clone();
-        let cb = Rc::new(move |_g: GameObject, added: bool| {
+        let cb = Shared::new(move |_g: GameObject, added: bool| {
             let mut v = called_cb.borrow_mut();
             if added {
                 v.0 = true;
@@ -747,9 +783,8 @@ mod tests {
     #[test]
     fn register_and_remove_game_object_callback_works() {
         use crate::animation::game_object::GameObject;
-        use std::cell::RefCell;
+        use crate::shared::{Shared, StateCell};
         use std::ffi::c_void;
-        use std::rc::Rc;
 
         let mut track = Track::default();
 
@@ -759,17 +794,17 @@ mod tests {
         let go1 = GameObject::from(p1);
         let go2 = GameObject::from(p2);
 
-        let cb1_called = Rc::new(RefCell::new(0usize));
-        let cb2_called = Rc::new(RefCell::new(0usize));
+        let cb1_called = Shared::new(StateCell::new(0usize));
+        let cb2_called = Shared::new(StateCell::new(0usize));
 
-        let cb1 = Rc::new({
+        let cb1 = Shared::new({
             let c = cb1_called.clone();
             move |_g: GameObject, _added: bool| {
                 *c.borrow_mut() += 1;
             }
         });
 
-        let cb2 = Rc::new({
+        let cb2 = Shared::new({
             let c = cb2_called.clone();
             move |_g: GameObject, _added: bool| {
                 *c.borrow_mut() += 1;
@@ -802,9 +837,8 @@ mod tests {
     #[test]
     fn callbacks_and_gameobjects_preserve_order() {
         use crate::animation::game_object::GameObject;
-        use std::cell::RefCell;
+        use crate::shared::{Shared, StateCell};
         use std::ffi::c_void;
-        use std::rc::Rc;
 
         let mut track = Track::default();
 
@@ -817,18 +851,18 @@ mod tests {
         let go3 = GameObject::from(p3);
 
         // record order of callback invocations (by pointer value)
-        let calls = Rc::new(RefCell::new(Vec::new()));
+        let calls = Shared::new(StateCell::new(Vec::new()));
 
         let c1 = {
             let calls = calls.clone();
-            Rc::new(move |g: GameObject, _added: bool| {
+            Shared::new(move |g: GameObject, _added: bool| {
                 calls.borrow_mut().push(g.ptr as usize);
             })
         };
 
         let c2 = {
             let calls = calls.clone();
-            Rc::new(move |g: GameObject, _added: bool| {
+            Shared::new(move |g: GameObject, _added: bool| {
                 calls.borrow_mut().push(g.ptr as usize + 1000); // offset to distinguish
             })
         };
@@ -883,18 +917,17 @@ mod tests {
     #[test]
     fn register_remove_multiple_times_invokes_callbacks_each_time() {
         use crate::animation::game_object::GameObject;
-        use std::cell::RefCell;
+        use crate::shared::{Shared, StateCell};
         use std::ffi::c_void;
-        use std::rc::Rc;
 
         let mut track = Track::default();
 
         let p = Box::into_raw(Box::new(55)) as *const c_void;
         let go = GameObject::from(p);
 
-        let calls = Rc::new(RefCell::new(0usize));
+        let calls = Shared::new(StateCell::new(0usize));
         let calls_clone = calls.clone();
-        let cb = Rc::new(move |_g: GameObject, _added: bool| {
+        let cb = Shared::new(move |_g: GameObject, _added: bool| {
             *calls_clone.borrow_mut() += 1;
         });
 
@@ -62,4 +62,8 @@ impl TracksHolder {
     pub fn len(&self) -> usize {
         self.tracks.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
 }
@@ -1,69 +1,257 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{collections::HashMap, fmt};
 
 use glam::{Quat, Vec3, Vec4};
 use log::{error, warn};
 
 use crate::{
-    base_value::BaseValue,
+    base_value::{BaseValue, WrapBaseValueType},
     prelude::AbstractValueProvider,
     providers::{
-        UpdateableValues, ValueProvider, base::BaseProviderValues, partial::PartialProviderValues,
-        smooth::SmoothProvidersValues, smooth_rot::SmoothRotationProvidersValues,
+        UpdateableValues, ValueProvider,
+        base::BaseProviderValues,
+        custom::{CustomProvider, CustomProviderValues},
+        generator::{GeneratorKind, GeneratorParam, GeneratorProviderValues},
+        partial::PartialProviderValues,
+        smooth::{SmoothKind, SmoothProvidersValues},
+        smooth_rot::SmoothRotationProvidersValues,
+        velocity::VelocityProviderValues,
     },
+    recording::Recording,
+    shared::{Shared, SharedWeak, StateCell},
 };
 
+/// The bases every context starts with, as sent by Heck.
+const BUILTIN_BASES: &[(&str, WrapBaseValueType)] = &[
+    //Score
+    ("baseCombo", WrapBaseValueType::Float),
+    ("baseMultipliedScore", WrapBaseValueType::Float),
+    (
+        "baseImmediateMaxPossibleMultipliedScore",
+        WrapBaseValueType::Float,
+    ),
+    ("baseModifiedScore", WrapBaseValueType::Float),
+    (
+        "baseImmediateMaxPossibleModifiedScore",
+        WrapBaseValueType::Float,
+    ),
+    ("baseRelativeScore", WrapBaseValueType::Float),
+    ("baseMultiplier", WrapBaseValueType::Float),
+    ("baseEnergy", WrapBaseValueType::Float),
+    ("baseSongTime", WrapBaseValueType::Float),
+    ("baseSongLength", WrapBaseValueType::Float),
+    //Colors
+    ("baseEnvironmentColor0", WrapBaseValueType::Vec4),
+    ("baseEnvironmentColor0Boost", WrapBaseValueType::Vec4),
+    ("baseEnvironmentColor1", WrapBaseValueType::Vec4),
+    ("baseEnvironmentColor1Boost", WrapBaseValueType::Vec4),
+    ("baseEnvironmentColorW", WrapBaseValueType::Vec4),
+    ("baseEnvironmentColorWBoost", WrapBaseValueType::Vec4),
+    ("baseNote0Color", WrapBaseValueType::Vec4),
+    ("baseNote1Color", WrapBaseValueType::Vec4),
+    ("baseObstaclesColor", WrapBaseValueType::Vec4),
+    ("baseSaberAColor", WrapBaseValueType::Vec4),
+    ("baseSaberBColor", WrapBaseValueType::Vec4),
+    //Transforms
+    ("baseHeadLocalPosition", WrapBaseValueType::Vec3),
+    ("baseHeadLocalRotation", WrapBaseValueType::Quat),
+    ("baseHeadLocalScale", WrapBaseValueType::Vec3),
+    ("baseHeadPosition", WrapBaseValueType::Vec3),
+    ("baseHeadRotation", WrapBaseValueType::Quat),
+    ("baseLeftHandLocalPosition", WrapBaseValueType::Vec3),
+    ("baseLeftHandLocalRotation", WrapBaseValueType::Quat),
+    ("baseLeftHandLocalScale", WrapBaseValueType::Vec3),
+    ("baseLeftHandPosition", WrapBaseValueType::Vec3),
+    ("baseLeftHandRotation", WrapBaseValueType::Quat),
+    ("baseRightHandLocalPosition", WrapBaseValueType::Vec3),
+    ("baseRightHandLocalRotation", WrapBaseValueType::Quat),
+    ("baseRightHandLocalScale", WrapBaseValueType::Vec3),
+    ("baseRightHandPosition", WrapBaseValueType::Vec3),
+    ("baseRightHandRotation", WrapBaseValueType::Quat),
+];
+
+/// Error returned when registering, reading or writing a base.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BaseError {
+    /// No base with this name is registered.
+    UnknownBase(String),
+    /// A base with this name is already registered.
+    AlreadyRegistered(String),
+    /// Base names must start with `base` and can't contain `.`.
+    InvalidName(String),
+    /// The value doesn't have the type the base was registered with.
+    TypeMismatch {
+        base: String,
+        expected: WrapBaseValueType,
+        found: WrapBaseValueType,
+    },
+    /// Custom providers have 1, 3 or 4 components, and rotations 3.
+    InvalidComponentCount {
+        base: String,
+        count: usize,
+        is_rotation: bool,
+    },
+    /// The base is a custom provider, its values come from the host.
+    ReadOnly(String),
+}
+
+impl fmt::Display for BaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaseError::UnknownBase(base) => write!(f, "unknown base provider \"{base}\""),
+            BaseError::AlreadyRegistered(base) => {
+                write!(f, "base provider \"{base}\" is already registered")
+            }
+            BaseError::InvalidName(base) => write!(
+                f,
+                "invalid base provider name \"{base}\", names must start with \"base\" and can't contain '.'"
+            ),
+            BaseError::TypeMismatch {
+                base,
+                expected,
+                found,
+            } => write!(
+                f,
+                "base provider \"{base}\" holds {expected:?} values, got {found:?}"
+            ),
+            BaseError::InvalidComponentCount {
+                base,
+                count,
+                is_rotation,
+            } => write!(
+                f,
+                "custom provider \"{base}\" can't have {count} components{}",
+                if *is_rotation { " as a rotation" } else { "" }
+            ),
+            BaseError::ReadOnly(base) => write!(
+                f,
+                "base provider \"{base}\" is a custom provider and can't be set"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BaseError {}
+
+/// Index of a registered base in its [`BaseProviderContext`], resolved once by name with
+/// [`BaseProviderContext::base_slot`] so reads and writes skip the name lookup.
+///
+/// Slots are only meaningful for the context that resolved them and its clones. The built-in
+/// bases have the same slots in every context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BaseSlot(usize);
+
+impl BaseSlot {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
 /// Context for base value providers
 /// Holds all the base values that can be accessed
 /// by base value providers
 ///
 /// This context is passed to the value providers
 /// to get the current base values
-#[derive(Default, Clone)]
+///
+/// Every context starts with the bases Heck sends (`baseCombo`, `baseHeadPosition`, ...).
+/// Hosts can add their own with [`BaseProviderContext::register_base`], or compute them on demand
+/// with [`BaseProviderContext::register_custom_provider`]. Both can be used in point definitions,
+/// swizzled and smoothed like the built-in ones.
+#[derive(Clone)]
 pub struct BaseProviderContext {
-    //Score
-    base_combo: f32,
-    multiplied_score: f32,
-    immediate_max_possible_multiplied_score: f32,
-    modified_score: f32,
-    immediate_max_possible_modified_score: f32,
-    relative_score: f32,
-    multiplier: f32,
-    energy: f32,
-    song_time: f32,
-    song_length: f32,
+    /// Current value of every registered base, indexed by [`BaseSlot`]. Its type never changes
+    /// after registration.
+    bases: Vec<BaseValue>,
+    base_names: Vec<String>,
+    base_indices: HashMap<String, usize>,
+    custom_providers: HashMap<String, CustomProviderValues>,
+
+    /// Smoothing providers ticked by `update_providers`. Held weakly so they stop updating once
+    /// no point definition uses them.
+    updatable_providers: Vec<SharedWeak<StateCell<dyn UpdateableValues>>>,
+    /// Length of `updatable_providers` after it was last pruned.
+    updatable_pruned_len: usize,
+    provider_cache: HashMap<String, CachedProvider>,
+    /// Set while recording; `update_providers` only takes `&self`, hence the cell.
+    recording: Option<StateCell<Recording>>,
+}
 
-    //Colors
-    environment_color_0: Vec4,
-    environment_color_0_boost: Vec4,
-    environment_color_1: Vec4,
-    environment_color_1_boost: Vec4,
-    environment_color_w: Vec4,
-    environment_color_w_boost: Vec4,
-    note_color_0: Vec4,
-    note_color_1: Vec4,
-    obstacles_color: Vec4,
-    saber_color_a: Vec4,
-    saber_color_b: Vec4,
+/// Counts of the providers a [`BaseProviderContext`] keeps track of.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProviderStats {
+    /// Provider keys in the cache, including smoothing providers that have since been dropped.
+    pub cached: usize,
+    /// Smoothing providers still in use, which `update_providers` ticks.
+    pub updatable_alive: usize,
+    /// Registered smoothing providers, including dropped ones that haven't been pruned yet.
+    pub updatable_registered: usize,
+}
 
-    //Transforms
-    head_local_position: Vec3,
-    head_local_rotation: Quat,
-    head_local_scale: Vec3,
-    head_position: Vec3,
-    head_rotation: Quat,
-    left_hand_local_position: Vec3,
-    left_hand_local_rotation: Quat,
-    left_hand_local_scale: Vec3,
-    left_hand_position: Vec3,
-    left_hand_rotation: Quat,
-    right_hand_local_position: Vec3,
-    right_hand_local_rotation: Quat,
-    right_hand_local_scale: Vec3,
-    right_hand_position: Vec3,
-    right_hand_rotation: Quat,
-
-    updatable_providers: Vec<Rc<RefCell<dyn UpdateableValues>>>,
-    provider_cache: HashMap<String, ValueProvider>,
+/// A provider in the cache. Smoothing providers are held weakly so the cache doesn't keep them
+/// alive after the point definitions using them are dropped.
+#[derive(Clone)]
+enum CachedProvider {
+    Stateless(ValueProvider),
+    Smooth(SharedWeak<StateCell<SmoothProvidersValues>>),
+    SmoothRotation(SharedWeak<StateCell<SmoothRotationProvidersValues>>),
+    Velocity(SharedWeak<StateCell<VelocityProviderValues>>),
+}
+
+impl CachedProvider {
+    fn new(provider: &ValueProvider) -> Option<Self> {
+        match provider {
+            ValueProvider::SmoothProviders(v) => Some(CachedProvider::Smooth(Shared::downgrade(v))),
+            ValueProvider::SmoothRotationProviders(v) => {
+                Some(CachedProvider::SmoothRotation(Shared::downgrade(v)))
+            }
+            ValueProvider::Velocity(v) => Some(CachedProvider::Velocity(Shared::downgrade(v))),
+            // Swizzles of smoothed values are cheap to rebuild from the cached smoothing provider
+            provider if provider.is_stateful() => None,
+            provider => Some(CachedProvider::Stateless(provider.clone())),
+        }
+    }
+
+    fn get(&self) -> Option<ValueProvider> {
+        match self {
+            CachedProvider::Stateless(provider) => Some(provider.clone()),
+            CachedProvider::Smooth(v) => v.upgrade().map(ValueProvider::SmoothProviders),
+            CachedProvider::SmoothRotation(v) => {
+                v.upgrade().map(ValueProvider::SmoothRotationProviders)
+            }
+            CachedProvider::Velocity(v) => v.upgrade().map(ValueProvider::Velocity),
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        match self {
+            CachedProvider::Stateless(_) => true,
+            CachedProvider::Smooth(v) => v.strong_count() > 0,
+            CachedProvider::SmoothRotation(v) => v.strong_count() > 0,
+            CachedProvider::Velocity(v) => v.strong_count() > 0,
+        }
+    }
+}
+
+impl Default for BaseProviderContext {
+    fn default() -> Self {
+        let mut context = Self {
+            bases: Vec::with_capacity(BUILTIN_BASES.len()),
+            base_names: Vec::with_capacity(BUILTIN_BASES.len()),
+            base_indices: HashMap::with_capacity(BUILTIN_BASES.len()),
+            custom_providers: HashMap::new(),
+            updatable_providers: Vec::new(),
+            updatable_pruned_len: 0,
+            provider_cache: HashMap::new(),
+            recording: None,
+        };
+        for &(name, ty) in BUILTIN_BASES {
+            context
+                .register_base(name, ty, default_value(ty))
+                .expect("built-in bases are valid");
+        }
+        context
+    }
 }
 
 impl BaseProviderContext {
@@ -71,169 +259,171 @@ impl BaseProviderContext {
         Default::default()
     }
 
-    pub fn get_values(&self, base: &str) -> BaseValue {
-        match base {
-            "baseCombo" => self.base_combo.into(),
+    /// Registers a new base named `name` holding values of type `ty`, starting at `default`.
+    ///
+    /// Fails if the name is taken, doesn't start with `base` or contains `.`, or if `default`
+    /// isn't of type `ty`.
+    pub fn register_base(
+        &mut self,
+        name: &str,
+        ty: WrapBaseValueType,
+        default: BaseValue,
+    ) -> Result<(), BaseError> {
+        self.check_new_name(name)?;
+        if default.get_type() != ty {
+            return Err(BaseError::TypeMismatch {
+                base: name.to_string(),
+                expected: ty,
+                found: default.get_type(),
+            });
+        }
 
-            "baseMultipliedScore" => self.multiplied_score.into(),
-            "baseImmediateMaxPossibleMultipliedScore" => {
-                self.immediate_max_possible_multiplied_score.into()
-            }
+        self.base_indices.insert(name.to_string(), self.bases.len());
+        self.base_names.push(name.to_string());
+        self.bases.push(default);
+        // So replays register it too
+        if let Some(recording) = &mut self.recording {
+            recording.get_mut().record_set(name, default);
+        }
+        Ok(())
+    }
 
-            "baseModifiedScore" => self.modified_score.into(),
-            "baseImmediateMaxPossibleModifiedScore" => {
-                self.immediate_max_possible_modified_score.into()
-            }
-            "baseRelativeScore" => self.relative_score.into(),
-            "baseMultiplier" => self.multiplier.into(),
-            "baseEnergy" => self.energy.into(),
-            "baseSongTime" => self.song_time.into(),
-            "baseSongLength" => self.song_length.into(),
-
-            "baseEnvironmentColor0" => self.environment_color_0.into(),
-            "baseEnvironmentColor0Boost" => self.environment_color_0_boost.into(),
-            "baseEnvironmentColor1" => self.environment_color_1.into(),
-            "baseEnvironmentColor1Boost" => self.environment_color_1_boost.into(),
-            "baseEnvironmentColorW" => self.environment_color_w.into(),
-            "baseEnvironmentColorWBoost" => self.environment_color_w_boost.into(),
-            "baseNote0Color" => self.note_color_0.into(),
-            "baseNote1Color" => self.note_color_1.into(),
-            "baseObstaclesColor" => self.obstacles_color.into(),
-            "baseSaberAColor" => self.saber_color_a.into(),
-            "baseSaberBColor" => self.saber_color_b.into(),
-
-            "baseHeadLocalPosition" => self.head_local_position.into(),
-            "baseHeadLocalRotation" => self.head_local_rotation.into(),
-            "baseHeadLocalScale" => self.head_local_scale.into(),
-            "baseHeadPosition" => self.head_position.into(),
-            "baseHeadRotation" => self.head_rotation.into(),
-            "baseLeftHandLocalPosition" => self.left_hand_local_position.into(),
-            "baseLeftHandLocalRotation" => self.left_hand_local_rotation.into(),
-            "baseLeftHandLocalScale" => self.left_hand_local_scale.into(),
-            "baseLeftHandPosition" => self.left_hand_position.into(),
-            "baseLeftHandRotation" => self.left_hand_rotation.into(),
-            "baseRightHandLocalPosition" => self.right_hand_local_position.into(),
-            "baseRightHandLocalRotation" => self.right_hand_local_rotation.into(),
-            "baseRightHandLocalScale" => self.right_hand_local_scale.into(),
-            "baseRightHandPosition" => self.right_hand_position.into(),
-            "baseRightHandRotation" => self.right_hand_rotation.into(),
-            _ => panic!("Base provider not found {base}"),
+    /// Registers a base named `name` whose values are computed by `provider` each time it is
+    /// sampled, instead of being set with [`BaseProviderContext::set_values`].
+    ///
+    /// `provider` returns `component_count` values; extra values are dropped and missing ones
+    /// are 0. Rotations are returned as 3 Euler angles in degrees, like quaternion bases.
+    pub fn register_custom_provider(
+        &mut self,
+        name: &str,
+        component_count: usize,
+        is_rotation: bool,
+        provider: impl CustomProvider + 'static,
+    ) -> Result<(), BaseError> {
+        self.check_new_name(name)?;
+
+        let custom =
+            CustomProviderValues::new(name.to_string(), component_count, is_rotation, provider);
+        if custom.value_type() == WrapBaseValueType::Unknown {
+            return Err(BaseError::InvalidComponentCount {
+                base: name.to_string(),
+                count: component_count,
+                is_rotation,
+            });
+        }
+
+        self.custom_providers.insert(name.to_string(), custom);
+        Ok(())
+    }
+
+    fn check_new_name(&self, name: &str) -> Result<(), BaseError> {
+        if !name.starts_with("base") || name.contains('.') {
+            return Err(BaseError::InvalidName(name.to_string()));
+        }
+        // Generator names are reserved
+        if self.has_base(name) || GeneratorKind::from_name(name).is_some() {
+            return Err(BaseError::AlreadyRegistered(name.to_string()));
         }
+        Ok(())
+    }
+
+    /// Whether a base or custom provider named `base` is registered.
+    pub fn has_base(&self, base: &str) -> bool {
+        self.base_indices.contains_key(base) || self.custom_providers.contains_key(base)
     }
 
+    /// The type `base` was registered with.
+    pub fn base_type(&self, base: &str) -> Option<WrapBaseValueType> {
+        match self.custom_providers.get(base) {
+            Some(custom) => Some(custom.value_type()),
+            None => self.base_value(base).map(BaseValue::get_type),
+        }
+    }
+
+    /// Names of all bases registered with [`BaseProviderContext::register_base`], built-in ones
+    /// included, in registration order. Custom providers are not included.
+    pub fn base_names(&self) -> impl Iterator<Item = &str> {
+        self.base_names.iter().map(String::as_str)
+    }
+
+    /// Resolves `base` to its slot, or `None` if it isn't registered.
+    pub fn base_slot(&self, base: &str) -> Option<BaseSlot> {
+        self.base_indices.get(base).copied().map(BaseSlot)
+    }
+
+    /// The slot with index `index`, if that many bases are registered.
+    #[cfg(feature = "ffi")]
+    pub(crate) fn slot_at(&self, index: usize) -> Option<BaseSlot> {
+        (index < self.bases.len()).then_some(BaseSlot(index))
+    }
+
+    pub(crate) fn base_value(&self, base: &str) -> Option<&BaseValue> {
+        self.base_slot(base).map(|slot| self.get_slot(slot))
+    }
+
+    /// Returns the current value in `slot`.
+    ///
+    /// Panics if `slot` was resolved by a context with more bases registered.
+    #[inline]
+    pub fn get_slot(&self, slot: BaseSlot) -> &BaseValue {
+        &self.bases[slot.0]
+    }
+
+    /// Sets the current value in `slot`, like [`BaseProviderContext::try_set_values`].
+    ///
+    /// Panics if `slot` was resolved by a context with more bases registered.
+    pub fn set_slot(&mut self, slot: BaseSlot, base_value: BaseValue) -> Result<(), BaseError> {
+        let value = &mut self.bases[slot.0];
+        if value.get_type() != base_value.get_type() {
+            return Err(BaseError::TypeMismatch {
+                base: self.base_names[slot.0].clone(),
+                expected: value.get_type(),
+                found: base_value.get_type(),
+            });
+        }
+        *value = base_value;
+        if let Some(recording) = &mut self.recording {
+            recording
+                .get_mut()
+                .record_set(&self.base_names[slot.0], base_value);
+        }
+        Ok(())
+    }
+
+    /// Returns the current value of `base`.
+    ///
+    /// Panics if `base` isn't registered; see [`BaseProviderContext::try_get_values`].
+    pub fn get_values(&self, base: &str) -> BaseValue {
+        self.try_get_values(base).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Like [`BaseProviderContext::get_values`]. Custom providers are sampled.
+    pub fn try_get_values(&self, base: &str) -> Result<BaseValue, BaseError> {
+        if let Some(custom) = self.custom_providers.get(base) {
+            return Ok(custom.base_value(self));
+        }
+        self.base_value(base)
+            .copied()
+            .ok_or_else(|| BaseError::UnknownBase(base.to_string()))
+    }
+
+    /// Sets the current value of `base`.
+    ///
+    /// Panics if `base` isn't registered or `base_value` has the wrong type; see
+    /// [`BaseProviderContext::try_set_values`].
     pub fn set_values(&mut self, base: &str, base_value: BaseValue) {
-        let values = base_value.as_slice_raw();
-        match base {
-            "baseCombo" => {
-                self.base_combo = values[0];
-            }
-            "baseMultipliedScore" => {
-                self.multiplied_score = values[0];
-            }
-            "baseImmediateMaxPossibleMultipliedScore" => {
-                self.immediate_max_possible_multiplied_score = values[0];
-            }
-            "baseModifiedScore" => {
-                self.modified_score = values[0];
-            }
-            "baseImmediateMaxPossibleModifiedScore" => {
-                self.immediate_max_possible_modified_score = values[0];
-            }
-            "baseRelativeScore" => {
-                self.relative_score = values[0];
-            }
-            "baseMultiplier" => {
-                self.multiplier = values[0];
-            }
-            "baseEnergy" => {
-                self.energy = values[0];
-            }
-            "baseSongTime" => {
-                self.song_time = values[0];
-            }
-            "baseSongLength" => {
-                self.song_length = values[0];
-            }
-            "baseEnvironmentColor0" => {
-                self.environment_color_0 = base_value.as_vec4().unwrap();
-            }
-            "baseEnvironmentColor0Boost" => {
-                self.environment_color_0_boost = base_value.as_vec4().unwrap();
-            }
-            "baseEnvironmentColor1" => {
-                self.environment_color_1 = base_value.as_vec4().unwrap();
-            }
-            "baseEnvironmentColor1Boost" => {
-                self.environment_color_1_boost = base_value.as_vec4().unwrap();
-            }
-            "baseEnvironmentColorW" => {
-                self.environment_color_w = base_value.as_vec4().unwrap();
-            }
-            "baseEnvironmentColorWBoost" => {
-                self.environment_color_w_boost = base_value.as_vec4().unwrap();
-            }
-            "baseNote0Color" => {
-                self.note_color_0 = base_value.as_vec4().unwrap();
-            }
-            "baseNote1Color" => {
-                self.note_color_1 = base_value.as_vec4().unwrap();
-            }
-            "baseObstaclesColor" => {
-                self.obstacles_color = base_value.as_vec4().unwrap();
-            }
-            "baseSaberAColor" => {
-                self.saber_color_a = base_value.as_vec4().unwrap();
-            }
-            "baseSaberBColor" => {
-                self.saber_color_b = base_value.as_vec4().unwrap();
-            }
-            "baseHeadLocalPosition" => {
-                self.head_local_position = base_value.as_vec3().unwrap();
-            }
-            "baseHeadLocalRotation" => {
-                self.head_local_rotation = base_value.as_quat().unwrap();
-            }
-            "baseHeadLocalScale" => {
-                self.head_local_scale = base_value.as_vec3().unwrap();
-            }
-            "baseHeadPosition" => {
-                self.head_position = base_value.as_vec3().unwrap();
-            }
-            "baseHeadRotation" => {
-                self.head_rotation = base_value.as_quat().unwrap();
-            }
-            "baseLeftHandLocalPosition" => {
-                self.left_hand_local_position = base_value.as_vec3().unwrap();
-            }
-            "baseLeftHandLocalRotation" => {
-                self.left_hand_local_rotation = base_value.as_quat().unwrap();
-            }
-            "baseLeftHandLocalScale" => {
-                self.left_hand_local_scale = base_value.as_vec3().unwrap();
-            }
-            "baseLeftHandPosition" => {
-                self.left_hand_position = base_value.as_vec3().unwrap();
-            }
-            "baseLeftHandRotation" => {
-                self.left_hand_rotation = base_value.as_quat().unwrap();
-            }
-            "baseRightHandLocalPosition" => {
-                self.right_hand_local_position = base_value.as_vec3().unwrap();
-            }
-            "baseRightHandLocalRotation" => {
-                self.right_hand_local_rotation = base_value.as_quat().unwrap();
-            }
-            "baseRightHandLocalScale" => {
-                self.right_hand_local_scale = base_value.as_vec3().unwrap();
-            }
-            "baseRightHandPosition" => {
-                self.right_hand_position = base_value.as_vec3().unwrap();
-            }
-            "baseRightHandRotation" => {
-                self.right_hand_rotation = base_value.as_quat().unwrap();
-            }
-            _ => panic!("Base provider not found"),
+        self.try_set_values(base, base_value)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    pub fn try_set_values(&mut self, base: &str, base_value: BaseValue) -> Result<(), BaseError> {
+        if self.custom_providers.contains_key(base) {
+            return Err(BaseError::ReadOnly(base.to_string()));
         }
+        let slot = self
+            .base_slot(base)
+            .ok_or_else(|| BaseError::UnknownBase(base.to_string()))?;
+        self.set_slot(slot, base_value)
     }
 
     /// Get or create a ValueProvider for the given base provider name
@@ -243,18 +433,31 @@ impl BaseProviderContext {
     /// e.g `baseHeadPosition` -> BaseProvider(baseHeadPosition)
     ///     `baseHeadPosition.x` -> PartialProvider(baseHeadPosition, [0])
     ///     `baseHeadPosition.s0_5` -> SmoothProvider(baseHeadPosition, 0.5)
+    ///
+    /// Panics if the base isn't registered; see [`BaseProviderContext::try_get_value_provider`].
     pub fn get_value_provider(&mut self, base: &str) -> ValueProvider {
+        self.try_get_value_provider(base)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Like [`BaseProviderContext::get_value_provider`], but returns
+    /// [`BaseError::UnknownBase`] if the part before the first `.` isn't a registered base.
+    pub fn try_get_value_provider(&mut self, base: &str) -> Result<ValueProvider, BaseError> {
         // If we already created a provider for the full key, return it
         // we can avoid string parsing this way for repeated accesses to the same provider
-        if let Some(cached) = self.provider_cache.get(base) {
-            return cached.clone();
+        if let Some(cached) = self.provider_cache.get(base).and_then(CachedProvider::get) {
+            return Ok(cached);
+        }
+
+        let base_name = base.split('.').next().unwrap_or_default();
+        if !self.has_base(base_name) && GeneratorKind::from_name(base_name).is_none() {
+            return Err(BaseError::UnknownBase(base_name.to_string()));
         }
 
         let provider = self.create_value_provider(base);
-        self.provider_cache
-            .insert(base.to_string(), provider.clone());
+        self.cache_provider(base.to_string(), &provider);
 
-        provider
+        Ok(provider)
     }
 
     /// Creates a ValueProvider for the given base provider name
@@ -264,34 +467,56 @@ impl BaseProviderContext {
             panic!("empty provider key");
         }
 
-        // Base provider name
+        // Base provider name, checked by `try_get_value_provider`
         let base_name = splits[0];
+        // Parts before this one were generator parameters
+        let mut first_part = 1;
+        let mut result = if let Some(kind) = GeneratorKind::from_name(base_name) {
+            let (generator, params) = self.create_generator_provider(kind, &splits);
+            first_part += params;
+            generator
+        } else {
+            match self.custom_providers.get(base_name) {
+                Some(custom) => ValueProvider::Custom(custom.clone()),
+                None => {
+                    let slot = self.base_slot(base_name).expect("base is registered");
+                    ValueProvider::BaseProvider(BaseProviderValues::new(base_name.to_owned(), slot))
+                }
+            }
+        };
 
         // Quick path: single-name base
-        let mut result = ValueProvider::BaseProvider(BaseProviderValues::new(base_name.to_owned()));
 
-        if splits.len() == 1 {
+        if splits.len() == first_part {
             return result;
         }
 
         // Start from the base provider value and apply each split part to build the final provider
         // Iterate through dotted parts and build/caches intermediate providers
-        for i in 1..splits.len() {
+        for i in first_part..splits.len() {
             let split = splits[i];
             let sub_key = splits[0..=i].join(".");
 
             // we can avoid string parsing
-            if let Some(cached) = self.provider_cache.get(&sub_key) {
-                result = cached.clone();
+            if let Some(cached) = self
+                .provider_cache
+                .get(&sub_key)
+                .and_then(CachedProvider::get)
+            {
+                result = cached;
                 continue;
             }
 
-            let updateable_values = self.handle_split_part(split, &result);
+            let updateable_values = self.handle_split_part(split, sub_key.clone(), &result);
 
             // If updateable, register it so it will be ticked via `update_providers`
             if updateable_values.is_updateable() {
                 self.register_updatable_provider(&updateable_values);
             }
+            // Share intermediate smoothing providers, e.g. `baseCombo.s1` for `baseCombo.s1.x`
+            if i + 1 < splits.len() {
+                self.cache_provider(sub_key, &updateable_values);
+            }
 
             result = updateable_values;
         }
@@ -299,64 +524,195 @@ impl BaseProviderContext {
         result
     }
 
+    fn cache_provider(&mut self, key: String, provider: &ValueProvider) {
+        if let Some(cached) = CachedProvider::new(provider) {
+            self.provider_cache.insert(key, cached);
+        }
+    }
+
+    /// Registers a smoothing or velocity provider to be ticked by [`BaseProviderContext::update_providers`].
+    ///
+    /// The context only holds a weak reference: the provider stops updating once every clone of
+    /// it is dropped.
     pub fn register_updatable_provider(&mut self, provider: &ValueProvider) {
-        match provider {
+        let weak = match provider {
             ValueProvider::SmoothProviders(v) => {
-                self.updatable_providers
-                    .push(v.clone() as Rc<RefCell<dyn UpdateableValues>>);
+                Shared::downgrade(v) as SharedWeak<StateCell<dyn UpdateableValues>>
             }
             ValueProvider::SmoothRotationProviders(v) => {
-                self.updatable_providers
-                    .push(v.clone() as Rc<RefCell<dyn UpdateableValues>>);
+                Shared::downgrade(v) as SharedWeak<StateCell<dyn UpdateableValues>>
+            }
+            ValueProvider::Velocity(v) => {
+                Shared::downgrade(v) as SharedWeak<StateCell<dyn UpdateableValues>>
             }
-            _ => {}
+            _ => return,
+        };
+        self.updatable_providers.push(weak);
+
+        // Prune dropped providers whenever the list doubles, so it stays proportional to the
+        // providers in use
+        if self.updatable_providers.len() >= (self.updatable_pruned_len * 2).max(64) {
+            self.prune_providers();
         }
     }
 
     pub fn update_providers(&self, delta: f32) {
+        if let Some(recording) = &self.recording {
+            recording.borrow_mut().record_update(delta);
+        }
         for provider in &self.updatable_providers {
-            provider.borrow_mut().update(delta, self);
+            if let Some(provider) = provider.upgrade() {
+                provider.borrow_mut().update(delta, self);
+            }
         }
     }
 
-    fn handle_split_part(&self, split: &str, result: &ValueProvider) -> ValueProvider {
-        if split.starts_with('s') {
-            return self.create_smooth_provider(result, split);
+    /// Starts capturing every base write and `update_providers` call for replay, see
+    /// [`crate::recording`]. Restarts the recording if one is running.
+    ///
+    /// The recording begins with the current value of every registered base.
+    pub fn start_recording(&mut self) {
+        let mut recording = Recording::default();
+        for (name, value) in self.base_names.iter().zip(&self.bases) {
+            recording.record_set(name, *value);
+        }
+        self.recording = Some(StateCell::new(recording));
+    }
+
+    /// Stops recording and returns what was captured, or `None` if nothing was being recorded.
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recording.take().map(StateCell::into_inner)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Removes dropped smoothing providers from the update list and the provider cache.
+    pub fn prune_providers(&mut self) {
+        self.updatable_providers
+            .retain(|provider| provider.strong_count() > 0);
+        self.provider_cache.retain(|_, cached| cached.is_alive());
+        self.updatable_pruned_len = self.updatable_providers.len();
+    }
+
+    /// Forgets every provider created so far, e.g. when unloading a map.
+    ///
+    /// Point definitions parsed before keep working, but their smoothing providers are no longer
+    /// updated and keep their last value. Registered bases, their values and custom providers
+    /// are kept.
+    pub fn clear_providers(&mut self) {
+        self.updatable_providers.clear();
+        self.updatable_pruned_len = 0;
+        self.provider_cache.clear();
+    }
+
+    pub fn provider_stats(&self) -> ProviderStats {
+        ProviderStats {
+            cached: self.provider_cache.len(),
+            updatable_alive: self
+                .updatable_providers
+                .iter()
+                .filter(|provider| provider.strong_count() > 0)
+                .count(),
+            updatable_registered: self.updatable_providers.len(),
+        }
+    }
+
+    fn handle_split_part(&self, split: &str, key: String, result: &ValueProvider) -> ValueProvider {
+        if split == "v" || split == "av" {
+            return self.create_velocity_provider(result, split == "av", key);
+        }
+        if let Some(kind) = SmoothKind::from_spec(split) {
+            return self.create_smooth_provider(result, split, kind, key);
         }
         // partial swizzle like x/y/z/w
-        self.create_partial_provider(result, split)
+        self.create_partial_provider(result, split, key)
     }
 
     /// Build a `PartialProvider` from a swizzle string like "x", "xy", "zw", etc.
-    fn create_partial_provider(&self, source: &ValueProvider, swizzle: &str) -> ValueProvider {
+    fn create_partial_provider(
+        &self,
+        source: &ValueProvider,
+        swizzle: &str,
+        key: String,
+    ) -> ValueProvider {
         let parts: Vec<usize> = swizzle
             .chars()
-            .flat_map(|s| s.to_lowercase())
-            .map(|n| match n {
-                'x' => 0,
-                'y' => 1,
-                'z' => 2,
-                'w' => 3,
-                other => {
-                    warn!("invalid swizzle char: {}", other);
+            .map(|n| {
+                swizzle_index(n).unwrap_or_else(|| {
+                    warn!("invalid swizzle char: {}", n);
                     0
-                }
+                })
             })
             .collect();
 
-        ValueProvider::PartialProvider(PartialProviderValues::new(source.clone(), parts))
+        ValueProvider::PartialProvider(PartialProviderValues::with_key(source.clone(), parts, key))
     }
 
-    /// Build a smoothing provider from a spec like `s1` or `s0_5`.
-    fn create_smooth_provider(&self, source: &ValueProvider, spec: &str) -> ValueProvider {
-        let rest = spec[1..].replace('_', ".");
-        let mult = rest.parse::<f32>();
+    /// Builds a generator from its name and the parameters following it, e.g. `baseSine.f2.p0_5`.
+    /// Returns the provider and how many parameters were used.
+    fn create_generator_provider(
+        &self,
+        kind: GeneratorKind,
+        splits: &[&str],
+    ) -> (ValueProvider, usize) {
+        let song_time = self.base_slot("baseSongTime").expect("built-in base");
+        let params = splits[1..]
+            .iter()
+            .take_while(|part| GeneratorParam::parse(part).is_some())
+            .count();
+        let key = splits[..=params].join(".");
+
+        let mut generator = GeneratorProviderValues::new(kind, key, song_time);
+        for part in &splits[1..=params] {
+            let param = GeneratorParam::parse(part).expect("counted as a parameter");
+            if param == GeneratorParam::Invalid {
+                error!(
+                    "Invalid generator parameter {part} in {}, ignoring it",
+                    generator.key
+                );
+            }
+            generator.apply(param);
+        }
+
+        (ValueProvider::Generator(generator), params)
+    }
 
-        let mult = match mult {
+    fn create_velocity_provider(
+        &self,
+        source: &ValueProvider,
+        angular: bool,
+        key: String,
+    ) -> ValueProvider {
+        let angular = if angular && !source.is_rotation(self) {
+            warn!("Angular velocity {key} of a provider that isn't a rotation, using `v` instead");
+            false
+        } else {
+            angular
+        };
+
+        ValueProvider::Velocity(Shared::new(StateCell::new(VelocityProviderValues::new(
+            source.clone(),
+            angular,
+            key,
+            self,
+        ))))
+    }
+
+    /// Build a smoothing provider from a spec like `s1` or `s0_5`.
+    fn create_smooth_provider(
+        &self,
+        source: &ValueProvider,
+        spec: &str,
+        kind: SmoothKind,
+        key: String,
+    ) -> ValueProvider {
+        let mult = match smooth_multiplier(spec) {
             Ok(mult) => mult,
             Err(e) => {
                 error!(
-                    "Invalid smooth provider specifier: {} due to parse error: {}, defaulting to 1.0",
+                    "Invalid smooth provider specifier: {}: {}, defaulting to 1.0",
                     spec, e
                 );
                 1.0
@@ -369,19 +725,52 @@ impl BaseProviderContext {
             true => {
                 // clone the underlying source provider so the smooth rotation provider can sample it each update
 
-                ValueProvider::SmoothRotationProviders(Rc::new(RefCell::new(
-                    SmoothRotationProvidersValues::new(source.clone(), mult),
+                ValueProvider::SmoothRotationProviders(Shared::new(StateCell::new(
+                    SmoothRotationProvidersValues::with_kind(source.clone(), mult, kind, key),
                 )))
             }
             false => {
                 // pass the source provider (clone) so smooth provider can sample it during updates
                 let src_provider = source.clone();
-                ValueProvider::SmoothProviders(Rc::new(RefCell::new(SmoothProvidersValues::new(
-                    src_provider,
-                    mult,
-                    self,
-                ))))
+                ValueProvider::SmoothProviders(Shared::new(StateCell::new(
+                    SmoothProvidersValues::with_kind(src_provider, mult, kind, key, self),
+                )))
             }
         }
     }
 }
+
+/// Component index for a swizzle character (`x`, `y`, `z` or `w`, case-insensitive).
+pub(crate) fn swizzle_index(c: char) -> Option<usize> {
+    match c.to_ascii_lowercase() {
+        'x' => Some(0),
+        'y' => Some(1),
+        'z' => Some(2),
+        'w' => Some(3),
+        _ => None,
+    }
+}
+
+/// Parses the multiplier of a smoothing spec, e.g. `s1` -> 1.0 or `e0_5` -> 0.5.
+///
+/// Negative and non-finite multipliers are rejected, a spring would diverge or turn into NaN.
+pub(crate) fn smooth_multiplier(spec: &str) -> Result<f32, String> {
+    let mult = spec[1..]
+        .replace('_', ".")
+        .parse::<f32>()
+        .map_err(|e| e.to_string())?;
+    if !mult.is_finite() || mult < 0.0 {
+        return Err(format!("{mult} is not a finite, non-negative multiplier"));
+    }
+    Ok(mult)
+}
+
+/// Zero, or the identity for rotations.
+fn default_value(ty: WrapBaseValueType) -> BaseValue {
+    match ty {
+        WrapBaseValueType::Vec3 => BaseValue::Vector3(Vec3::ZERO),
+        WrapBaseValueType::Quat => BaseValue::Quaternion(Quat::IDENTITY),
+        WrapBaseValueType::Vec4 => BaseValue::Vector4(Vec4::ZERO),
+        WrapBaseValueType::Float | WrapBaseValueType::Unknown => BaseValue::Float(0.0),
+    }
+}
@@ -15,6 +15,7 @@ use crate::quaternion_utils::QuaternionUtilsExt;
 use crate::value_types::ValueType;
 
 #[derive(Clone, Debug, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BaseValue {
     Float(f32),
     Vector3(Vec3),
@@ -30,6 +31,7 @@ impl Default for BaseValue {
 
 #[repr(u8)]
 #[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Hash, Ord, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WrapBaseValueType {
     #[default]
     Unknown = u8::MAX,
@@ -145,6 +147,10 @@ impl ValueType for BaseValue {
             _ => panic!("Invalid number of values for BaseValue: {}", values.len()),
         }
     }
+
+    fn as_slice(&self) -> &[f32] {
+        self.as_slice_raw()
+    }
     fn from_translate_array(_values: [f32; Self::VALUE_COUNT]) -> Self {
         unreachable!(
             "from_translate_array should not be called for BaseValue, as it does not have a fixed number of components"
@@ -0,0 +1,345 @@
+//! Loading of Heck custom events from a beatmap's `customData`.
+//!
+//! [`load_custom_events`] registers every track referenced by the custom events in a
+//! [`TracksHolder`], collects the named `pointDefinitions` and turns `AnimateTrack` and
+//! `AssignPathAnimation` events into [`EventData`] ready for [`CoroutineManager::start_event_coroutine`](crate::animation::coroutine_manager::CoroutineManager::start_event_coroutine).
+//!
+//! Both V3 and legacy V2 `customData` are accepted; see [`v2`] for how V2 maps are read.
+
+pub mod v2;
+
+use std::fmt;
+use std::str::FromStr;
+
+use log::warn;
+use serde_json::{Map, Value as JsonValue};
+
+use crate::{
+    animation::{
+        events::{EventData, EventType},
+        track::{PathPropertyHandle, Track, ValuePropertyHandle},
+        tracks_holder::{TrackKey, TracksHolder},
+    },
+    base_provider_context::BaseProviderContext,
+    base_value::WrapBaseValueType,
+    easings::functions::Functions,
+    point_definition::{
+        PointDefinitionLike, base_point_definition::BasePointDefinition, parse_error::ParseError,
+        registry::PointDefinitionRegistry,
+    },
+    providers::dependencies::BaseDependencies,
+};
+
+// customData keys
+pub const CUSTOM_EVENTS: &str = "customEvents";
+pub const POINT_DEFINITIONS: &str = "pointDefinitions";
+
+// custom event keys
+pub const EVENT_BEAT: &str = "b";
+pub const EVENT_TYPE: &str = "t";
+pub const EVENT_DATA: &str = "d";
+
+// custom event data keys
+pub const TRACK: &str = "track";
+pub const DURATION: &str = "duration";
+pub const EASING: &str = "easing";
+pub const REPEAT: &str = "repeat";
+
+// custom event types
+pub const ANIMATE_TRACK: &str = "AnimateTrack";
+pub const ASSIGN_PATH_ANIMATION: &str = "AssignPathAnimation";
+
+/// Custom events loaded from a beatmap.
+#[derive(Debug, Clone, Default)]
+pub struct CustomEvents {
+    /// One event per track and animated property, ordered by start time.
+    pub events: Vec<EventData>,
+    /// Custom event types that were skipped, in the order they were first seen.
+    pub unsupported: Vec<String>,
+    /// The beatmap's named point definitions, including the typed definitions events resolved.
+    pub point_definitions: PointDefinitionRegistry,
+}
+
+impl CustomEvents {
+    /// The bases and stateful providers read by any event's point definition, e.g. so the host
+    /// only updates the bases the map uses.
+    pub fn base_dependencies(&self) -> BaseDependencies {
+        let mut dependencies = BaseDependencies::new();
+        for point_data in self.events.iter().filter_map(|e| e.point_data.as_ref()) {
+            dependencies.merge(&point_data.base_dependencies());
+        }
+        dependencies
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BeatmapError {
+    /// `customData` or one of its custom events is not shaped as expected.
+    Malformed {
+        event: Option<usize>,
+        reason: String,
+    },
+    /// A property's point definition failed to parse.
+    PointDefinition {
+        event: usize,
+        property: String,
+        error: ParseError,
+    },
+    /// A property refers to a point definition name that is not declared.
+    UnknownPointDefinition {
+        event: usize,
+        property: String,
+        name: String,
+    },
+    /// The bpm used to convert beats to seconds is not a finite positive number.
+    InvalidBpm(f32),
+}
+
+impl fmt::Display for BeatmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeatmapError::Malformed {
+                event: Some(event),
+                reason,
+            } => write!(f, "custom event {event}: {reason}"),
+            BeatmapError::Malformed {
+                event: None,
+                reason,
+            } => write!(f, "{reason}"),
+            BeatmapError::PointDefinition {
+                event,
+                property,
+                error,
+            } => write!(f, "custom event {event}: property \"{property}\": {error}"),
+            BeatmapError::UnknownPointDefinition {
+                event,
+                property,
+                name,
+            } => write!(
+                f,
+                "custom event {event}: property \"{property}\": unknown point definition \"{name}\""
+            ),
+            BeatmapError::InvalidBpm(bpm) => {
+                write!(f, "bpm must be a finite positive number, got {bpm}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BeatmapError {}
+
+/// Loads the custom events of a beatmap's `customData`.
+///
+/// V2 `customData` is converted with [`v2::to_v3`] first.
+///
+/// Tracks named by any custom event are added to `tracks` unless a track with that name already
+/// exists. Event start times are converted from beats to seconds using `bpm`, which must be
+/// finite and positive.
+///
+/// Each property is either an inline point definition or the name of an entry in
+/// `pointDefinitions`. A property whose point definition is `null` produces an event without
+/// point data, which clears the property when started. Properties the track does not know about
+/// are skipped.
+pub fn load_custom_events(
+    custom_data: &JsonValue,
+    bpm: f32,
+    tracks: &mut TracksHolder,
+    context: &mut BaseProviderContext,
+) -> Result<CustomEvents, BeatmapError> {
+    let malformed = |event, reason: &str| BeatmapError::Malformed {
+        event,
+        reason: reason.to_string(),
+    };
+
+    if !bpm.is_finite() || bpm <= 0.0 {
+        return Err(BeatmapError::InvalidBpm(bpm));
+    }
+
+    if v2::is_v2(custom_data) {
+        return load_custom_events(&v2::to_v3(custom_data), bpm, tracks, context);
+    }
+
+    let Some(custom_data) = custom_data.as_object() else {
+        return Err(malformed(None, "customData is not an object"));
+    };
+
+    let mut result = CustomEvents {
+        point_definitions: match custom_data.get(POINT_DEFINITIONS) {
+            None => PointDefinitionRegistry::new(),
+            Some(definitions @ JsonValue::Object(_)) => {
+                PointDefinitionRegistry::from_json(definitions)
+            }
+            Some(_) => return Err(malformed(None, "pointDefinitions is not an object")),
+        },
+        ..Default::default()
+    };
+
+    let raw_events = match custom_data.get(CUSTOM_EVENTS) {
+        None => return Ok(result),
+        Some(JsonValue::Array(raw_events)) => raw_events,
+        Some(_) => return Err(malformed(None, "customEvents is not an array")),
+    };
+
+    for (index, raw_event) in raw_events.iter().enumerate() {
+        let Some(raw_event) = raw_event.as_object() else {
+            return Err(malformed(Some(index), "not an object"));
+        };
+        let Some(event_type) = raw_event.get(EVENT_TYPE).and_then(JsonValue::as_str) else {
+            return Err(malformed(Some(index), "missing event type"));
+        };
+        let data = match raw_event.get(EVENT_DATA) {
+            Some(JsonValue::Object(data)) => data,
+            None => &Map::new(),
+            Some(_) => return Err(malformed(Some(index), "event data is not an object")),
+        };
+
+        // register tracks for every event, supported or not, so hosts can look them up
+        let track_keys = register_tracks(data, tracks);
+
+        let is_path = match event_type {
+            ANIMATE_TRACK => false,
+            ASSIGN_PATH_ANIMATION => true,
+            _ => {
+                if !result.unsupported.iter().any(|t| t == event_type) {
+                    result.unsupported.push(event_type.to_string());
+                }
+                continue;
+            }
+        };
+
+        if track_keys.is_empty() {
+            return Err(malformed(Some(index), "missing track"));
+        }
+
+        let beat = raw_event
+            .get(EVENT_BEAT)
+            .and_then(JsonValue::as_f64)
+            .unwrap_or_default() as f32;
+        let raw_duration = data
+            .get(DURATION)
+            .and_then(JsonValue::as_f64)
+            .unwrap_or_default() as f32;
+        let easing = data
+            .get(EASING)
+            .and_then(JsonValue::as_str)
+            .and_then(|e| Functions::from_str(e).ok())
+            .unwrap_or(Functions::EaseLinear);
+        let repeat = data
+            .get(REPEAT)
+            .and_then(JsonValue::as_u64)
+            .unwrap_or_default() as u32;
+
+        for (name, value) in data {
+            if matches!(name.as_str(), TRACK | DURATION | EASING | REPEAT) {
+                continue;
+            }
+
+            let (property, ty) = match is_path {
+                false => {
+                    let handle = ValuePropertyHandle::new(name);
+                    let ty = value_property_type(&handle, tracks, track_keys[0]);
+                    (EventType::AnimateTrack(handle), ty)
+                }
+                true => {
+                    let handle = PathPropertyHandle::new(name);
+                    let ty = path_property_type(&handle, tracks, track_keys[0]);
+                    (EventType::AssignPathAnimation(handle), ty)
+                }
+            };
+
+            if ty == WrapBaseValueType::Unknown {
+                warn!("custom event {index}: unknown property \"{name}\", skipping");
+                continue;
+            }
+
+            let point_error = |error| BeatmapError::PointDefinition {
+                event: index,
+                property: name.clone(),
+                error,
+            };
+            let point_data = match value {
+                JsonValue::Null => None,
+                JsonValue::Array(_) => Some(
+                    BasePointDefinition::try_parse_typed(value, ty, context)
+                        .map_err(point_error)?,
+                ),
+                JsonValue::String(definition_name) => Some(
+                    result
+                        .point_definitions
+                        .resolve(definition_name, ty, context)
+                        .ok_or_else(|| BeatmapError::UnknownPointDefinition {
+                            event: index,
+                            property: name.clone(),
+                            name: definition_name.clone(),
+                        })?
+                        .map_err(point_error)?,
+                ),
+                _ => {
+                    warn!("custom event {index}: unsupported value for \"{name}\", skipping");
+                    continue;
+                }
+            };
+
+            result
+                .events
+                .extend(track_keys.iter().map(|&track_key| EventData {
+                    raw_duration,
+                    easing,
+                    repeat,
+                    start_song_time: beat * 60.0 / bpm,
+                    property: property.clone(),
+                    track_key,
+                    point_data: point_data.clone(),
+                }));
+        }
+    }
+
+    result
+        .events
+        .sort_by(|a, b| a.start_song_time.total_cmp(&b.start_song_time));
+
+    Ok(result)
+}
+
+/// Reads the `track` field, which is either a single name or an array of names, and returns the
+/// keys of those tracks, adding any that do not exist yet.
+fn register_tracks(data: &Map<String, JsonValue>, tracks: &mut TracksHolder) -> Vec<TrackKey> {
+    let names: Vec<&str> = match data.get(TRACK) {
+        Some(JsonValue::String(name)) => vec![name],
+        Some(JsonValue::Array(names)) => names.iter().filter_map(JsonValue::as_str).collect(),
+        _ => vec![],
+    };
+
+    names
+        .into_iter()
+        .map(|name| {
+            tracks
+                .get_track_key(name)
+                .unwrap_or_else(|| tracks.add_track(Track::named(name)))
+        })
+        .collect()
+}
+
+// The type of a property is whatever the track registered it as; unknown custom properties are
+// resolved only if the host registered them beforehand.
+fn value_property_type(
+    handle: &ValuePropertyHandle,
+    tracks: &TracksHolder,
+    track_key: TrackKey,
+) -> WrapBaseValueType {
+    tracks
+        .get_track(track_key)
+        .and_then(|t| t.properties.get_by_handle(handle))
+        .map_or(WrapBaseValueType::Unknown, |p| p.get_type())
+}
+
+fn path_property_type(
+    handle: &PathPropertyHandle,
+    tracks: &TracksHolder,
+    track_key: TrackKey,
+) -> WrapBaseValueType {
+    tracks
+        .get_track(track_key)
+        .and_then(|t| t.path_properties.get_by_handle(handle))
+        .map_or(WrapBaseValueType::Unknown, |p| p.get_type())
+}
@@ -0,0 +1,150 @@
+//! Legacy V2 beatmap support.
+//!
+//! V2 maps store their custom events under `_customEvents` (`_time`, `_type`, `_data`) and their
+//! named point definitions under `_pointDefinitions` as an array of `{_name, _points}`. Every key
+//! inside an event's `_data` is underscore-prefixed.
+//!
+//! [`to_v3`] rewrites V2 `customData` into its V3 shape, which is also how
+//! [`load_custom_events`](super::load_custom_events) reads V2 maps.
+//!
+//! Property names follow Noodle Extensions' V2 semantics rather than the plain underscore alias:
+//! `_position` is an offset and becomes `offsetPosition`, and `_rotation` is a world rotation
+//! offset and becomes `offsetWorldRotation`. `_localRotation` stays a local rotation.
+
+use serde_json::{Map, Value as JsonValue};
+
+use crate::animation::track::{
+    ATTENTUATION, COLOR, DEFINITE_POSITION, DISSOLVE, DISSOLVE_ARROW, FOG_OFFSET,
+    HEIGHT_FOG_HEIGHT, HEIGHT_FOG_START_Y, INTERACTABLE, LOCAL_POSITION, LOCAL_ROTATION,
+    OFFSET_POSITION, OFFSET_ROTATION, SCALE, TIME, V2_ATTENTUATION, V2_COLOR, V2_CUTTABLE,
+    V2_DEFINITE_POSITION, V2_DISSOLVE, V2_DISSOLVE_ARROW, V2_FOG_OFFSET, V2_HEIGHT_FOG_HEIGHT,
+    V2_HEIGHT_FOG_START_Y, V2_LOCAL_POSITION, V2_LOCAL_ROTATION, V2_POSITION, V2_ROTATION,
+    V2_SCALE, V2_TIME,
+};
+
+use super::{
+    CUSTOM_EVENTS, DURATION, EASING, EVENT_BEAT, EVENT_DATA, EVENT_TYPE, POINT_DEFINITIONS, TRACK,
+};
+
+// customData keys
+pub const V2_CUSTOM_EVENTS: &str = "_customEvents";
+pub const V2_POINT_DEFINITIONS: &str = "_pointDefinitions";
+
+// custom event keys
+pub const V2_EVENT_TIME: &str = "_time";
+pub const V2_EVENT_TYPE: &str = "_type";
+pub const V2_EVENT_DATA: &str = "_data";
+
+// point definition keys
+pub const V2_NAME: &str = "_name";
+pub const V2_POINTS: &str = "_points";
+
+// custom event data keys
+pub const V2_TRACK: &str = "_track";
+pub const V2_DURATION: &str = "_duration";
+pub const V2_EASING: &str = "_easing";
+pub const V2_INTERACTABLE: &str = "_interactable";
+
+/// Whether `custom_data` is in the V2 format.
+pub fn is_v2(custom_data: &JsonValue) -> bool {
+    custom_data
+        .as_object()
+        .is_some_and(|c| c.contains_key(V2_CUSTOM_EVENTS) || c.contains_key(V2_POINT_DEFINITIONS))
+}
+
+/// The V3 name of a key inside a V2 custom event's `_data`.
+///
+/// Known keys are mapped explicitly; any other underscore-prefixed key has the underscore
+/// removed, e.g. `_childrenTracks` becomes `childrenTracks`.
+pub fn v3_data_key(key: &str) -> String {
+    let mapped = match key {
+        V2_TRACK => TRACK,
+        V2_DURATION => DURATION,
+        V2_EASING => EASING,
+
+        V2_POSITION => OFFSET_POSITION,
+        V2_ROTATION => OFFSET_ROTATION,
+        V2_LOCAL_POSITION => LOCAL_POSITION,
+        V2_LOCAL_ROTATION => LOCAL_ROTATION,
+        V2_SCALE => SCALE,
+        V2_DEFINITE_POSITION => DEFINITE_POSITION,
+        V2_DISSOLVE => DISSOLVE,
+        V2_DISSOLVE_ARROW => DISSOLVE_ARROW,
+        V2_TIME => TIME,
+        V2_INTERACTABLE | V2_CUTTABLE => INTERACTABLE,
+        V2_COLOR => COLOR,
+        V2_ATTENTUATION => ATTENTUATION,
+        V2_FOG_OFFSET => FOG_OFFSET,
+        V2_HEIGHT_FOG_START_Y => HEIGHT_FOG_START_Y,
+        V2_HEIGHT_FOG_HEIGHT => HEIGHT_FOG_HEIGHT,
+
+        _ => return key.strip_prefix('_').unwrap_or(key).to_string(),
+    };
+    mapped.to_string()
+}
+
+/// Converts V2 `customData` to V3 `customData`.
+///
+/// `_customEvents` and `_pointDefinitions` are converted; other keys are copied unchanged.
+/// Malformed entries are skipped. Input that is already V3 is returned as is.
+pub fn to_v3(custom_data: &JsonValue) -> JsonValue {
+    let Some(custom_data) = custom_data.as_object() else {
+        return custom_data.clone();
+    };
+
+    let mut result = Map::new();
+    for (key, value) in custom_data {
+        match key.as_str() {
+            V2_CUSTOM_EVENTS => {
+                let events = value.as_array().map(Vec::as_slice).unwrap_or_default();
+                result.insert(
+                    CUSTOM_EVENTS.to_string(),
+                    events.iter().filter_map(convert_event).collect(),
+                );
+            }
+            V2_POINT_DEFINITIONS => {
+                let definitions = value.as_array().map(Vec::as_slice).unwrap_or_default();
+                result.insert(
+                    POINT_DEFINITIONS.to_string(),
+                    JsonValue::Object(
+                        definitions
+                            .iter()
+                            .filter_map(convert_point_definition)
+                            .collect(),
+                    ),
+                );
+            }
+            _ => {
+                result.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    JsonValue::Object(result)
+}
+
+fn convert_event(event: &JsonValue) -> Option<JsonValue> {
+    let event = event.as_object()?;
+
+    let mut result = Map::new();
+    if let Some(time) = event.get(V2_EVENT_TIME) {
+        result.insert(EVENT_BEAT.to_string(), time.clone());
+    }
+    result.insert(EVENT_TYPE.to_string(), event.get(V2_EVENT_TYPE)?.clone());
+
+    if let Some(data) = event.get(V2_EVENT_DATA).and_then(JsonValue::as_object) {
+        let data = data
+            .iter()
+            .map(|(key, value)| (v3_data_key(key), value.clone()))
+            .collect();
+        result.insert(EVENT_DATA.to_string(), JsonValue::Object(data));
+    }
+
+    Some(JsonValue::Object(result))
+}
+
+fn convert_point_definition(definition: &JsonValue) -> Option<(String, JsonValue)> {
+    let name = definition.get(V2_NAME)?.as_str()?;
+    let points = definition.get(V2_POINTS)?;
+    Some((name.to_string(), points.clone()))
+}
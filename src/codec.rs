@@ -0,0 +1,84 @@
+//! Little-endian primitives and LEB128 varints shared by the binary formats in
+//! [`crate::point_definition::cache`] and [`crate::recording`].
+
+/// Errors any binary format can hit while reading primitives. Each format converts them into its
+/// own error type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CodecError {
+    /// The data ends in the middle of a value.
+    UnexpectedEof,
+    /// A varint is longer than a `usize`.
+    InvalidVarint,
+}
+
+pub(crate) fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads primitives from a byte slice, never past its end.
+pub(crate) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(CodecError::UnexpectedEof)?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], CodecError> {
+        Ok(self.take(N)?.try_into().expect("slice has length N"))
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn f32(&mut self) -> Result<f32, CodecError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<usize, CodecError> {
+        let mut value = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.u8()?;
+            value |= usize::from(byte & 0x7f)
+                .checked_shl(shift)
+                .filter(|bits| bits >> shift == usize::from(byte & 0x7f))
+                .ok_or(CodecError::InvalidVarint)?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::InvalidVarint)
+    }
+
+    /// A count of entries that each take at least one byte, checked against the bytes left so
+    /// corrupt data can't trigger a huge allocation or a long loop.
+    pub(crate) fn count(&mut self) -> Result<usize, CodecError> {
+        let count = self.varint()?;
+        match count <= self.bytes.len() - self.position {
+            true => Ok(count),
+            false => Err(CodecError::UnexpectedEof),
+        }
+    }
+}
@@ -79,6 +79,46 @@ impl Functions {
     }
 }
 
+impl Functions {
+    /// The Heck name of this easing, e.g. `easeInQuad`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EaseLinear => "easeLinear",
+            Self::EaseStep => "easeStep",
+            Self::EaseInQuad => "easeInQuad",
+            Self::EaseOutQuad => "easeOutQuad",
+            Self::EaseInOutQuad => "easeInOutQuad",
+            Self::EaseInCubic => "easeInCubic",
+            Self::EaseOutCubic => "easeOutCubic",
+            Self::EaseInOutCubic => "easeInOutCubic",
+            Self::EaseInQuart => "easeInQuart",
+            Self::EaseOutQuart => "easeOutQuart",
+            Self::EaseInOutQuart => "easeInOutQuart",
+            Self::EaseInQuint => "easeInQuint",
+            Self::EaseOutQuint => "easeOutQuint",
+            Self::EaseInOutQuint => "easeInOutQuint",
+            Self::EaseInSine => "easeInSine",
+            Self::EaseOutSine => "easeOutSine",
+            Self::EaseInOutSine => "easeInOutSine",
+            Self::EaseInCirc => "easeInCirc",
+            Self::EaseOutCirc => "easeOutCirc",
+            Self::EaseInOutCirc => "easeInOutCirc",
+            Self::EaseInExpo => "easeInExpo",
+            Self::EaseOutExpo => "easeOutExpo",
+            Self::EaseInOutExpo => "easeInOutExpo",
+            Self::EaseInElastic => "easeInElastic",
+            Self::EaseOutElastic => "easeOutElastic",
+            Self::EaseInOutElastic => "easeInOutElastic",
+            Self::EaseInBack => "easeInBack",
+            Self::EaseOutBack => "easeOutBack",
+            Self::EaseInOutBack => "easeInOutBack",
+            Self::EaseInBounce => "easeInBounce",
+            Self::EaseOutBounce => "easeOutBounce",
+            Self::EaseInOutBounce => "easeInOutBounce",
+        }
+    }
+}
+
 impl FromStr for Functions {
     type Err = ();
 
@@ -128,3 +168,20 @@ impl fmt::Display for Functions {
         write!(f, "{self:?}")
     }
 }
+
+/// Serialized as the Heck easing name, e.g. `"easeInQuad"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Functions {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Functions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Functions::from_str(&name)
+            .map_err(|_| serde::de::Error::custom(format!("unknown easing \"{name}\"")))
+    }
+}
@@ -1,7 +1,8 @@
-use crate::base_provider_context::BaseProviderContext;
+use crate::base_provider_context::{BaseProviderContext, ProviderStats};
 use crate::base_value::{BaseValue, WrapBaseValueType};
 use crate::ffi::types::{WrapBaseValue, WrappedValues};
-use crate::providers::base_ffi::{BaseFFIProvider, BaseFFIProviderValues};
+use crate::providers::base_ffi::{BaseFFIProvider, BaseFFIProviderValues, FFICallbackProvider};
+use log::error;
 use std::ffi::{CStr, c_void};
 use std::ptr;
 
@@ -20,6 +21,10 @@ pub extern "C" fn base_provider_context_create() -> *mut BaseProviderContext {
 }
 
 /// Destroy a `BaseProviderContext` previously returned by `base_provider_context_create`.
+///
+/// # Safety
+/// - `ctx` must be a pointer previously returned by `base_provider_context_create` and not already freed.
+/// - Passing a null pointer is a no-op.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn base_provider_context_destroy(ctx: *mut BaseProviderContext) {
     if ctx.is_null() {
@@ -33,10 +38,16 @@ pub unsafe extern "C" fn base_provider_context_destroy(ctx: *mut BaseProviderCon
 
 /// Create a `BaseFFIProviderValues` wrapper from a C function pointer and user value.
 ///
+/// Nothing over FFI takes the returned wrapper. Register the callback as a base with
+/// `base_provider_context_register_ffi_provider` instead.
+///
 /// # Safety
 /// - `func` must be a valid pointer to a `BaseFFIProvider` function table and not null.
 /// - `user_value` is passed through as-is and its ownership remains with the caller.
 #[unsafe(no_mangle)]
+#[deprecated(
+    note = "Use base_provider_context_register_ffi_provider to register the callback as a base instead"
+)]
 pub unsafe extern "C" fn tracks_make_base_ffi_provider(
     func: *const BaseFFIProvider,
     user_value: *mut c_void,
@@ -49,6 +60,10 @@ pub unsafe extern "C" fn tracks_make_base_ffi_provider(
 }
 
 /// Set a base provider value by name. `value` is a `WrapBaseValue` (C layout) converted into `BaseValue`.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `base` must be null or a valid null-terminated C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn base_provider_context_set_value(
     ctx: *mut BaseProviderContext,
@@ -63,12 +78,170 @@ pub unsafe extern "C" fn base_provider_context_set_value(
     let cstr = unsafe { CStr::from_ptr(base) };
     if let Ok(name) = cstr.to_str() {
         let bv: BaseValue = value.into();
-        ctx_ref.set_values(name, bv);
+        if let Err(e) = ctx_ref.try_set_values(name, bv) {
+            error!("{e}");
+        }
+    }
+}
+
+/// Register a custom base named `base` holding values of type `ty`, starting at `default`.
+/// Point definitions parsed afterwards can reference it, swizzle it and smooth it like a built-in base.
+///
+/// Returns false if the name is taken or invalid, or if `default` isn't of type `ty`.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `base` must be null or a valid null-terminated C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_register_base(
+    ctx: *mut BaseProviderContext,
+    base: *const std::os::raw::c_char,
+    ty: WrapBaseValueType,
+    default: WrapBaseValue,
+) -> bool {
+    if ctx.is_null() || base.is_null() || ty == WrapBaseValueType::Unknown {
+        return false;
+    }
+
+    let ctx_ref = unsafe { &mut *ctx };
+    let cstr = unsafe { CStr::from_ptr(base) };
+    let Ok(name) = cstr.to_str() else {
+        return false;
+    };
+
+    match ctx_ref.register_base(name, ty, default.into()) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("{e}");
+            false
+        }
+    }
+}
+
+/// Register a base named `base` whose values come from calling `fetch` with `user_data` every
+/// time it is sampled. Point definitions can then use it like any other base, e.g. `"baseMyThing.s1"`.
+///
+/// `fetch` returns `component_count` values (1, 3 or 4); rotations return 3 Euler angles in degrees.
+/// Returns false if the name is taken or invalid, or the component count isn't supported.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `base` must be null or a valid null-terminated C string.
+/// - `fetch` must return a pointer to at least `length` floats that stays valid until it is called again,
+///   or a null pointer.
+/// - `user_data` is passed through as-is and must stay valid for as long as `ctx` lives.
+/// - With the `sync` feature, `fetch` may be called from any thread sampling `ctx`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_register_ffi_provider(
+    ctx: *mut BaseProviderContext,
+    base: *const std::os::raw::c_char,
+    fetch: Option<BaseFFIProvider>,
+    user_data: *mut c_void,
+    component_count: usize,
+    is_rotation: bool,
+) -> bool {
+    let Some(fetch) = fetch else {
+        return false;
+    };
+    if ctx.is_null() || base.is_null() {
+        return false;
+    }
+
+    let ctx_ref = unsafe { &mut *ctx };
+    let cstr = unsafe { CStr::from_ptr(base) };
+    let Ok(name) = cstr.to_str() else {
+        return false;
+    };
+
+    let provider = FFICallbackProvider { fetch, user_data };
+    match ctx_ref.register_custom_provider(name, component_count, is_rotation, provider) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("{e}");
+            false
+        }
+    }
+}
+
+/// Resolve a base name to its slot index for `base_provider_context_set_slot_value` and
+/// `base_provider_context_get_slot_value`. Returns -1 if the base isn't registered.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `base` must be null or a valid null-terminated C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_get_slot(
+    ctx: *const BaseProviderContext,
+    base: *const std::os::raw::c_char,
+) -> isize {
+    if ctx.is_null() || base.is_null() {
+        return -1;
+    }
+
+    let ctx_ref = unsafe { &*ctx };
+    let cstr = unsafe { CStr::from_ptr(base) };
+    cstr.to_str()
+        .ok()
+        .and_then(|name| ctx_ref.base_slot(name))
+        .map_or(-1, |slot| slot.index() as isize)
+}
+
+/// Set a base provider value by the slot returned from `base_provider_context_get_slot`.
+///
+/// Returns false if the slot is invalid or `value` has a different type than the base.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_set_slot_value(
+    ctx: *mut BaseProviderContext,
+    slot: isize,
+    value: WrapBaseValue,
+) -> bool {
+    if ctx.is_null() {
+        return false;
+    }
+
+    let ctx_ref = unsafe { &mut *ctx };
+    let Some(slot) = usize::try_from(slot).ok().and_then(|i| ctx_ref.slot_at(i)) else {
+        return false;
+    };
+    match ctx_ref.set_slot(slot, value.into()) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("{e}");
+            false
+        }
+    }
+}
+
+/// Get a base provider value by the slot returned from `base_provider_context_get_slot`.
+/// Returns a zeroed value if the slot is invalid.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_get_slot_value(
+    ctx: *const BaseProviderContext,
+    slot: isize,
+) -> WrapBaseValue {
+    if ctx.is_null() {
+        return unsafe { std::mem::zeroed() };
+    }
+
+    let ctx_ref = unsafe { &*ctx };
+    match usize::try_from(slot).ok().and_then(|i| ctx_ref.slot_at(i)) {
+        Some(slot) => (*ctx_ref.get_slot(slot)).into(),
+        None => unsafe { std::mem::zeroed() },
     }
 }
 
 /// Get a base provider value by name as a `WrapBaseValue`.
-/// The returned `WrapBaseValue` points into data owned by `ctx` (via slice pointer), callers must not free it.
+/// Returns a zeroed value if the base isn't registered.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `base` must be null or a valid null-terminated C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn base_provider_context_get_value(
     ctx: *const BaseProviderContext,
@@ -80,17 +253,18 @@ pub unsafe extern "C" fn base_provider_context_get_value(
 
     let ctx_ref = unsafe { &*ctx };
     let cstr = unsafe { CStr::from_ptr(base) };
-    if let Ok(name) = cstr.to_str() {
-        let bvref = ctx_ref.get_values(name);
-        let bv = bvref;
-
-        bv.into()
-    } else {
-        unsafe { std::mem::zeroed() }
+    match cstr.to_str().map(|name| ctx_ref.try_get_values(name)) {
+        Ok(Ok(bv)) => bv.into(),
+        _ => unsafe { std::mem::zeroed() },
     }
 }
 
 /// Get base provider values as a pointer+length pair. The returned `WrappedValues` borrows data from `ctx`.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `base` must be null or a valid null-terminated C string.
+/// - The returned pointer is only valid while `ctx` is alive and not mutated.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn base_provider_context_get_values_array(
     ctx: *const BaseProviderContext,
@@ -105,8 +279,7 @@ pub unsafe extern "C" fn base_provider_context_get_values_array(
 
     let ctx_ref = unsafe { &*ctx };
     let cstr = unsafe { CStr::from_ptr(base) };
-    if let Ok(name) = cstr.to_str() {
-        let bvref = ctx_ref.get_values(name);
+    if let Some(bvref) = cstr.to_str().ok().and_then(|name| ctx_ref.base_value(name)) {
         let slice = bvref.as_slice_raw();
         WrappedValues {
             values: slice.as_ptr(),
@@ -120,7 +293,11 @@ pub unsafe extern "C" fn base_provider_context_get_values_array(
     }
 }
 
-/// Get the type of the base provider value for `base` (Vec3/Quat/Vec4/Float)
+/// Get the type of the base provider value for `base` (Vec3/Quat/Vec4/Float), or `Unknown` if it isn't registered
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `base` must be null or a valid null-terminated C string.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn base_provider_context_get_type(
     ctx: *const BaseProviderContext,
@@ -132,19 +309,16 @@ pub unsafe extern "C" fn base_provider_context_get_type(
 
     let ctx_ref = unsafe { &*ctx };
     let cstr = unsafe { CStr::from_ptr(base) };
-    if let Ok(name) = cstr.to_str() {
-        match ctx_ref.get_values(name) {
-            BaseValue::Float(_) => WrapBaseValueType::Float,
-            BaseValue::Vector3(_) => WrapBaseValueType::Vec3,
-            BaseValue::Vector4(_) => WrapBaseValueType::Vec4,
-            BaseValue::Quaternion(_) => WrapBaseValueType::Quat,
-        }
-    } else {
-        WrapBaseValueType::Unknown
-    }
+    cstr.to_str()
+        .ok()
+        .and_then(|name| ctx_ref.base_type(name))
+        .unwrap_or(WrapBaseValueType::Unknown)
 }
 
 /// Call `update_providers` on the `BaseProviderContext` with a delta time.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn base_provider_context_update(ctx: *mut BaseProviderContext, delta: f32) {
     if ctx.is_null() {
@@ -154,3 +328,101 @@ pub unsafe extern "C" fn base_provider_context_update(ctx: *mut BaseProviderCont
     let ctx_ref = unsafe { &*ctx };
     ctx_ref.update_providers(delta);
 }
+
+/// Forget every provider created so far, e.g. when unloading a map. Point definitions parsed before
+/// keep working, but their smoothing providers are no longer updated.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_clear_providers(ctx: *mut BaseProviderContext) {
+    if ctx.is_null() {
+        return;
+    }
+
+    let ctx_ref = unsafe { &mut *ctx };
+    ctx_ref.clear_providers();
+}
+
+/// Remove smoothing providers that are no longer used by any point definition.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_prune_providers(ctx: *mut BaseProviderContext) {
+    if ctx.is_null() {
+        return;
+    }
+
+    let ctx_ref = unsafe { &mut *ctx };
+    ctx_ref.prune_providers();
+}
+
+/// Get counts of the cached and updatable providers. Returns zeroes if `ctx` is null.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_get_provider_stats(
+    ctx: *const BaseProviderContext,
+) -> ProviderStats {
+    if ctx.is_null() {
+        return ProviderStats::default();
+    }
+
+    let ctx_ref = unsafe { &*ctx };
+    ctx_ref.provider_stats()
+}
+
+/// Start recording every base write and `base_provider_context_update` call, e.g. to
+/// attach to a bug report. Restarts the recording if one is running.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_start_recording(ctx: *mut BaseProviderContext) {
+    if ctx.is_null() {
+        return;
+    }
+
+    let ctx_ref = unsafe { &mut *ctx };
+    ctx_ref.start_recording();
+}
+
+/// Stop recording and return the encoded recording, writing its length to `out_len`. Returns null
+/// if nothing was being recorded. Free the bytes with `tracks_free_recording`.
+///
+/// # Safety
+/// - `ctx` must be null or a valid pointer to a `BaseProviderContext`.
+/// - `out_len` must be a valid pointer to a `usize`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn base_provider_context_stop_recording(
+    ctx: *mut BaseProviderContext,
+    out_len: *mut usize,
+) -> *mut u8 {
+    if ctx.is_null() || out_len.is_null() {
+        return ptr::null_mut();
+    }
+
+    let ctx_ref = unsafe { &mut *ctx };
+    let Some(recording) = ctx_ref.stop_recording() else {
+        return ptr::null_mut();
+    };
+    let bytes = recording.encode().into_boxed_slice();
+    unsafe { out_len.write(bytes.len()) };
+    Box::into_raw(bytes) as *mut u8
+}
+
+/// Free bytes returned by `base_provider_context_stop_recording`.
+///
+/// # Safety
+/// - `data` must be null or a pointer returned by `base_provider_context_stop_recording`, and
+///   `len` the length it wrote. It must not be freed twice.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tracks_free_recording(data: *mut u8, len: usize) {
+    if data.is_null() {
+        return;
+    }
+
+    unsafe { drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len))) };
+}
@@ -1,8 +1,10 @@
 use std::{
-    ffi::{CStr, c_char},
-    slice,
+    ffi::{CStr, CString, c_char},
+    ptr, slice,
 };
 
+use crate::point_definition::parse_error::{ParseError, ParseErrorKind};
+
 /// JSON FFI
 #[repr(C)]
 #[derive(Debug)]
@@ -138,16 +140,199 @@ pub(crate) unsafe fn convert_json_value_to_serde(
 }
 
 #[unsafe(no_mangle)]
-/// Free an `FFIJsonValue` previously produced that may contain heap allocations.
+/// Free the arrays allocated by `tracks_create_json_array` for an `FFIJsonValue`, including the
+/// ones nested inside its elements.
 ///
 /// # Safety
-/// - `json_value` must point to an `FFIJsonValue` produced by the corresponding create functions and not already freed.
-/// - This implementation only frees the top-level `JsonArray` if present; nested structures are not recursively freed.
+/// - `json_value` must be null or point to an `FFIJsonValue` built with the create functions whose
+///   arrays have not already been freed.
+/// - The `FFIJsonValue` itself and the element buffers passed to `tracks_create_json_array` stay
+///   owned by the caller. The top-level array pointer is reset to null, so freeing twice is a no-op.
 pub unsafe extern "C" fn tracks_free_json_value(json_value: *mut FFIJsonValue) {
-    // Free the memory allocated for the JsonValue
-    // This is a simple implementation that doesn't handle nested structures
-    // For a complete implementation, you would need to recursively free all nested elements
-    if !json_value.is_null() {
-        drop(unsafe { Box::from_raw(json_value) });
+    if json_value.is_null() {
+        return;
+    }
+
+    let json_value = unsafe { &mut *json_value };
+    if let JsonValueType::Array = json_value.value_type {
+        unsafe { free_json_array(json_value.data.array) };
+        json_value.data.array = ptr::null();
+    }
+}
+
+unsafe fn free_json_array(array: *const JsonArray) {
+    if array.is_null() {
+        return;
+    }
+
+    let array = unsafe { Box::from_raw(array as *mut JsonArray) };
+    if array.elements.is_null() {
+        return;
+    }
+
+    let elements = unsafe { slice::from_raw_parts(array.elements, array.length) };
+    for element in elements {
+        if let JsonValueType::Array = element.value_type {
+            unsafe { free_json_array(element.data.array) };
+        }
+    }
+}
+
+/// Why parsing JSON text failed. Mirrors `ParseErrorKind`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorCode {
+    Success = 0,
+    /// A required pointer argument was null.
+    NullArgument,
+    /// The requested value type is `Unknown`.
+    UnknownValueType,
+    InvalidJson,
+    NotAnArray,
+    EmptyArray,
+    WrongComponentCount,
+    MissingTime,
+    MissingValues,
+    MissingOperation,
+    UnknownOperation,
+    UnknownBase,
+    RotationOnlyOperation,
+    WrongControlCount,
+    UnsupportedSpline,
+    /// A `base*` provider couldn't be created for a reason other than an unknown name.
+    InvalidBase,
+}
+
+impl From<&ParseErrorKind> for ParseErrorCode {
+    fn from(kind: &ParseErrorKind) -> Self {
+        match kind {
+            ParseErrorKind::NotAnArray => ParseErrorCode::NotAnArray,
+            ParseErrorKind::EmptyArray => ParseErrorCode::EmptyArray,
+            ParseErrorKind::WrongComponentCount { .. } => ParseErrorCode::WrongComponentCount,
+            ParseErrorKind::MissingTime => ParseErrorCode::MissingTime,
+            ParseErrorKind::MissingValues => ParseErrorCode::MissingValues,
+            ParseErrorKind::MissingOperation => ParseErrorCode::MissingOperation,
+            ParseErrorKind::UnknownOperation(_) => ParseErrorCode::UnknownOperation,
+            ParseErrorKind::InvalidJson(_) => ParseErrorCode::InvalidJson,
+            ParseErrorKind::UnknownBase(_) => ParseErrorCode::UnknownBase,
+            ParseErrorKind::Base(_) => ParseErrorCode::InvalidBase,
+            ParseErrorKind::RotationOnlyOperation(_) => ParseErrorCode::RotationOnlyOperation,
+            ParseErrorKind::WrongControlCount { .. } => ParseErrorCode::WrongControlCount,
+            ParseErrorKind::UnsupportedSpline(_) => ParseErrorCode::UnsupportedSpline,
+        }
+    }
+}
+
+/// Error reported by the functions parsing JSON text.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FFIParseError {
+    pub code: ParseErrorCode,
+    /// Null-terminated description including the JSON path of the offending value, or null on
+    /// success. Free it with `tracks_free_parse_error`.
+    pub message: *mut c_char,
+}
+
+impl FFIParseError {
+    pub(crate) fn success() -> Self {
+        Self {
+            code: ParseErrorCode::Success,
+            message: ptr::null_mut(),
+        }
+    }
+
+    pub(crate) fn new(code: ParseErrorCode, message: &str) -> Self {
+        // Messages come from `Display` impls and JSON input; drop interior nuls rather than the message
+        let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+        Self {
+            code,
+            message: message.into_raw(),
+        }
+    }
+}
+
+impl From<&ParseError> for FFIParseError {
+    fn from(error: &ParseError) -> Self {
+        FFIParseError::new((&error.kind).into(), &error.to_string())
+    }
+}
+
+/// Writes `error` to `out_error` if it is not null.
+///
+/// # Safety
+/// - `out_error` must be null or valid for writes.
+pub(crate) unsafe fn write_parse_error(out_error: *mut FFIParseError, error: FFIParseError) {
+    if out_error.is_null() {
+        // Nobody will free the message
+        if !error.message.is_null() {
+            drop(unsafe { CString::from_raw(error.message) });
+        }
+        return;
+    }
+    unsafe { out_error.write(error) };
+}
+
+#[unsafe(no_mangle)]
+/// Free the message of an `FFIParseError` filled in by a parsing function.
+///
+/// # Safety
+/// - `error` must be null or point to an `FFIParseError` written by this library.
+/// - The message pointer is reset to null, so freeing twice is a no-op.
+pub unsafe extern "C" fn tracks_free_parse_error(error: *mut FFIParseError) {
+    if error.is_null() {
+        return;
+    }
+
+    let error = unsafe { &mut *error };
+    if !error.message.is_null() {
+        drop(unsafe { CString::from_raw(error.message) });
+        error.message = ptr::null_mut();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_json_value_releases_nested_arrays() {
+        unsafe {
+            let inner = [
+                tracks_create_json_number(1.0),
+                tracks_create_json_number(2.0),
+            ];
+            let nested = [tracks_create_json_array(inner.as_ptr(), inner.len())];
+            let mut outer = tracks_create_json_array(nested.as_ptr(), nested.len());
+
+            let value = convert_json_value_to_serde(&outer);
+            assert_eq!(value, serde_json::json!([[1.0, 2.0]]));
+
+            tracks_free_json_value(&mut outer);
+            assert!(outer.data.array.is_null());
+            // Already freed
+            tracks_free_json_value(&mut outer);
+
+            let mut number = tracks_create_json_number(1.0);
+            tracks_free_json_value(&mut number);
+            tracks_free_json_value(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn parse_error_message_includes_path() {
+        let error = ParseError::new(
+            ParseErrorKind::UnknownOperation("opBogus".to_string()),
+            crate::point_definition::parse_error::JsonPath::new()
+                .child(1)
+                .child(2),
+        );
+        let mut ffi_error = FFIParseError::from(&error);
+        assert_eq!(ffi_error.code, ParseErrorCode::UnknownOperation);
+
+        let message = unsafe { CStr::from_ptr(ffi_error.message) };
+        assert_eq!(message.to_str().unwrap(), error.to_string());
+
+        unsafe { tracks_free_parse_error(&mut ffi_error) };
+        assert!(ffi_error.message.is_null());
     }
 }
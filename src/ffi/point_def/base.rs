@@ -1,17 +1,16 @@
-use glam::Vec4;
+use std::{
+    ffi::{CStr, c_char},
+    ptr, slice,
+};
 
 use crate::{
     base_provider_context::BaseProviderContext,
     base_value::WrapBaseValueType,
     ffi::{
-        json::{self, FFIJsonValue},
+        json::{self, FFIJsonValue, FFIParseError, ParseErrorCode},
         types::WrapBaseValue,
     },
-    point_definition::{
-        PointDefinitionLike, base_point_definition, basic_point_definition::BasicPointDefinition,
-        quaternion_point_definition::QuaternionPointDefinition,
-        vector3_point_definition::Vector3PointDefinition,
-    },
+    point_definition::{PointDefinitionLike, base_point_definition},
 };
 
 /// BASE POINT DEFINITION
@@ -30,21 +29,67 @@ pub unsafe extern "C" fn tracks_make_base_point_definition(
     let value = unsafe { json::convert_json_value_to_serde(json) };
     let context = unsafe { &mut *context };
 
-    let point_definition: base_point_definition::BasePointDefinition = match ty {
-        WrapBaseValueType::Vec3 => Vector3PointDefinition::parse(value, context).into(),
-        WrapBaseValueType::Quat => QuaternionPointDefinition::parse(value, context).into(),
-        WrapBaseValueType::Vec4 => BasicPointDefinition::<Vec4>::parse(value, context).into(),
-        WrapBaseValueType::Float => BasicPointDefinition::<f32>::parse(value, context).into(),
-        WrapBaseValueType::Unknown => {
-            panic!("Cannot create BasePointDefinition with Unknown type");
-        }
-    };
+    let point_definition =
+        base_point_definition::BasePointDefinition::try_parse_typed(&value, ty, context)
+            .unwrap_or_else(|e| panic!("{e}"));
 
     let point_definition = Box::new(point_definition);
 
     (Box::leak(point_definition)) as _
 }
 
+/// Parse a base point definition from JSON text.
+///
+/// `json` points to `len` bytes of UTF-8 JSON and does not need to be null-terminated, so hosts
+/// can pass a slice of a larger buffer such as the beatmap file. Prefer this over building an
+/// `FFIJsonValue` tree for `tracks_make_base_point_definition`.
+///
+/// Returns null on failure. `out_error` then receives the error code and a message that must be
+/// freed with `tracks_free_parse_error`; on success it is set to `Success` with a null message.
+///
+/// # Safety
+/// - `context` must be a valid, non-null pointer to a live `BaseProviderContext` for the duration of this call.
+/// - `json` must point to at least `len` readable bytes.
+/// - `out_error` may be null; if non-null it must be valid for writes.
+/// - The returned pointer is owned by the caller and must be freed by calling `base_point_definition_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tracks_parse_base_point_definition_str(
+    context: *mut BaseProviderContext,
+    json: *const c_char,
+    len: usize,
+    ty: WrapBaseValueType,
+    out_error: *mut FFIParseError,
+) -> *mut base_point_definition::BasePointDefinition {
+    let fail = |error| {
+        unsafe { json::write_parse_error(out_error, error) };
+        ptr::null_mut()
+    };
+
+    if context.is_null() || json.is_null() {
+        return fail(FFIParseError::new(
+            ParseErrorCode::NullArgument,
+            "context and json must not be null",
+        ));
+    }
+    if ty == WrapBaseValueType::Unknown {
+        return fail(FFIParseError::new(
+            ParseErrorCode::UnknownValueType,
+            "cannot parse a point definition of Unknown type",
+        ));
+    }
+
+    let json = unsafe { slice::from_raw_parts(json as *const u8, len) };
+    let context = unsafe { &mut *context };
+
+    match base_point_definition::BasePointDefinition::try_parse_typed_slice(json, ty, context) {
+        Ok(point_definition) => {
+            unsafe { json::write_parse_error(out_error, FFIParseError::success()) };
+            Box::into_raw(Box::new(point_definition))
+        }
+        Err(error) => fail(FFIParseError::from(&error)),
+    }
+}
+
 /// BASE POINT DEFINITION FREE
 ///
 /// # Safety
@@ -88,7 +133,7 @@ pub unsafe extern "C" fn tracks_interpolate_base_point_definition(
 
 /// Return number of points in the point definition.
 ///
-/// Safety:
+/// # Safety
 /// - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn tracks_base_point_definition_count(
@@ -100,7 +145,7 @@ pub unsafe extern "C" fn tracks_base_point_definition_count(
 
 /// Check whether the point definition references a base provider.
 ///
-/// Safety:
+/// # Safety
 /// - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn tracks_base_point_definition_has_base_provider(
@@ -110,8 +155,28 @@ pub unsafe extern "C" fn tracks_base_point_definition_has_base_provider(
     point_definition.has_base_provider()
 }
 
+/// Check whether sampling the point definition reads `base`, directly or through swizzles,
+/// smoothing or velocity. Hosts can skip updating bases no point definition reads.
+///
+/// # Safety
+/// - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
+/// - `base` must be null or a valid null-terminated C string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tracks_base_point_definition_reads_base(
+    point_definition: *const base_point_definition::BasePointDefinition,
+    base: *const c_char,
+) -> bool {
+    if base.is_null() {
+        return false;
+    }
+    let point_definition = unsafe { &*point_definition };
+    let base = unsafe { CStr::from_ptr(base) };
+    base.to_str()
+        .is_ok_and(|base| point_definition.base_dependencies().contains_base(base))
+}
+
 /// Get the `WrapBaseValueType` of the point definition.
-/// Safety:
+/// # Safety
 /// - `point_definition` must be a valid, non-null pointer to a `BasePointDefinition`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn tracks_base_point_definition_get_type(
@@ -120,3 +185,122 @@ pub unsafe extern "C" fn tracks_base_point_definition_get_type(
     let point_definition = unsafe { &*point_definition };
     point_definition.get_type()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_value::BaseValue;
+    use crate::ffi::json::tracks_free_parse_error;
+
+    unsafe fn parse(
+        context: &mut BaseProviderContext,
+        json: &str,
+        ty: WrapBaseValueType,
+    ) -> (
+        *mut base_point_definition::BasePointDefinition,
+        FFIParseError,
+    ) {
+        let mut error = FFIParseError::success();
+        let point_definition = unsafe {
+            tracks_parse_base_point_definition_str(
+                context,
+                json.as_ptr() as *const c_char,
+                json.len(),
+                ty,
+                &mut error,
+            )
+        };
+        (point_definition, error)
+    }
+
+    #[test]
+    fn test_parse_base_point_definition_str() {
+        let mut context = BaseProviderContext::new();
+        // Only the definition inside the surrounding beatmap text is passed in
+        let beatmap = r#"{"dissolve":[[0,0],[1,1,"easeInQuad"]],"track":"a"}"#;
+        let start = beatmap.find('[').unwrap();
+        let end = beatmap.find(",\"track\"").unwrap();
+
+        unsafe {
+            let (point_definition, error) =
+                parse(&mut context, &beatmap[start..end], WrapBaseValueType::Float);
+            assert_eq!(error.code, ParseErrorCode::Success);
+            assert!(error.message.is_null());
+            assert!(!point_definition.is_null());
+
+            assert_eq!(tracks_base_point_definition_count(point_definition), 2);
+            let (value, _) = (*point_definition).interpolate(1.0, &context);
+            assert_eq!(value, BaseValue::Float(1.0));
+
+            base_point_definition_free(point_definition);
+        }
+    }
+
+    #[test]
+    fn test_reads_base() {
+        let mut context = BaseProviderContext::new();
+        let json = r#"[["baseHeadPosition.s2.x", 0], [1, 1, ["baseCombo", "opMul"]]]"#;
+
+        unsafe {
+            let (point_definition, error) = parse(&mut context, json, WrapBaseValueType::Float);
+            assert_eq!(error.code, ParseErrorCode::Success);
+
+            let reads = |base: &CStr| {
+                tracks_base_point_definition_reads_base(point_definition, base.as_ptr())
+            };
+            assert!(reads(c"baseHeadPosition"));
+            assert!(reads(c"baseCombo"));
+            assert!(!reads(c"baseSongTime"));
+            assert!(!tracks_base_point_definition_reads_base(
+                point_definition,
+                ptr::null()
+            ));
+
+            base_point_definition_free(point_definition);
+        }
+    }
+
+    #[test]
+    fn test_parse_base_point_definition_str_errors() {
+        let mut context = BaseProviderContext::new();
+        let cases = [
+            (
+                "[[0, 0], [1, ",
+                WrapBaseValueType::Float,
+                ParseErrorCode::InvalidJson,
+            ),
+            ("{}", WrapBaseValueType::Float, ParseErrorCode::NotAnArray),
+            (
+                "[[0, [1, \"opBogus\"], 0]]",
+                WrapBaseValueType::Float,
+                ParseErrorCode::UnknownOperation,
+            ),
+            (
+                "[[0, 0]]",
+                WrapBaseValueType::Unknown,
+                ParseErrorCode::UnknownValueType,
+            ),
+        ];
+
+        for (json, ty, code) in cases {
+            unsafe {
+                let (point_definition, mut error) = parse(&mut context, json, ty);
+                assert!(point_definition.is_null());
+                assert_eq!(error.code, code, "{json}");
+                assert!(!CStr::from_ptr(error.message).to_bytes().is_empty());
+                tracks_free_parse_error(&mut error);
+            }
+        }
+
+        unsafe {
+            let point_definition = tracks_parse_base_point_definition_str(
+                ptr::null_mut(),
+                ptr::null(),
+                0,
+                WrapBaseValueType::Float,
+                ptr::null_mut(),
+            );
+            assert!(point_definition.is_null());
+        }
+    }
+}
@@ -9,17 +9,34 @@ use crate::{
     },
     base_provider_context::BaseProviderContext,
     ffi::types::{FloatOption, QuatOption, Vec3Option, Vec4Option},
+    shared::Shared,
 };
 use std::{
     ffi::{CStr, CString, c_char},
     ptr,
-    rc::Rc,
 };
 
 // C-compatible callback function type for game object modifications
 // Parameters: game_object, was_added (true for added, false for removed), user_data
 pub type CGameObjectCallback = extern "C" fn(GameObject, bool, *mut std::ffi::c_void);
 
+/// User data passed back to a `CGameObjectCallback`.
+#[derive(Clone, Copy)]
+struct CallbackUserData(*mut std::ffi::c_void);
+
+impl CallbackUserData {
+    fn get(self) -> *mut std::ffi::c_void {
+        self.0
+    }
+}
+
+// SAFETY: the host owns the user data; with the `sync` feature tracks may be moved to other
+// threads, but callbacks only run where the host adds or removes game objects
+#[cfg(feature = "sync")]
+unsafe impl Send for CallbackUserData {}
+#[cfg(feature = "sync")]
+unsafe impl Sync for CallbackUserData {}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct TrackKeyFFI(u64);
@@ -477,7 +494,7 @@ pub unsafe extern "C" fn track_get_path_property(
 
 /// Return a `CPropertiesMap` with pointers into the track's registered properties.
 ///
-/// Safety:
+/// # Safety
 /// - `track` must be a valid, non-null pointer to a `Track`.
 /// - The returned pointers are valid only while the `Track` is alive and not mutated in a way that moves or removes the properties.
 /// - Do not retain these pointers across calls that might mutate the track.
@@ -510,7 +527,7 @@ pub unsafe extern "C" fn track_get_properties_map(track: *mut Track) -> CPropert
 
 /// Return a `CPathPropertiesMap` with pointers into the track's path properties.
 ///
-/// Safety:
+/// # Safety
 /// - `track` must be a valid, non-null pointer to a `Track`.
 /// - Returned pointers are valid only while the track's path properties remain in-place.
 #[unsafe(no_mangle)]
@@ -537,7 +554,7 @@ pub unsafe extern "C" fn track_get_path_properties_map(track: *mut Track) -> CPa
 }
 
 /// Return a `CPropertiesValues` with the current values of the track's properties.
-/// Safety:
+/// # Safety
 /// - `track` must be a valid, non-null pointer to a `Track
 /// - The returned struct contains copies of the current property values.
 #[unsafe(no_mangle)]
@@ -569,7 +586,7 @@ pub unsafe extern "C" fn track_get_properties_values(track: *mut Track) -> CProp
 }
 
 /// Return a `CPathPropertiesValues` with the interpolated values of the track's path properties at the given time.
-/// Safety:
+/// # Safety
 /// - `track` must be a valid, non-null pointer to a `Track`.
 /// - `ctx` must be a valid, non-null pointer to a `BaseProviderContext`.
 ///
@@ -631,7 +648,7 @@ pub unsafe extern "C" fn track_get_path_properties_values(
 // FFI functions for per-track game object modification callbacks
 /// Register a C callback to be invoked when a game object is added/removed.
 ///
-/// Safety:
+/// # Safety
 /// - `track` must be a valid pointer to a `Track`.
 /// - `callback` and `user_data` must remain valid for as long as the callback may be invoked.
 /// - The returned pointer is an opaque handle to the stored Rust closure; it must be removed with `track_remove_game_object_callback`.
@@ -651,20 +668,21 @@ pub unsafe extern "C" fn track_register_game_object_callback(
     unsafe {
         let track_ref = &mut *track;
         // Create a closure that captures the C callback and user data
+        let user_data = CallbackUserData(user_data);
         let rust_callback = move |game_object: GameObject, was_added: bool| {
-            callback(game_object, was_added, user_data);
+            callback(game_object, was_added, user_data.get());
         };
 
-        let rc = Rc::new(rust_callback);
+        let rc = Shared::new(rust_callback);
 
         track_ref.register_game_object_callback(rc.clone());
-        Rc::into_raw(rc) as *const fn(GameObject, bool)
+        Shared::into_raw(rc) as *const fn(GameObject, bool)
     }
 }
 
 /// Remove a previously registered game object callback.
 ///
-/// Safety:
+/// # Safety
 /// - `track` must be a valid pointer to a `Track`.
 /// - `callback` must be a pointer previously returned by `track_register_game_object_callback`.
 /// - After calling this function the `callback` pointer must not be used again.
@@ -680,7 +698,7 @@ pub unsafe extern "C" fn track_remove_game_object_callback(
     unsafe {
         let track_ref = &mut *track;
         // Create a closure that matches the one we want to remove
-        let rc: Rc<fn(GameObject, bool)> = Rc::from_raw(callback);
+        let rc: Shared<fn(GameObject, bool)> = Shared::from_raw(callback);
 
         track_ref.remove_game_object_callback(rc);
     }
@@ -708,6 +726,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_track_set_get_name_and_destroy() {
         unsafe {
             let track = track_create();
@@ -728,6 +747,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_track_register_unregister_game_object_and_callbacks() {
         unsafe {
             let track = track_create();
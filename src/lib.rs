@@ -6,6 +6,7 @@
 //! - `base_provider_context` — runtime "bases" (score, colors, transforms, time).
 //!   - Creation: `BaseProviderContext::new()` (Rust) or `base_provider_context_create()` (FFI).
 //!   - Set/get: `set_values` / `get_values`, and FFI wrappers in `src/ffi/base_provider_context.rs`.
+//!   - Custom bases: `register_base` adds a named, typed base that point definitions can reference.
 //! - `point_definition` — parsing and sampling of point definitions. See the `PointDefinition` trait
 //!   (`parse`, `interpolate` / `interpolate_points`) and concrete implementations in
 //!   `src/point_definition/*` (float, vec3, vec4, quaternion).
@@ -16,6 +17,9 @@
 //! - `ffi` (optional) — C-compatible bindings and factories for hosts; check `src/ffi/mod.rs` and
 //!   `src/ffi/base_provider_context.rs` for how hosts create/drive the runtime.
 //! - `animation` + `quaternion_utils` — helpers for applying sampled values and rotation math.
+//! - `recording` — captures base inputs from a context and replays them, for bug reports and tests.
+//! - `shared` — `Rc`/`RefCell` aliases that become `Arc` and locks with the `sync` feature.
+//! - `beatmap` (json) — loads a beatmap's custom events into tracks and `EventData`.
 //!
 //! Runtime flow (high level): host updates `BaseProviderContext` → events register tracks/point
 //! definitions → sampling uses `PointDefinition` + `PointData` and may query `BaseProviderContext`
@@ -27,6 +31,7 @@
 #![feature(generic_const_exprs)]
 #![feature(generic_const_items)]
 #![feature(associated_type_defaults)]
+#![allow(incomplete_features)]
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
@@ -36,6 +41,9 @@ pub mod test_helpers;
 
 pub mod animation;
 pub mod base_provider_context;
+#[cfg(feature = "json")]
+pub mod beatmap;
+pub(crate) mod codec;
 pub mod easings;
 pub mod modifiers;
 pub mod point_data;
@@ -46,6 +54,8 @@ pub mod base_value;
 pub mod value_types;
 
 pub mod quaternion_utils;
+pub mod recording;
+pub mod shared;
 
 /// Lightweight prelude for external consumers.
 ///
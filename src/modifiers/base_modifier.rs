@@ -7,6 +7,7 @@ use crate::{
         quaternion_modifier::QuaternionModifier,
     },
     prelude::BaseProviderContext,
+    providers::dependencies::BaseDependencies,
 };
 
 /// Modifiers represent small arithmetic transformations applied to point data.
@@ -97,4 +98,13 @@ impl ModifierLike<BaseValue> for BaseModifier {
             BaseModifier::Quaternion(modifier) => modifier.has_base_provider(),
         }
     }
+
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies) {
+        match self {
+            BaseModifier::Float(modifier) => modifier.collect_dependencies(dependencies),
+            BaseModifier::Vector3(modifier) => modifier.collect_dependencies(dependencies),
+            BaseModifier::Vector4(modifier) => modifier.collect_dependencies(dependencies),
+            BaseModifier::Quaternion(modifier) => modifier.collect_dependencies(dependencies),
+        }
+    }
 }
@@ -1,7 +1,14 @@
-use super::{ModifierLike, operation::Operation};
-use super::{ModifierValues, shared_has_base_provider};
+#[cfg(feature = "json")]
+use serde_json::Value as JsonValue;
+
+use super::{
+    ModifierLike,
+    operation::{Operation, clamp_components},
+};
+use super::{ModifierValues, Step, hoist_static, shared_has_base_provider};
 use crate::base_provider_context::BaseProviderContext;
 use crate::providers::AbstractValueProvider;
+use crate::providers::dependencies::BaseDependencies;
 use crate::value_types::ValueType;
 
 /// A basic, typed modifier that applies component-wise operations to point values.
@@ -10,12 +17,22 @@ use crate::value_types::ValueType;
 /// a list of nested modifiers, and an `Operation` describing how to combine
 /// nested modifier results with the base point. It implements `ModifierLike<T>`
 /// for use by the point-definition parsing and evaluation machinery.
+///
+/// Static parts of the tree are folded when the modifier is built, while `values` and `modifiers`
+/// keep the tree as written for serialization.
 #[derive(Debug, Clone)]
 pub struct BasicModifier<T: ValueType> {
     values: ModifierValues<T>,
     has_base_provider: bool,
     modifiers: Vec<BasicModifier<T>>,
     operation: Operation,
+    /// `opClamp` only: the upper bound, with the same nested modifiers. This modifier is the lower
+    /// bound.
+    upper: Option<Box<BasicModifier<T>>>,
+    /// The result, if the values and all nested modifiers are static
+    folded: Option<T>,
+    /// Nested modifiers with their static start folded, if it could be
+    hoisted: Option<Box<[Step<T>]>>,
 }
 
 impl<T: ValueType> BasicModifier<T>
@@ -29,23 +46,81 @@ where
     ) -> Self {
         let has_base_provider =
             shared_has_base_provider(matches!(point, ModifierValues::Dynamic(_)), &modifiers);
+        let original = point.as_static_values().copied();
+        let folded =
+            original.and_then(|value| modifiers.iter().try_fold(value, |acc, m| m.fold_into(acc)));
+        let hoisted = match folded {
+            Some(_) => None,
+            None => hoist_static(
+                original,
+                modifiers.iter().map(|m| (m.operation, m.static_operand())),
+            ),
+        };
         Self {
             values: point,
             has_base_provider,
             modifiers,
             operation,
+            upper: None,
+            folded,
+            hoisted,
         }
     }
-}
 
-impl<T: ValueType> ModifierLike<T> for BasicModifier<T>
-where
-    [(); T::VALUE_COUNT]:,
-{
-    const VALUE_COUNT: usize = T::VALUE_COUNT;
+    /// An `opClamp` modifier from its bounds, both built with `Operation::None` and the same
+    /// nested modifiers.
+    #[cfg(feature = "extended_ops")]
+    pub fn clamp(lower: Self, upper: Self) -> Self {
+        Self {
+            has_base_provider: lower.has_base_provider || upper.has_base_provider,
+            operation: Operation::Clamp,
+            upper: Some(Box::new(upper)),
+            ..lower
+        }
+    }
 
-    fn get_modified_point(&self, context: &BaseProviderContext) -> T {
-        let original_point = match &self.values {
+    pub fn values(&self) -> &ModifierValues<T> {
+        &self.values
+    }
+
+    pub fn modifiers(&self) -> &[BasicModifier<T>] {
+        &self.modifiers
+    }
+
+    /// The upper bound of an `opClamp` modifier, whose own values are the lower bound.
+    pub fn upper_bound(&self) -> Option<&BasicModifier<T>> {
+        self.upper.as_deref()
+    }
+
+    /// Combines `base` with this modifier's result using its operation, as its parent does.
+    fn combine_into(&self, base: T, context: &BaseProviderContext) -> T {
+        match &self.upper {
+            Some(upper) => clamp_components(
+                base,
+                self.get_modified_point(context),
+                upper.get_modified_point(context),
+            ),
+            None => self
+                .operation
+                .combine(base, self.get_modified_point(context)),
+        }
+    }
+
+    /// [`BasicModifier::combine_into`] for static modifiers, `None` if this one reads providers.
+    fn fold_into(&self, base: T) -> Option<T> {
+        match &self.upper {
+            Some(upper) => Some(clamp_components(base, self.folded?, upper.folded?)),
+            None => Some(self.operation.combine(base, self.folded?)),
+        }
+    }
+
+    /// The folded value if combining with it only takes the operation, i.e. unless it's a clamp.
+    fn static_operand(&self) -> Option<T> {
+        self.folded.filter(|_| self.upper.is_none())
+    }
+
+    fn original_point(&self, context: &BaseProviderContext) -> T {
+        match &self.values {
             ModifierValues::Static(s) => *s,
             ModifierValues::Dynamic(value_providers) => {
                 let mut values = [0.0; T::VALUE_COUNT];
@@ -61,16 +136,52 @@ where
                 }
                 T::from_translate_array(values)
             }
+        }
+    }
+
+    fn evaluate_steps(&self, steps: &[Step<T>], context: &BaseProviderContext) -> T {
+        let (original_point, steps) = match steps {
+            // The values are replaced by the first step, skip reading them
+            [Step::Static(Operation::None, value), rest @ ..] => (*value, rest),
+            _ => (self.original_point(context), steps),
         };
+        steps.iter().fold(original_point, |acc, step| match step {
+            Step::Static(operation, value) => operation.combine(acc, *value),
+            Step::Dynamic(index) => self.modifiers[*index].combine_into(acc, context),
+        })
+    }
+
+    /// Writes the modifier as a Heck modifier array: values, nested modifiers, then the `op*` flag.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> JsonValue {
+        let mut elements = self.values.to_json();
+        if let Some(upper) = &self.upper {
+            elements.extend(upper.values.to_json());
+        }
+        elements.extend(self.modifiers.iter().map(BasicModifier::to_json));
+        elements.push(self.operation.as_str().into());
+        JsonValue::Array(elements)
+    }
+}
+
+impl<T: ValueType> ModifierLike<T> for BasicModifier<T>
+where
+    [(); T::VALUE_COUNT]:,
+{
+    const VALUE_COUNT: usize = T::VALUE_COUNT;
+
+    fn get_modified_point(&self, context: &BaseProviderContext) -> T {
+        if let Some(folded) = self.folded {
+            return folded;
+        }
+        if let Some(steps) = &self.hoisted {
+            return self.evaluate_steps(steps, context);
+        }
+
+        let original_point = self.original_point(context);
         self.modifiers
             .iter()
-            .fold(original_point, |acc, x| match x.get_operation() {
-                Operation::Add => acc + x.get_modified_point(context),
-                Operation::Sub => acc - x.get_modified_point(context),
-                Operation::Mul => acc * x.get_modified_point(context),
-                Operation::Div => acc / x.get_modified_point(context),
-                Operation::None => x.get_modified_point(context),
-            })
+            .fold(original_point, |acc, x| x.combine_into(acc, context))
     }
 
     fn get_raw_point(&self) -> T {
@@ -84,4 +195,14 @@ where
     fn has_base_provider(&self) -> bool {
         self.has_base_provider
     }
+
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies) {
+        self.values.collect_dependencies(dependencies);
+        if let Some(upper) = &self.upper {
+            upper.values.collect_dependencies(dependencies);
+        }
+        for modifier in &self.modifiers {
+            modifier.collect_dependencies(dependencies);
+        }
+    }
 }
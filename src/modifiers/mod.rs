@@ -6,9 +6,16 @@ pub mod quaternion_modifier;
 
 use smallvec::SmallVec;
 
+#[cfg(feature = "json")]
+use serde_json::Value as JsonValue;
+
 use crate::base_provider_context::BaseProviderContext;
 use crate::modifiers::operation::Operation;
+use crate::providers::dependencies::BaseDependencies;
 use crate::providers::{AbstractValueProvider, ValueProvider};
+#[cfg(feature = "json")]
+use crate::providers::{json_number, serialize_values};
+use crate::value_types::ValueType;
 
 /// Representation of modifier input values.
 ///
@@ -38,6 +45,24 @@ impl<T> ModifierValues<T> {
             _ => None,
         }
     }
+
+    /// Adds the dependencies of dynamic values, static values have none.
+    pub fn collect_dependencies(&self, dependencies: &mut BaseDependencies) {
+        if let ModifierValues::Dynamic(values) = self {
+            dependencies.add_providers(values);
+        }
+    }
+}
+
+impl<T: ValueType> ModifierValues<T> {
+    /// Writes the values as they appear in a point definition.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Vec<JsonValue> {
+        match self {
+            ModifierValues::Static(s) => s.as_slice().iter().map(|v| json_number(*v)).collect(),
+            ModifierValues::Dynamic(values) => serialize_values(values),
+        }
+    }
 }
 
 /// Trait implemented by concrete modifier types.
@@ -64,6 +89,16 @@ pub trait ModifierLike<T> {
     /// Whether this modifier depends on a base provider (strings like `baseX`).
     fn has_base_provider(&self) -> bool;
 
+    /// Adds the bases and stateful providers read by this modifier and its nested modifiers.
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies);
+
+    /// The bases and stateful providers read by this modifier and its nested modifiers.
+    fn base_dependencies(&self) -> BaseDependencies {
+        let mut dependencies = BaseDependencies::new();
+        self.collect_dependencies(&mut dependencies);
+        dependencies
+    }
+
     /// The component-wise operation that composes this modifier (add, mul, ...).
     fn get_operation(&self) -> Operation;
 
@@ -99,3 +134,113 @@ pub fn shared_has_base_provider<T: ModifierLike<V>, V>(is_dynamic: bool, modifie
         false => modifiers.iter().any(|m| m.has_base_provider()),
     }
 }
+
+/// A nested modifier as evaluated after folding, see [`hoist_static`].
+#[derive(Clone, Debug)]
+pub(crate) enum Step<T> {
+    /// A static value, or with `Operation::None` the static values the walk starts from.
+    Static(Operation, T),
+    /// Index of a dynamic nested modifier, evaluated as written.
+    Dynamic(usize),
+}
+
+/// Folds the static start of a partly dynamic modifier so it isn't recomputed on every sample.
+///
+/// `original` is the modifier's own value if it is static, and `modifiers` yields each nested
+/// modifier's operation and its folded value, or `None` if it reads providers. A static value,
+/// or a static `opNone` modifier, absorbs the static modifiers after it up to the first dynamic
+/// one. The operations are applied in order, so the result is bit-identical to the unfolded walk;
+/// static runs after a dynamic modifier are left alone, as reassociating them wouldn't be.
+/// Returns `None` if nothing could be folded.
+pub(crate) fn hoist_static<T: ValueType>(
+    original: Option<T>,
+    modifiers: impl ExactSizeIterator<Item = (Operation, Option<T>)>,
+) -> Option<Box<[Step<T>]>>
+where
+    [(); T::VALUE_COUNT]:,
+{
+    let count = modifiers.len() + original.is_some() as usize;
+    let mut steps: Vec<Step<T>> = original
+        .map(|value| Step::Static(Operation::None, value))
+        .into_iter()
+        .collect();
+
+    for (index, (operation, value)) in modifiers.enumerate() {
+        let Some(value) = value else {
+            steps.push(Step::Dynamic(index));
+            continue;
+        };
+        match steps.last_mut() {
+            _ if operation == Operation::None => {
+                // Replaces everything before it
+                steps.clear();
+                steps.push(Step::Static(Operation::None, value));
+            }
+            Some(Step::Static(Operation::None, acc)) => *acc = operation.combine(*acc, value),
+            _ => steps.push(Step::Static(operation, value)),
+        }
+    }
+
+    (steps.len() < count).then(|| steps.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(original: Option<f32>, modifiers: &[(Operation, Option<f32>)]) -> Vec<String> {
+        hoist_static(original, modifiers.iter().copied())
+            .map(|steps| steps.iter().map(|step| format!("{step:?}")).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn folds_static_prefix() {
+        // A static value absorbs the static modifiers after it
+        assert_eq!(
+            steps(
+                Some(1.0),
+                &[
+                    (Operation::Add, Some(2.0)),
+                    (Operation::Mul, Some(3.0)),
+                    (Operation::Add, None)
+                ]
+            ),
+            ["Static(None, 9.0)", "Dynamic(2)"]
+        );
+        // Folding one modifier into the static value is enough to hoist
+        assert_eq!(
+            steps(
+                Some(1.0),
+                &[(Operation::Add, Some(2.0)), (Operation::Mul, None)]
+            ),
+            ["Static(None, 3.0)", "Dynamic(1)"]
+        );
+        // `opNone` drops everything before it
+        assert_eq!(
+            steps(
+                None,
+                &[
+                    (Operation::Add, None),
+                    (Operation::None, Some(5.0)),
+                    (Operation::Add, Some(1.0))
+                ]
+            ),
+            ["Static(None, 6.0)"]
+        );
+        // Static runs after a dynamic modifier aren't reassociated
+        assert!(
+            steps(
+                None,
+                &[
+                    (Operation::Add, Some(2.0)),
+                    (Operation::Sub, Some(3.0)),
+                    (Operation::Mul, None),
+                    (Operation::Mul, Some(4.0)),
+                    (Operation::Div, Some(8.0)),
+                ]
+            )
+            .is_empty()
+        );
+    }
+}
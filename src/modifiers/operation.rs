@@ -1,3 +1,5 @@
+use crate::value_types::ValueType;
+
 /// Component-wise operation applied by modifiers.
 ///
 /// `Operation` describes how a nested modifier's result should combine with
@@ -14,6 +16,30 @@ pub enum Operation {
     Mul,
     /// Divide the base value by the modifier value.
     Div,
+    /// Rotations only: rotates the base rotation by the modifier's rotation, composing them as
+    /// quaternions instead of adding Euler angles.
+    Rotate,
+    /// Rotations only: undoes `Rotate`, rotating by the inverse of the modifier's rotation.
+    InverseRotate,
+    /// Smaller of the base and modifier value.
+    #[cfg(feature = "extended_ops")]
+    Min,
+    /// Larger of the base and modifier value.
+    #[cfg(feature = "extended_ops")]
+    Max,
+    /// Base value modulo the modifier value, wrapping negative values like `rem_euclid`.
+    #[cfg(feature = "extended_ops")]
+    Mod,
+    /// Base value raised to the power of the modifier value.
+    #[cfg(feature = "extended_ops")]
+    Pow,
+    /// Absolute value of the base value. The modifier's value is ignored.
+    #[cfg(feature = "extended_ops")]
+    Abs,
+    /// Clamps the base value between a lower and an upper bound. The modifier lists the lower
+    /// bounds followed by the upper bounds, e.g. `[0, 1, "opClamp"]` for a float.
+    #[cfg(feature = "extended_ops")]
+    Clamp,
 }
 
 impl std::str::FromStr for Operation {
@@ -25,7 +51,150 @@ impl std::str::FromStr for Operation {
             "opSub" => Ok(Self::Sub),
             "opMul" => Ok(Self::Mul),
             "opDiv" => Ok(Self::Div),
-            _ => Ok(Self::None),
+            "opNone" => Ok(Self::None),
+            "opRotate" => Ok(Self::Rotate),
+            "opInverseRotate" => Ok(Self::InverseRotate),
+            #[cfg(feature = "extended_ops")]
+            "opMin" => Ok(Self::Min),
+            #[cfg(feature = "extended_ops")]
+            "opMax" => Ok(Self::Max),
+            #[cfg(feature = "extended_ops")]
+            "opMod" => Ok(Self::Mod),
+            #[cfg(feature = "extended_ops")]
+            "opPow" => Ok(Self::Pow),
+            #[cfg(feature = "extended_ops")]
+            "opAbs" => Ok(Self::Abs),
+            #[cfg(feature = "extended_ops")]
+            "opClamp" => Ok(Self::Clamp),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Operation {
+    /// The Heck flag for this operation, e.g. `opAdd`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "opNone",
+            Self::Add => "opAdd",
+            Self::Sub => "opSub",
+            Self::Mul => "opMul",
+            Self::Div => "opDiv",
+            Self::Rotate => "opRotate",
+            Self::InverseRotate => "opInverseRotate",
+            #[cfg(feature = "extended_ops")]
+            Self::Min => "opMin",
+            #[cfg(feature = "extended_ops")]
+            Self::Max => "opMax",
+            #[cfg(feature = "extended_ops")]
+            Self::Mod => "opMod",
+            #[cfg(feature = "extended_ops")]
+            Self::Pow => "opPow",
+            #[cfg(feature = "extended_ops")]
+            Self::Abs => "opAbs",
+            #[cfg(feature = "extended_ops")]
+            Self::Clamp => "opClamp",
+        }
+    }
+
+    /// Whether the operation composes rotations and is only valid in rotation modifiers.
+    pub fn is_rotation(&self) -> bool {
+        matches!(self, Self::Rotate | Self::InverseRotate)
+    }
+
+    /// Combines one component of the base value with the matching component of the modifier value.
+    ///
+    /// Rotation operations aren't component-wise and leave the base value unchanged, as does
+    /// `Clamp`, whose two bounds are applied by the modifier with [`clamp_components`].
+    #[inline]
+    pub fn apply(self, base: f32, value: f32) -> f32 {
+        match self {
+            Self::None => value,
+            Self::Rotate | Self::InverseRotate => base,
+            Self::Add => base + value,
+            Self::Sub => base - value,
+            Self::Mul => base * value,
+            Self::Div => base / value,
+            #[cfg(feature = "extended_ops")]
+            Self::Min => base.min(value),
+            #[cfg(feature = "extended_ops")]
+            Self::Max => base.max(value),
+            #[cfg(feature = "extended_ops")]
+            Self::Mod => base.rem_euclid(value),
+            #[cfg(feature = "extended_ops")]
+            Self::Pow => base.powf(value),
+            #[cfg(feature = "extended_ops")]
+            Self::Abs => base.abs(),
+            #[cfg(feature = "extended_ops")]
+            Self::Clamp => base,
         }
     }
+
+    /// [`Operation::apply`] for each component of `base` and `value`.
+    pub fn apply_components<T: ValueType>(self, base: T, value: T) -> T
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        let mut result = [0.0; T::VALUE_COUNT];
+        for ((result, base), value) in result.iter_mut().zip(base.as_slice()).zip(value.as_slice())
+        {
+            *result = self.apply(*base, *value);
+        }
+        T::from_translate_array(result)
+    }
+
+    /// Combines a base value with a modifier value, e.g. when folding nested modifiers.
+    #[inline]
+    pub fn combine<T: ValueType>(self, base: T, value: T) -> T
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        match self {
+            Self::Add => base + value,
+            Self::Sub => base - value,
+            Self::Mul => base * value,
+            Self::Div => base / value,
+            Self::None => value,
+            _ => self.apply_components(base, value),
+        }
+    }
+}
+
+/// Clamps each component of `base` between the matching components of `lower` and `upper`.
+///
+/// Not `f32::clamp`, which panics on NaN bounds or when `lower` is above `upper`. In that case the
+/// upper bound wins.
+pub fn clamp_components<T: ValueType>(base: T, lower: T, upper: T) -> T
+where
+    [(); T::VALUE_COUNT]:,
+{
+    let mut result = [0.0; T::VALUE_COUNT];
+    for (((result, base), lower), upper) in result
+        .iter_mut()
+        .zip(base.as_slice())
+        .zip(lower.as_slice())
+        .zip(upper.as_slice())
+    {
+        *result = base.max(*lower).min(*upper);
+    }
+    T::from_translate_array(result)
+}
+
+/// Serialized as the Heck flag, e.g. `"opAdd"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Operation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Operation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use std::str::FromStr;
+
+        let flag = String::deserialize(deserializer)?;
+        Operation::from_str(&flag)
+            .map_err(|_| serde::de::Error::custom(format!("unknown operation \"{flag}\"")))
+    }
 }
@@ -1,9 +1,13 @@
-use super::{ModifierLike, operation::Operation, shared_has_base_provider};
+use super::operation::{Operation, clamp_components};
+use super::{ModifierLike, Step, hoist_static, shared_has_base_provider};
 use crate::prelude::{AbstractValueProvider, ValueProvider};
+use crate::providers::dependencies::BaseDependencies;
 use crate::value_types::ValueType;
 use crate::{base_provider_context::BaseProviderContext, quaternion_utils::QuaternionUtilsExt};
 use glam::Vec3A;
 use glam::{Quat, Vec3};
+#[cfg(feature = "json")]
+use serde_json::Value as JsonValue;
 use smallvec::SmallVec;
 
 /// Representation of the quaternion modifier input values.
@@ -20,15 +24,43 @@ pub enum QuaternionValues {
     Dynamic(SmallVec<[ValueProvider; 1]>),
 }
 
+impl QuaternionValues {
+    /// Writes the values as they appear in a point definition, statics as Euler degrees.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Vec<JsonValue> {
+        use crate::providers::{json_number, serialize_values};
+
+        match self {
+            QuaternionValues::Static(euler, _) => {
+                euler.to_array().into_iter().map(json_number).collect()
+            }
+            QuaternionValues::Dynamic(values) => serialize_values(values),
+        }
+    }
+}
+
 /// Quaternion-specific modifier. It evaluates to a `Quat` by converting
 /// Euler-component results into a quaternion. Nested quaternion modifiers are
-/// applied component-wise to the Euler vector before conversion.
+/// applied component-wise to the Euler vector before conversion, except for
+/// `opRotate` and `opInverseRotate`, which compose the rotations as quaternions.
+///
+/// Like [`BasicModifier`](super::basic_modifier::BasicModifier), static parts of the tree are
+/// folded when the modifier is built.
 #[derive(Debug, Clone)]
 pub struct QuaternionModifier {
     values: QuaternionValues,
     has_base_provider: bool,
+    /// Whether a nested modifier uses `opRotate` or `opInverseRotate`
+    composes_rotations: bool,
     modifiers: Vec<QuaternionModifier>,
     operation: Operation,
+    /// `opClamp` only: the upper bound, with the same nested modifiers. This modifier is the lower
+    /// bound.
+    upper: Option<Box<QuaternionModifier>>,
+    /// Euler angles and rotation of the result, if the values and all nested modifiers are static
+    folded: Option<(Vec3, Quat)>,
+    /// Nested modifiers with their static start folded, if it could be. Not used with rotation operations.
+    hoisted: Option<Box<[Step<Vec3>]>>,
 }
 
 impl QuaternionModifier {
@@ -39,14 +71,103 @@ impl QuaternionModifier {
     ) -> Self {
         let has_base_provider =
             shared_has_base_provider(matches!(point, QuaternionValues::Dynamic(_)), &modifiers);
+        let composes_rotations = modifiers.iter().any(|m| m.operation.is_rotation());
+
+        let folded = match &point {
+            QuaternionValues::Static(euler, rotation) if modifiers.is_empty() => {
+                Some((*euler, *rotation))
+            }
+            QuaternionValues::Static(euler, _) if modifiers.iter().all(Self::is_static) => {
+                let vector = |m: &QuaternionModifier| m.folded.map_or(Vec3::ZERO, |(e, _)| e);
+                Some(if composes_rotations {
+                    let rotation =
+                        |m: &QuaternionModifier| m.folded.map_or(Quat::IDENTITY, |(_, r)| r);
+                    let rotation = Self::compose_rotations(*euler, &modifiers, vector, rotation);
+                    (rotation.to_unity_euler_degrees(), rotation)
+                } else {
+                    let euler = Self::fold_euler(*euler, &modifiers, vector);
+                    (euler, Quat::from_unity_euler_degrees(euler))
+                })
+            }
+            _ => None,
+        };
+        let original = match &point {
+            QuaternionValues::Static(euler, _) => Some(*euler),
+            QuaternionValues::Dynamic(_) => None,
+        };
+        let hoisted = match folded.is_none() && !composes_rotations {
+            true => hoist_static(
+                original,
+                modifiers.iter().map(|m| (m.operation, m.static_operand())),
+            ),
+            false => None,
+        };
+
         Self {
             values: point,
             has_base_provider,
+            composes_rotations,
             modifiers,
             operation,
+            upper: None,
+            folded,
+            hoisted,
+        }
+    }
+
+    /// An `opClamp` modifier from its bounds, both built with `Operation::None` and the same
+    /// nested modifiers. The bounds apply to Euler angles.
+    #[cfg(feature = "extended_ops")]
+    pub fn clamp(lower: Self, upper: Self) -> Self {
+        Self {
+            has_base_provider: lower.has_base_provider || upper.has_base_provider,
+            operation: Operation::Clamp,
+            upper: Some(Box::new(upper)),
+            ..lower
         }
     }
 
+    pub fn values(&self) -> &QuaternionValues {
+        &self.values
+    }
+
+    pub fn modifiers(&self) -> &[QuaternionModifier] {
+        &self.modifiers
+    }
+
+    /// The upper bound of an `opClamp` modifier, whose own values are the lower bound.
+    pub fn upper_bound(&self) -> Option<&QuaternionModifier> {
+        self.upper.as_deref()
+    }
+
+    /// Whether this modifier, and the upper bound of a clamp, are folded.
+    fn is_static(&self) -> bool {
+        self.folded.is_some()
+            && self
+                .upper
+                .as_ref()
+                .is_none_or(|upper| upper.folded.is_some())
+    }
+
+    /// The folded angles if combining with them only takes the operation, i.e. unless it's a clamp.
+    fn static_operand(&self) -> Option<Vec3> {
+        self.folded
+            .filter(|_| self.upper.is_none())
+            .map(|(euler, _)| euler)
+    }
+
+    /// Writes the modifier as a Heck modifier array: values, nested modifiers, then the `op*` flag.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> JsonValue {
+        let mut elements = self.values.to_json();
+        if let Some(upper) = &self.upper {
+            elements.extend(upper.values.to_json());
+        }
+        elements.extend(self.modifiers.iter().map(QuaternionModifier::to_json));
+        elements.push(self.operation.as_str().into());
+        JsonValue::Array(elements)
+    }
+
     fn translate_euler(values: &[ValueProvider], context: &BaseProviderContext) -> Vec3 {
         let mut vec3 = Vec3::ZERO;
 
@@ -67,36 +188,149 @@ impl QuaternionModifier {
         vec3
     }
 
-    pub fn get_vector_point(&self, context: &BaseProviderContext) -> Vec3 {
-        let original_point = match &self.values {
+    fn original_point(&self, context: &BaseProviderContext) -> Vec3 {
+        match &self.values {
             QuaternionValues::Static(s, _) => *s,
             QuaternionValues::Dynamic(value_providers) => {
                 Self::translate_euler(value_providers, context)
             }
-        };
+        }
+    }
+
+    /// Applies a component-wise operation to Euler angles.
+    #[inline]
+    fn combine_euler(operation: Operation, acc_a: Vec3A, v_a: Vec3A) -> Vec3A {
+        match operation {
+            Operation::Add => acc_a + v_a,
+            Operation::Sub => acc_a - v_a,
+            Operation::Mul => acc_a * v_a,
+            Operation::Div => acc_a / v_a,
+            Operation::None => v_a,
+            Operation::Rotate | Operation::InverseRotate => {
+                unreachable!("rotations are composed by `compose_rotations`")
+            }
+            #[cfg(feature = "extended_ops")]
+            op => Vec3A::from(op.apply_components(Vec3::from(acc_a), Vec3::from(v_a))),
+        }
+    }
+
+    /// Combines `acc_a` with this modifier's angles, read with `vector`, as its parent does.
+    #[inline]
+    fn apply_euler(&self, acc_a: Vec3A, vector: &impl Fn(&QuaternionModifier) -> Vec3) -> Vec3A {
+        match &self.upper {
+            Some(upper) => Vec3A::from(clamp_components(
+                Vec3::from(acc_a),
+                vector(self),
+                vector(upper),
+            )),
+            None => Self::combine_euler(self.operation, acc_a, Vec3A::from(vector(self))),
+        }
+    }
+
+    pub fn get_vector_point(&self, context: &BaseProviderContext) -> Vec3 {
+        if let Some((euler, _)) = self.folded {
+            return euler;
+        }
+        if self.composes_rotations {
+            return self.composed_rotation(context).to_unity_euler_degrees();
+        }
+        if let Some(steps) = &self.hoisted {
+            return self.evaluate_steps(steps, context);
+        }
+
+        Self::fold_euler(self.original_point(context), &self.modifiers, |m| {
+            m.get_vector_point(context)
+        })
+    }
+
+    /// Applies the Euler operations of `modifiers` to `original`, reading each nested modifier's
+    /// angles with `vector`.
+    #[inline]
+    fn fold_euler(
+        original: Vec3,
+        modifiers: &[QuaternionModifier],
+        vector: impl Fn(&QuaternionModifier) -> Vec3,
+    ) -> Vec3 {
         // Use Vec3A for accumulation in hot inner loop then convert back
+        let mut acc_a = Vec3A::from(original);
+        for quat_point in modifiers {
+            acc_a = quat_point.apply_euler(acc_a, &vector);
+        }
+
+        Vec3::from(acc_a)
+    }
+
+    fn evaluate_steps(&self, steps: &[Step<Vec3>], context: &BaseProviderContext) -> Vec3 {
+        let (original_point, steps) = match steps {
+            // The values are replaced by the first step, skip reading them
+            [Step::Static(Operation::None, value), rest @ ..] => (*value, rest),
+            _ => (self.original_point(context), steps),
+        };
         let mut acc_a = Vec3A::from(original_point);
-        for quat_point in &self.modifiers {
-            let v_a = Vec3A::from(quat_point.get_vector_point(context));
-            acc_a = match quat_point.get_operation() {
-                Operation::Add => acc_a + v_a,
-                Operation::Sub => acc_a - v_a,
-                Operation::Mul => acc_a * v_a,
-                Operation::Div => acc_a / v_a,
-                Operation::None => v_a,
+        for step in steps {
+            acc_a = match step {
+                Step::Static(operation, value) => {
+                    Self::combine_euler(*operation, acc_a, Vec3A::from(*value))
+                }
+                Step::Dynamic(index) => self.modifiers[*index]
+                    .apply_euler(acc_a, &|m: &QuaternionModifier| m.get_vector_point(context)),
             };
         }
 
         Vec3::from(acc_a)
     }
+
+    fn composed_rotation(&self, context: &BaseProviderContext) -> Quat {
+        Self::compose_rotations(
+            self.original_point(context),
+            &self.modifiers,
+            |m| m.get_vector_point(context),
+            |m| m.get_modified_point(context),
+        )
+    }
+
+    /// Applies `modifiers` in order, switching to quaternions at the first `opRotate` or
+    /// `opInverseRotate`. Later Euler operations work on the angles of the rotation so far.
+    fn compose_rotations(
+        original: Vec3,
+        modifiers: &[QuaternionModifier],
+        vector: impl Fn(&QuaternionModifier) -> Vec3,
+        rotation_of: impl Fn(&QuaternionModifier) -> Quat,
+    ) -> Quat {
+        let mut euler = Vec3A::from(original);
+        let mut rotation = None;
+        for modifier in modifiers {
+            let operation = modifier.get_operation();
+            if operation.is_rotation() {
+                let current =
+                    rotation.unwrap_or_else(|| Quat::from_unity_euler_degrees(Vec3::from(euler)));
+                let operand = rotation_of(modifier);
+                // The modifier's rotation is applied in the local space of the rotation so far
+                rotation = Some(match operation {
+                    Operation::InverseRotate => current * operand.inverse(),
+                    _ => current * operand,
+                });
+            } else {
+                if let Some(current) = rotation.take() {
+                    euler = Vec3A::from(current.to_unity_euler_degrees());
+                }
+                euler = modifier.apply_euler(euler, &vector);
+            }
+        }
+
+        rotation.unwrap_or_else(|| Quat::from_unity_euler_degrees(Vec3::from(euler)))
+    }
 }
 
 impl ModifierLike<Quat> for QuaternionModifier {
     const VALUE_COUNT: usize = 3;
 
     fn get_modified_point(&self, context: &BaseProviderContext) -> Quat {
-        if self.modifiers.is_empty() && matches!(self.values, QuaternionValues::Static(_, _)) {
-            return self.get_raw_point();
+        if let Some((_, rotation)) = self.folded {
+            return rotation;
+        }
+        if self.composes_rotations {
+            return self.composed_rotation(context);
         }
         // modifiers applied to the point
         let vector_point = self.get_vector_point(context);
@@ -118,4 +352,16 @@ impl ModifierLike<Quat> for QuaternionModifier {
     fn has_base_provider(&self) -> bool {
         self.has_base_provider
     }
+
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies) {
+        if let QuaternionValues::Dynamic(values) = &self.values {
+            dependencies.add_providers(values);
+        }
+        if let Some(QuaternionValues::Dynamic(values)) = self.upper.as_ref().map(|u| &u.values) {
+            dependencies.add_providers(values);
+        }
+        for modifier in &self.modifiers {
+            modifier.collect_dependencies(dependencies);
+        }
+    }
 }
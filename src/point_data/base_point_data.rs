@@ -6,6 +6,7 @@ use crate::{
         PointDataLike, basic_point_data::BasicPointData, quaternion_point_data::QuaternionPointData,
     },
     prelude::{BaseProviderContext, Functions},
+    providers::dependencies::BaseDependencies,
 };
 
 #[derive(Debug, Clone)]
@@ -121,6 +122,15 @@ impl PointDataLike<BaseValue> for BasePointData {
         }
     }
 
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies) {
+        match self {
+            BasePointData::Float(point_data) => point_data.collect_dependencies(dependencies),
+            BasePointData::Vector3(point_data) => point_data.collect_dependencies(dependencies),
+            BasePointData::Vector4(point_data) => point_data.collect_dependencies(dependencies),
+            BasePointData::Quaternion(point_data) => point_data.collect_dependencies(dependencies),
+        }
+    }
+
     fn get_point(&self, context: &BaseProviderContext) -> BaseValue {
         match self {
             BasePointData::Float(point_data) => BaseValue::Float(point_data.get_point(context)),
@@ -140,4 +150,14 @@ impl PointDataLike<BaseValue> for BasePointData {
             BasePointData::Quaternion(point_data) => point_data.get_time(),
         }
     }
+
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            BasePointData::Float(point_data) => point_data.to_json(),
+            BasePointData::Vector3(point_data) => point_data.to_json(),
+            BasePointData::Vector4(point_data) => point_data.to_json(),
+            BasePointData::Quaternion(point_data) => point_data.to_json(),
+        }
+    }
 }
@@ -4,6 +4,8 @@ use crate::{
     modifiers::{
         ModifierLike, ModifierValues, basic_modifier::BasicModifier, operation::Operation,
     },
+    point_definition::spline::SplineKind,
+    providers::dependencies::BaseDependencies,
     value_types::ValueType,
 };
 
@@ -12,7 +14,9 @@ use super::PointDataLike;
 #[derive(Debug, Clone)]
 pub struct BasicPointData<T: ValueType> {
     base_modifier: BasicModifier<T>,
-    pub smooth: bool,
+    /// How the segment leading to this point is interpolated.
+    pub spline: SplineKind,
+    controls: Vec<BasicModifier<T>>,
     easing: Functions,
     time: f32,
 }
@@ -21,20 +25,54 @@ impl<T: ValueType> BasicPointData<T>
 where
     [(); T::VALUE_COUNT]:,
 {
+    /// A point interpolated linearly, or with a Catmull-Rom spline if `smooth` is set.
     pub fn new(
         point: ModifierValues<T>,
         time: f32,
         smooth: bool,
         modifiers: Vec<BasicModifier<T>>,
         easing: Functions,
+    ) -> Self {
+        let spline = match smooth {
+            true => SplineKind::CatmullRom,
+            false => SplineKind::Linear,
+        };
+        Self::with_spline(point, time, spline, modifiers, easing)
+    }
+
+    /// A point whose segment is interpolated with `spline`. Splines with control points also
+    /// need [`BasicPointData::with_controls`].
+    pub fn with_spline(
+        point: ModifierValues<T>,
+        time: f32,
+        spline: SplineKind,
+        modifiers: Vec<BasicModifier<T>>,
+        easing: Functions,
     ) -> Self {
         Self {
             base_modifier: BasicModifier::new(point, modifiers, Operation::None),
-            smooth,
+            spline,
+            controls: vec![],
             easing,
             time,
         }
     }
+
+    /// Sets the control points of a Bezier or Hermite spline, see [`SplineKind::control_count`].
+    /// Their operations are ignored.
+    pub fn with_controls(mut self, controls: Vec<BasicModifier<T>>) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    /// The point's values and nested modifiers.
+    pub(crate) fn modifier(&self) -> &BasicModifier<T> {
+        &self.base_modifier
+    }
+
+    pub fn controls(&self) -> &[BasicModifier<T>] {
+        &self.controls
+    }
 }
 
 // impl<T: ValueType> ModifierLike for BasicPointData<T> {
@@ -74,9 +112,36 @@ where
     }
     fn has_base_provider(&self) -> bool {
         self.base_modifier.has_base_provider()
+            || self.controls.iter().any(|c| c.has_base_provider())
+    }
+
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies) {
+        self.base_modifier.collect_dependencies(dependencies);
+        for control in &self.controls {
+            control.collect_dependencies(dependencies);
+        }
     }
 
     fn get_point(&self, context: &BaseProviderContext) -> T {
         self.base_modifier.get_modified_point(context)
     }
+
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> serde_json::Value {
+        use crate::point_definition::spline::control_json;
+
+        let values = self.base_modifier.values();
+        let mut elements = super::point_json(
+            values.to_json(),
+            values.as_static_values().map(|_| self.time),
+            self.base_modifier
+                .modifiers()
+                .iter()
+                .map(BasicModifier::to_json)
+                .chain(self.controls.iter().map(control_json)),
+            self.easing,
+        );
+        elements.extend(self.spline.flag().map(Into::into));
+        serde_json::Value::Array(elements)
+    }
 }
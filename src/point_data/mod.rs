@@ -2,13 +2,42 @@ pub mod base_point_data;
 pub mod basic_point_data;
 pub mod quaternion_point_data;
 
+#[cfg(feature = "json")]
+use serde_json::Value as JsonValue;
+
 use crate::base_provider_context::BaseProviderContext;
 use crate::easings::functions::Functions;
+use crate::providers::dependencies::BaseDependencies;
 
 // Generic trait for point data
 pub trait PointDataLike<T>: Clone {
     fn get_easing(&self) -> Functions;
     fn has_base_provider(&self) -> bool;
+    /// Adds the bases and stateful providers read by the point's values and modifiers.
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies);
     fn get_point(&self, context: &BaseProviderContext) -> T;
     fn get_time(&self) -> f32;
+
+    /// Writes the point as a Heck point array: values and time, modifiers, then flags.
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> JsonValue;
+}
+
+/// Appends the time (only written for static points, dynamic points carry it in their
+/// last provider), the nested modifiers and the easing flag to a point's values.
+#[cfg(feature = "json")]
+pub(crate) fn point_json(
+    mut elements: Vec<JsonValue>,
+    time: Option<f32>,
+    modifiers: impl Iterator<Item = JsonValue>,
+    easing: Functions,
+) -> Vec<JsonValue> {
+    if let Some(time) = time {
+        elements.push(crate::providers::json_number(time));
+    }
+    elements.extend(modifiers);
+    if easing != Functions::EaseLinear {
+        elements.push(easing.as_str().into());
+    }
+    elements
 }
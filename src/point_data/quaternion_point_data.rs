@@ -6,6 +6,7 @@ use crate::{
         operation::Operation,
         quaternion_modifier::{QuaternionModifier, QuaternionValues},
     },
+    providers::dependencies::BaseDependencies,
 };
 use glam::Quat;
 
@@ -14,6 +15,7 @@ use super::PointDataLike;
 #[derive(Debug, Clone)]
 pub struct QuaternionPointData {
     base_modifier: QuaternionModifier,
+    pub smooth: bool,
     easing: Functions,
     time: f32,
 }
@@ -27,10 +29,22 @@ impl QuaternionPointData {
     ) -> Self {
         Self {
             base_modifier: QuaternionModifier::new(point, modifiers, Operation::None),
+            smooth: false,
             easing,
             time,
         }
     }
+
+    /// Sets whether the segment leading to this point is interpolated with SQUAD instead of slerp.
+    pub fn with_smooth(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    /// The point's values and nested modifiers.
+    pub(crate) fn modifier(&self) -> &QuaternionModifier {
+        &self.base_modifier
+    }
 }
 
 // impl ModifierLike for QuaternionPointData {
@@ -71,7 +85,30 @@ impl PointDataLike<Quat> for QuaternionPointData {
         self.base_modifier.has_base_provider()
     }
 
+    fn collect_dependencies(&self, dependencies: &mut BaseDependencies) {
+        self.base_modifier.collect_dependencies(dependencies);
+    }
+
     fn get_point(&self, context: &BaseProviderContext) -> Quat {
         self.base_modifier.get_modified_point(context)
     }
+
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> serde_json::Value {
+        let values = self.base_modifier.values();
+        let time = matches!(values, QuaternionValues::Static(..)).then_some(self.time);
+        let mut elements = super::point_json(
+            values.to_json(),
+            time,
+            self.base_modifier
+                .modifiers()
+                .iter()
+                .map(QuaternionModifier::to_json),
+            self.easing,
+        );
+        if self.smooth {
+            elements.push("splineCatmullRom".into());
+        }
+        serde_json::Value::Array(elements)
+    }
 }
@@ -11,12 +11,17 @@ use crate::point_definition::basic_point_definition::BasicPointDefinition;
 use crate::point_definition::quaternion_point_definition::QuaternionPointDefinition;
 use crate::point_definition::vector3_point_definition::Vector3PointDefinition;
 use crate::providers::ValueProvider;
+use crate::providers::dependencies::BaseDependencies;
 
 use crate::base_provider_context::BaseProviderContext;
 
 use crate::base_value::{BaseValue, WrapBaseValueType};
 
-use super::PointDefinitionLike;
+#[cfg(feature = "json")]
+use super::parse_error::ParseError;
+#[cfg(feature = "json")]
+use super::raw::{self, RawElement};
+use super::{PointDefinitionLike, parse_error::ParseErrorKind};
 
 /// Point definitions are used to describe what happens over the course of an animation,
 /// they are used slightly differently for different properties.
@@ -88,12 +93,21 @@ impl PointDefinitionLike<BaseValue> for BasePointDefinition {
         }
     }
 
+    fn base_dependencies(&self) -> BaseDependencies {
+        match self {
+            BasePointDefinition::Float(def) => def.base_dependencies(),
+            BasePointDefinition::Vector3(def) => def.base_dependencies(),
+            BasePointDefinition::Vector4(def) => def.base_dependencies(),
+            BasePointDefinition::Quaternion(def) => def.base_dependencies(),
+        }
+    }
+
     fn create_modifier(
         _values: SmallVec<[ValueProvider; 1]>,
         _modifiers: Vec<BaseModifier>,
         _operation: Operation,
         _context: &BaseProviderContext,
-    ) -> BaseModifier {
+    ) -> Result<BaseModifier, ParseErrorKind> {
         unimplemented!(
             "Cannot create Modifier directly from BasePointDefinition; use specific point definition types instead."
         )
@@ -103,9 +117,10 @@ impl PointDefinitionLike<BaseValue> for BasePointDefinition {
         _values: SmallVec<[ValueProvider; 1]>,
         _flags: Vec<String>,
         _modifiers: Vec<BaseModifier>,
+        _controls: Vec<BaseModifier>,
         _easing: Functions,
         _context: &BaseProviderContext,
-    ) -> BasePointData {
+    ) -> Result<BasePointData, ParseErrorKind> {
         unimplemented!(
             "Cannot create PointData directly from BasePointDefinition; use specific point definition types instead."
         )
@@ -121,6 +136,16 @@ impl PointDefinitionLike<BaseValue> for BasePointDefinition {
         )
     }
 
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            BasePointDefinition::Float(def) => def.to_json(),
+            BasePointDefinition::Vector3(def) => def.to_json(),
+            BasePointDefinition::Vector4(def) => def.to_json(),
+            BasePointDefinition::Quaternion(def) => def.to_json(),
+        }
+    }
+
     fn get_type(&self) -> WrapBaseValueType {
         match self {
             BasePointDefinition::Float(_) => WrapBaseValueType::Float,
@@ -186,6 +211,71 @@ impl PointDefinitionLike<BaseValue> for BasePointDefinition {
     }
 }
 
+impl BasePointDefinition {
+    /// Parses `value` as the typed point definition matching `ty`.
+    ///
+    /// Panics if `ty` is [`WrapBaseValueType::Unknown`].
+    #[cfg(feature = "json")]
+    pub fn try_parse_typed(
+        value: &serde_json::Value,
+        ty: WrapBaseValueType,
+        context: &mut BaseProviderContext,
+    ) -> Result<Self, ParseError> {
+        Self::parse_raw_typed(&RawElement::from_value(value), ty, context)
+    }
+
+    /// Parses JSON text as the typed point definition matching `ty`; see
+    /// [`PointDefinitionLike::try_parse_str`].
+    ///
+    /// Panics if `ty` is [`WrapBaseValueType::Unknown`].
+    #[cfg(feature = "json")]
+    pub fn try_parse_typed_str(
+        json: &str,
+        ty: WrapBaseValueType,
+        context: &mut BaseProviderContext,
+    ) -> Result<Self, ParseError> {
+        Self::try_parse_typed_slice(json.as_bytes(), ty, context)
+    }
+
+    /// Byte slice version of [`BasePointDefinition::try_parse_typed_str`].
+    ///
+    /// Panics if `ty` is [`WrapBaseValueType::Unknown`].
+    #[cfg(feature = "json")]
+    pub fn try_parse_typed_slice(
+        json: &[u8],
+        ty: WrapBaseValueType,
+        context: &mut BaseProviderContext,
+    ) -> Result<Self, ParseError> {
+        Self::parse_raw_typed(&raw::from_slice(json)?, ty, context)
+    }
+
+    #[cfg(feature = "json")]
+    fn parse_raw_typed(
+        raw: &RawElement,
+        ty: WrapBaseValueType,
+        context: &mut BaseProviderContext,
+    ) -> Result<Self, ParseError> {
+        let point_definition = match ty {
+            WrapBaseValueType::Vec3 => {
+                raw::parse_definition::<_, Vector3PointDefinition>(raw, context)?.into()
+            }
+            WrapBaseValueType::Quat => {
+                raw::parse_definition::<_, QuaternionPointDefinition>(raw, context)?.into()
+            }
+            WrapBaseValueType::Vec4 => {
+                raw::parse_definition::<_, BasicPointDefinition<Vec4>>(raw, context)?.into()
+            }
+            WrapBaseValueType::Float => {
+                raw::parse_definition::<_, BasicPointDefinition<f32>>(raw, context)?.into()
+            }
+            WrapBaseValueType::Unknown => {
+                panic!("Cannot create BasePointDefinition with Unknown type");
+            }
+        };
+        Ok(point_definition)
+    }
+}
+
 impl Default for BasePointDefinition {
     fn default() -> Self {
         BasePointDefinition::Float(Default::default())
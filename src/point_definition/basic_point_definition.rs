@@ -1,5 +1,3 @@
-use std::rc::Rc;
-
 use smallvec::SmallVec;
 
 use crate::{
@@ -9,14 +7,19 @@ use crate::{
     modifiers::{ModifierValues, basic_modifier::BasicModifier, operation::Operation},
     point_data::{PointDataLike, basic_point_data::BasicPointData},
     prelude::{AbstractValueProvider, ValueProvider},
+    shared::Shared,
     value_types::ValueType,
 };
 
-use super::PointDefinitionLike;
+use super::{
+    PointDefinitionLike,
+    parse_error::ParseErrorKind,
+    spline::{self, SplineKind},
+};
 
 #[derive(Default, Debug, Clone)]
 pub struct BasicPointDefinition<T: ValueType> {
-    points: Rc<[BasicPointData<T>]>,
+    points: Shared<[BasicPointData<T>]>,
 }
 
 impl<T: ValueType> PointDefinitionLike<T> for BasicPointDefinition<T>
@@ -38,7 +41,7 @@ where
 
     fn new(points: Vec<Self::PointData>) -> Self {
         Self {
-            points: Rc::from(points),
+            points: Shared::from(points),
         }
     }
 
@@ -47,7 +50,19 @@ where
         modifiers: Vec<BasicModifier<T>>,
         operation: Operation,
         context: &BaseProviderContext,
-    ) -> BasicModifier<T> {
+    ) -> Result<BasicModifier<T>, ParseErrorKind> {
+        if operation.is_rotation() {
+            return Err(ParseErrorKind::RotationOnlyOperation(
+                operation.as_str().to_string(),
+            ));
+        }
+        #[cfg(feature = "extended_ops")]
+        if operation == Operation::Clamp {
+            let (lower, upper) = super::split_clamp_bounds(values, T::VALUE_COUNT, context)?;
+            let lower = Self::create_modifier(lower, modifiers.clone(), Operation::None, context)?;
+            let upper = Self::create_modifier(upper, modifiers, Operation::None, context)?;
+            return Ok(BasicModifier::clamp(lower, upper));
+        }
         let val: ModifierValues<T> = match values.as_slice() {
             // Single static value [T]
             [ValueProvider::Static(static_val)] if static_val.values.len() == T::VALUE_COUNT => {
@@ -57,25 +72,26 @@ where
             // Any other case is treated as dynamic and translated/padded at evaluation time.
             _ => {
                 let count: usize = values.iter().map(|v| v.values(context).len()).sum();
-                assert_eq!(
-                    count,
-                    T::VALUE_COUNT,
-                    "modifier point must have {} numbers",
-                    T::VALUE_COUNT
-                );
+                if count != T::VALUE_COUNT {
+                    return Err(ParseErrorKind::WrongComponentCount {
+                        expected: T::VALUE_COUNT,
+                        found: count,
+                    });
+                }
                 ModifierValues::Dynamic(values)
             }
         };
-        Self::Modifier::new(val, modifiers, operation)
+        Ok(Self::Modifier::new(val, modifiers, operation))
     }
 
     fn create_point_data(
         values: SmallVec<[ValueProvider; 1]>,
         flags: Vec<String>,
         modifiers: Vec<Self::Modifier>,
+        controls: Vec<Self::Modifier>,
         easing: Functions,
         context: &BaseProviderContext,
-    ) -> Self::PointData {
+    ) -> Result<Self::PointData, ParseErrorKind> {
         // If one value is present and it contains two floats, the first is the point value and the second is time.
 
         let (value, time) = match &values[..] {
@@ -92,18 +108,22 @@ where
             _ => {
                 // get time from last provider last value
                 // https://github.com/Aeroluna/Heck/blob/1dc9f470a7f8d3e64d0e3bc34e2f2279190eb8b8/Heck/Animation/PointDefinition/Vector3PointDefinition.cs#L80-L81
-                let time = values.last()
+                let time = values
+                    .last()
                     .and_then(|vp| vp.values(context).last().copied())
-                    .unwrap_or_else(|| panic!("Expected at least one value provider with at least {} values for point data, with the last one being time",
-                        T::VALUE_COUNT + 1));
+                    .ok_or(ParseErrorKind::MissingTime)?;
 
                 (ModifierValues::Dynamic(values), time)
             }
         };
 
-        let smooth = flags.iter().any(|f| f == "splineCatmullRom");
+        let spline = SplineKind::from_flags(&flags);
+        spline.check_controls(controls.len())?;
 
-        BasicPointData::new(value, time, smooth, modifiers, easing)
+        Ok(
+            BasicPointData::with_spline(value, time, spline, modifiers, easing)
+                .with_controls(controls),
+        )
     }
 
     fn get_points(&self) -> &[Self::PointData] {
@@ -116,16 +136,13 @@ where
 
     fn interpolate_points(
         &self,
-        l: &Self::PointData,
-        r: &Self::PointData,
-        _l_index: usize,
-        _r_index: usize,
+        _l: &Self::PointData,
+        _r: &Self::PointData,
+        l_index: usize,
+        r_index: usize,
         time: f32,
         context: &BaseProviderContext,
     ) -> T {
-        let point_l = PointDataLike::get_point(l, context);
-        let point_r = PointDataLike::get_point(r, context);
-
-        T::value_lerp_clamped(point_l, point_r, time)
+        spline::interpolate_points(&self.points, l_index, r_index, time, context)
     }
 }
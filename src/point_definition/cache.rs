@@ -0,0 +1,632 @@
+//! Versioned binary encoding of parsed point definitions.
+//!
+//! Parsing a large modchart means reading megabytes of JSON on every level start. [`encode`]
+//! writes an already parsed [`BasePointDefinition`] in a compact form that [`decode`] rebuilds
+//! without touching JSON, so hosts can store it next to the map as a preprocessed cache keyed by
+//! [`source_hash`] of the original JSON.
+//!
+//! Layout, little-endian, counts and indices as LEB128 varints:
+//!
+//! ```text
+//! magic "TRPD" | version u16 | value type u8 | source hash u64
+//! string count | (length | utf8 bytes)*
+//! point count  | (time f32 | easing string | spline u8 | control count | modifier* | modifier)*
+//! modifier     = operation string | values | nested modifier count | modifier* | values?
+//! values       = 0 | component f32*              static, as many as the value type has
+//!              | 1 | provider count | provider*  dynamic
+//! provider     = 0 | is_rotation u8 | count | f32*  static values
+//!              | 1 | string                       `base*` provider key
+//! ```
+//!
+//! Strings (provider keys, easings and operations) are interned in the string table and
+//! referenced by index. `opClamp` modifiers end with the values of their upper bound, the first
+//! values being the lower bound. Quaternion statics are stored as Euler degrees, as in the JSON. The spline
+//! is the [`SplineKind`] discriminant, followed by the point's spline control points.
+//!
+//! Decoding resolves provider keys through [`BaseProviderContext::try_get_value_provider`], which
+//! registers smoothing providers with the context just like parsing does. Custom bases used by
+//! the definition must be registered with the context before decoding.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use glam::{Quat, Vec3, Vec4};
+use smallvec::SmallVec;
+
+use crate::base_provider_context::{BaseError, BaseProviderContext};
+use crate::base_value::WrapBaseValueType;
+use crate::codec::{ByteReader, CodecError, write_varint};
+use crate::easings::functions::Functions;
+use crate::modifiers::basic_modifier::BasicModifier;
+use crate::modifiers::operation::Operation;
+use crate::modifiers::quaternion_modifier::{QuaternionModifier, QuaternionValues};
+use crate::modifiers::{ModifierLike, ModifierValues};
+use crate::point_data::PointDataLike;
+use crate::point_data::basic_point_data::BasicPointData;
+use crate::point_data::quaternion_point_data::QuaternionPointData;
+use crate::providers::ValueProvider;
+use crate::providers::r#static::StaticValues;
+use crate::quaternion_utils::QuaternionUtilsExt;
+use crate::value_types::ValueType;
+
+use super::PointDefinitionLike;
+use super::base_point_definition::BasePointDefinition;
+use super::basic_point_definition::BasicPointDefinition;
+use super::quaternion_point_definition::QuaternionPointDefinition;
+use super::spline::SplineKind;
+use super::vector3_point_definition::Vector3PointDefinition;
+
+pub const CACHE_MAGIC: [u8; 4] = *b"TRPD";
+/// Bumped whenever the layout changes; older caches fail with [`CacheError::UnsupportedVersion`].
+///
+/// cbindgen:ignore
+pub const CACHE_VERSION: u16 = 3;
+
+const VALUES_STATIC: u8 = 0;
+const VALUES_DYNAMIC: u8 = 1;
+const PROVIDER_STATIC: u8 = 0;
+const PROVIDER_KEY: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheError {
+    /// The data ends in the middle of a value.
+    UnexpectedEof,
+    /// The data does not start with [`CACHE_MAGIC`].
+    BadMagic,
+    /// The cache was written by a different format version.
+    UnsupportedVersion(u16),
+    /// The value type byte is not a known [`WrapBaseValueType`].
+    UnknownType(u8),
+    /// A tag byte has a value the format does not define.
+    InvalidTag(u8),
+    /// A varint is longer than a `usize`.
+    InvalidVarint,
+    /// A string reference points outside of the string table.
+    InvalidString(usize),
+    /// A string in the string table is not UTF-8.
+    InvalidUtf8,
+    UnknownEasing(String),
+    UnknownOperation(String),
+    /// A provider key names a base that isn't registered with the decoding context.
+    UnknownBase(String),
+    /// A provider couldn't be created from its key for another reason.
+    Base(BaseError),
+    /// A rotation point uses a spline other than linear or Catmull-Rom.
+    UnsupportedSpline(SplineKind),
+    /// A point has a different number of control points than its spline takes.
+    UnexpectedControls(usize),
+    /// There is data left after the point definition.
+    TrailingBytes,
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::UnexpectedEof => write!(f, "unexpected end of cache data"),
+            CacheError::BadMagic => write!(f, "not a point definition cache"),
+            CacheError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported cache version {version}, expected {CACHE_VERSION}"
+            ),
+            CacheError::UnknownType(ty) => write!(f, "unknown value type {ty}"),
+            CacheError::InvalidTag(tag) => write!(f, "invalid tag {tag}"),
+            CacheError::InvalidVarint => write!(f, "invalid varint"),
+            CacheError::InvalidString(index) => write!(f, "invalid string reference {index}"),
+            CacheError::InvalidUtf8 => write!(f, "string table is not valid UTF-8"),
+            CacheError::UnknownEasing(easing) => write!(f, "unknown easing \"{easing}\""),
+            CacheError::UnknownOperation(op) => write!(f, "unknown operation \"{op}\""),
+            CacheError::UnknownBase(base) => write!(f, "unknown base provider \"{base}\""),
+            CacheError::Base(error) => write!(f, "{error}"),
+            CacheError::UnsupportedSpline(spline) => {
+                write!(f, "spline {spline:?} is not supported for rotations")
+            }
+            CacheError::UnexpectedControls(count) => {
+                write!(
+                    f,
+                    "unexpected {count} control points for the point's spline"
+                )
+            }
+            CacheError::TrailingBytes => write!(f, "trailing bytes after point definition"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+impl From<CodecError> for CacheError {
+    fn from(error: CodecError) -> Self {
+        match error {
+            CodecError::UnexpectedEof => CacheError::UnexpectedEof,
+            CodecError::InvalidVarint => CacheError::InvalidVarint,
+        }
+    }
+}
+
+/// Header of an encoded point definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheHeader {
+    pub version: u16,
+    pub ty: WrapBaseValueType,
+    /// The hash passed to [`encode`], normally [`source_hash`] of the source JSON.
+    pub source_hash: u64,
+}
+
+/// Stable 64-bit FNV-1a hash of the source JSON, for keying caches.
+///
+/// Unlike [`std::hash::DefaultHasher`] the result does not change between Rust versions.
+pub fn source_hash(json: &[u8]) -> u64 {
+    json.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Encodes `definition`, storing `source_hash` in the header.
+pub fn encode(definition: &BasePointDefinition, source_hash: u64) -> Vec<u8> {
+    let mut body = Writer::default();
+    match definition {
+        BasePointDefinition::Float(def) => body.basic_points(def.get_points()),
+        BasePointDefinition::Vector3(def) => body.basic_points(def.get_points()),
+        BasePointDefinition::Vector4(def) => body.basic_points(def.get_points()),
+        BasePointDefinition::Quaternion(def) => body.quaternion_points(def.get_points()),
+    }
+
+    let mut bytes = Vec::with_capacity(body.bytes.len() + 64);
+    bytes.extend_from_slice(&CACHE_MAGIC);
+    bytes.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+    bytes.push(definition.get_type() as u8);
+    bytes.extend_from_slice(&source_hash.to_le_bytes());
+    write_varint(&mut bytes, body.strings.len());
+    for string in &body.strings {
+        write_varint(&mut bytes, string.len());
+        bytes.extend_from_slice(string.as_bytes());
+    }
+    bytes.extend_from_slice(&body.bytes);
+    bytes
+}
+
+/// Reads only the header, e.g. to compare the stored hash with the current source.
+pub fn read_header(bytes: &[u8]) -> Result<CacheHeader, CacheError> {
+    Reader::new(bytes).header()
+}
+
+/// Rebuilds a point definition written by [`encode`], resolving its providers in `context`.
+pub fn decode(
+    bytes: &[u8],
+    context: &mut BaseProviderContext,
+) -> Result<BasePointDefinition, CacheError> {
+    let mut reader = Reader::new(bytes);
+    let header = reader.header()?;
+    reader.string_table()?;
+
+    let definition = match header.ty {
+        WrapBaseValueType::Float => {
+            BasicPointDefinition::<f32>::new(reader.basic_points(context)?).into()
+        }
+        WrapBaseValueType::Vec3 => {
+            Vector3PointDefinition::new(reader.basic_points(context)?).into()
+        }
+        WrapBaseValueType::Vec4 => {
+            BasicPointDefinition::<Vec4>::new(reader.basic_points(context)?).into()
+        }
+        WrapBaseValueType::Quat => {
+            QuaternionPointDefinition::new(reader.quaternion_points(context)?).into()
+        }
+        WrapBaseValueType::Unknown => unreachable!("rejected while reading the header"),
+    };
+
+    if !reader.input.is_at_end() {
+        return Err(CacheError::TrailingBytes);
+    }
+    Ok(definition)
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+    strings: Vec<String>,
+    string_ids: HashMap<String, usize>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn varint(&mut self, value: usize) {
+        write_varint(&mut self.bytes, value);
+    }
+
+    fn string(&mut self, string: &str) {
+        let id = match self.string_ids.get(string) {
+            Some(id) => *id,
+            None => {
+                let id = self.strings.len();
+                self.strings.push(string.to_string());
+                self.string_ids.insert(string.to_string(), id);
+                id
+            }
+        };
+        self.varint(id);
+    }
+
+    fn point_header(&mut self, time: f32, easing: Functions, spline: SplineKind) {
+        self.f32(time);
+        self.string(easing.as_str());
+        self.u8(spline as u8);
+    }
+
+    fn basic_points<T: ValueType>(&mut self, points: &[BasicPointData<T>])
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        self.varint(points.len());
+        for point in points {
+            self.point_header(point.get_time(), point.get_easing(), point.spline);
+            self.varint(point.controls().len());
+            for control in point.controls() {
+                self.basic_modifier(control);
+            }
+            self.basic_modifier(point.modifier());
+        }
+    }
+
+    fn basic_modifier<T: ValueType>(&mut self, modifier: &BasicModifier<T>)
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        self.string(modifier.get_operation().as_str());
+        self.basic_values(modifier.values());
+        self.varint(modifier.modifiers().len());
+        for nested in modifier.modifiers() {
+            self.basic_modifier(nested);
+        }
+        if let Some(upper) = modifier.upper_bound() {
+            self.basic_values(upper.values());
+        }
+    }
+
+    fn basic_values<T: ValueType>(&mut self, values: &ModifierValues<T>)
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        match values {
+            ModifierValues::Static(value) => {
+                self.u8(VALUES_STATIC);
+                value.as_slice().iter().for_each(|v| self.f32(*v));
+            }
+            ModifierValues::Dynamic(providers) => self.providers(providers),
+        }
+    }
+
+    fn quaternion_points(&mut self, points: &[QuaternionPointData]) {
+        self.varint(points.len());
+        for point in points {
+            let spline = match point.smooth {
+                true => SplineKind::CatmullRom,
+                false => SplineKind::Linear,
+            };
+            self.point_header(point.get_time(), point.get_easing(), spline);
+            self.varint(0);
+            self.quaternion_modifier(point.modifier());
+        }
+    }
+
+    fn quaternion_modifier(&mut self, modifier: &QuaternionModifier) {
+        self.string(modifier.get_operation().as_str());
+        self.quaternion_values(modifier.values());
+        self.varint(modifier.modifiers().len());
+        for nested in modifier.modifiers() {
+            self.quaternion_modifier(nested);
+        }
+        if let Some(upper) = modifier.upper_bound() {
+            self.quaternion_values(upper.values());
+        }
+    }
+
+    fn quaternion_values(&mut self, values: &QuaternionValues) {
+        match values {
+            QuaternionValues::Static(euler, _) => {
+                self.u8(VALUES_STATIC);
+                euler.to_array().iter().for_each(|v| self.f32(*v));
+            }
+            QuaternionValues::Dynamic(providers) => self.providers(providers),
+        }
+    }
+
+    fn providers(&mut self, providers: &[ValueProvider]) {
+        self.u8(VALUES_DYNAMIC);
+        self.varint(providers.len());
+        for provider in providers {
+            match provider {
+                ValueProvider::Static(s) => {
+                    self.u8(PROVIDER_STATIC);
+                    self.u8(s.is_rotation as u8);
+                    self.varint(s.values.len());
+                    s.values.iter().for_each(|v| self.f32(*v));
+                }
+                _ => {
+                    self.u8(PROVIDER_KEY);
+                    self.string(&provider.key().unwrap_or_default());
+                }
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    input: ByteReader<'a>,
+    strings: Vec<&'a str>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            input: ByteReader::new(bytes),
+            strings: vec![],
+        }
+    }
+
+    fn bool(&mut self) -> Result<bool, CacheError> {
+        match self.input.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(CacheError::InvalidTag(tag)),
+        }
+    }
+
+    fn string(&mut self) -> Result<&'a str, CacheError> {
+        let index = self.input.varint()?;
+        self.strings
+            .get(index)
+            .copied()
+            .ok_or(CacheError::InvalidString(index))
+    }
+
+    fn header(&mut self) -> Result<CacheHeader, CacheError> {
+        if self.input.take(CACHE_MAGIC.len()).ok() != Some(CACHE_MAGIC.as_slice()) {
+            return Err(CacheError::BadMagic);
+        }
+        let version = u16::from_le_bytes(self.input.array()?);
+        if version != CACHE_VERSION {
+            return Err(CacheError::UnsupportedVersion(version));
+        }
+        let ty = match self.input.u8()? {
+            ty if ty == WrapBaseValueType::Vec3 as u8 => WrapBaseValueType::Vec3,
+            ty if ty == WrapBaseValueType::Quat as u8 => WrapBaseValueType::Quat,
+            ty if ty == WrapBaseValueType::Vec4 as u8 => WrapBaseValueType::Vec4,
+            ty if ty == WrapBaseValueType::Float as u8 => WrapBaseValueType::Float,
+            ty => return Err(CacheError::UnknownType(ty)),
+        };
+        let source_hash = u64::from_le_bytes(self.input.array()?);
+        Ok(CacheHeader {
+            version,
+            ty,
+            source_hash,
+        })
+    }
+
+    fn string_table(&mut self) -> Result<(), CacheError> {
+        let count = self.input.count()?;
+        self.strings = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = self.input.varint()?;
+            let string =
+                std::str::from_utf8(self.input.take(len)?).map_err(|_| CacheError::InvalidUtf8)?;
+            self.strings.push(string);
+        }
+        Ok(())
+    }
+
+    fn point_header(&mut self) -> Result<(f32, Functions, SplineKind), CacheError> {
+        let time = self.input.f32()?;
+        let easing = self.string()?;
+        let easing = Functions::from_str(easing)
+            .map_err(|_| CacheError::UnknownEasing(easing.to_string()))?;
+        let spline = match self.input.u8()? {
+            0 => SplineKind::Linear,
+            1 => SplineKind::CatmullRom,
+            2 => SplineKind::CentripetalCatmullRom,
+            3 => SplineKind::Bezier,
+            4 => SplineKind::Hermite,
+            5 => SplineKind::BSpline,
+            tag => return Err(CacheError::InvalidTag(tag)),
+        };
+        Ok((time, easing, spline))
+    }
+
+    /// Reads a point's control point count, which must be the number its spline takes.
+    fn control_count(&mut self, spline: SplineKind) -> Result<usize, CacheError> {
+        let count = self.input.count()?;
+        match count == spline.control_count() {
+            true => Ok(count),
+            false => Err(CacheError::UnexpectedControls(count)),
+        }
+    }
+
+    fn operation(&mut self) -> Result<Operation, CacheError> {
+        let op = self.string()?;
+        Operation::from_str(op).map_err(|_| CacheError::UnknownOperation(op.to_string()))
+    }
+
+    fn basic_points<T: ValueType>(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<Vec<BasicPointData<T>>, CacheError>
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        let count = self.input.count()?;
+        let mut points = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (time, easing, spline) = self.point_header()?;
+            let controls = (0..self.control_count(spline)?)
+                .map(|_| self.basic_modifier(context))
+                .collect::<Result<_, _>>()?;
+            let (_, values, modifiers) = self.basic_parts::<T>(context)?;
+            points.push(
+                BasicPointData::with_spline(values, time, spline, modifiers, easing)
+                    .with_controls(controls),
+            );
+        }
+        Ok(points)
+    }
+
+    fn basic_modifier<T: ValueType>(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<BasicModifier<T>, CacheError>
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        let (operation, values, modifiers) = self.basic_parts(context)?;
+        #[cfg(feature = "extended_ops")]
+        if operation == Operation::Clamp {
+            let upper = self.basic_values(context)?;
+            return Ok(BasicModifier::clamp(
+                BasicModifier::new(values, modifiers.clone(), Operation::None),
+                BasicModifier::new(upper, modifiers, Operation::None),
+            ));
+        }
+        Ok(BasicModifier::new(values, modifiers, operation))
+    }
+
+    // The operation, values and nested modifiers of a modifier. Points store their values as a
+    // modifier whose operation is ignored.
+    #[allow(clippy::type_complexity)]
+    fn basic_parts<T: ValueType>(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<(Operation, ModifierValues<T>, Vec<BasicModifier<T>>), CacheError>
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        let operation = self.operation()?;
+        let values = self.basic_values(context)?;
+        let count = self.input.count()?;
+        let modifiers = (0..count)
+            .map(|_| self.basic_modifier(context))
+            .collect::<Result<_, _>>()?;
+        Ok((operation, values, modifiers))
+    }
+
+    fn basic_values<T: ValueType>(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<ModifierValues<T>, CacheError>
+    where
+        [(); T::VALUE_COUNT]:,
+    {
+        Ok(match self.input.u8()? {
+            VALUES_STATIC => {
+                let mut components = [0.0; T::VALUE_COUNT];
+                for component in &mut components {
+                    *component = self.input.f32()?;
+                }
+                ModifierValues::Static(T::from_slice(&components))
+            }
+            VALUES_DYNAMIC => ModifierValues::Dynamic(self.providers(context)?),
+            tag => return Err(CacheError::InvalidTag(tag)),
+        })
+    }
+
+    fn quaternion_points(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<Vec<QuaternionPointData>, CacheError> {
+        let count = self.input.count()?;
+        let mut points = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (time, easing, spline) = self.point_header()?;
+            let smooth = match spline {
+                SplineKind::Linear => false,
+                SplineKind::CatmullRom => true,
+                spline => return Err(CacheError::UnsupportedSpline(spline)),
+            };
+            self.control_count(spline)?;
+            let (_, values, modifiers) = self.quaternion_parts(context)?;
+            points.push(
+                QuaternionPointData::new(values, time, modifiers, easing).with_smooth(smooth),
+            );
+        }
+        Ok(points)
+    }
+
+    fn quaternion_modifier(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<QuaternionModifier, CacheError> {
+        let (operation, values, modifiers) = self.quaternion_parts(context)?;
+        #[cfg(feature = "extended_ops")]
+        if operation == Operation::Clamp {
+            let upper = self.quaternion_values(context)?;
+            return Ok(QuaternionModifier::clamp(
+                QuaternionModifier::new(values, modifiers.clone(), Operation::None),
+                QuaternionModifier::new(upper, modifiers, Operation::None),
+            ));
+        }
+        Ok(QuaternionModifier::new(values, modifiers, operation))
+    }
+
+    fn quaternion_parts(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<(Operation, QuaternionValues, Vec<QuaternionModifier>), CacheError> {
+        let operation = self.operation()?;
+        let values = self.quaternion_values(context)?;
+        let count = self.input.count()?;
+        let modifiers = (0..count)
+            .map(|_| self.quaternion_modifier(context))
+            .collect::<Result<_, _>>()?;
+        Ok((operation, values, modifiers))
+    }
+
+    fn quaternion_values(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<QuaternionValues, CacheError> {
+        Ok(match self.input.u8()? {
+            VALUES_STATIC => {
+                let euler = Vec3::new(self.input.f32()?, self.input.f32()?, self.input.f32()?);
+                QuaternionValues::Static(euler, Quat::from_unity_euler_degrees(euler))
+            }
+            VALUES_DYNAMIC => QuaternionValues::Dynamic(self.providers(context)?),
+            tag => return Err(CacheError::InvalidTag(tag)),
+        })
+    }
+
+    fn providers(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<SmallVec<[ValueProvider; 1]>, CacheError> {
+        let count = self.input.count()?;
+        let mut providers = SmallVec::with_capacity(count);
+        for _ in 0..count {
+            let provider = match self.input.u8()? {
+                PROVIDER_STATIC => {
+                    let is_rotation = self.bool()?;
+                    let len = self.input.count()?;
+                    let values = (0..len)
+                        .map(|_| self.input.f32())
+                        .collect::<Result<SmallVec<[f32; 4]>, _>>()?;
+                    ValueProvider::Static(StaticValues::new(values, is_rotation))
+                }
+                PROVIDER_KEY => {
+                    context
+                        .try_get_value_provider(self.string()?)
+                        .map_err(|e| match e {
+                            BaseError::UnknownBase(base) => CacheError::UnknownBase(base),
+                            e => CacheError::Base(e),
+                        })?
+                }
+                tag => return Err(CacheError::InvalidTag(tag)),
+            };
+            providers.push(provider);
+        }
+        Ok(providers)
+    }
+}
@@ -1,27 +1,37 @@
 pub mod base_point_definition;
 pub mod basic_point_definition;
+pub mod cache;
+pub mod parse_error;
 pub mod point_definition_interpolation;
+pub mod spline;
 
 // specific handling
 pub mod quaternion_point_definition;
 pub mod vector3_point_definition;
 
+#[cfg(feature = "json")]
+pub(crate) mod raw;
+#[cfg(feature = "json")]
+pub mod registry;
+#[cfg(feature = "json")]
+pub mod validate;
+
 pub type FloatPointDefinition = basic_point_definition::BasicPointDefinition<f32>;
 pub type Vector4PointDefinition = basic_point_definition::BasicPointDefinition<glam::Vec4>;
 
-use std::str::FromStr;
-
 #[cfg(feature = "json")]
 use serde_json::Value as JsonValue;
 
-#[cfg(feature = "json")]
-use serde_json::json;
 use smallvec::SmallVec;
 
 use crate::base_provider_context::BaseProviderContext;
 use crate::base_value::WrapBaseValueType;
 use crate::modifiers::ModifierLike;
 use crate::point_data::PointDataLike;
+use crate::point_definition::parse_error::ParseErrorKind;
+#[cfg(feature = "json")]
+use crate::point_definition::parse_error::{JsonPath, ParseError};
+use crate::providers::dependencies::BaseDependencies;
 use crate::{
     easings::functions::Functions, modifiers::operation::Operation, providers::ValueProvider,
 };
@@ -49,6 +59,16 @@ where
     // Required methods common to all definitions
     fn get_count(&self) -> usize;
     fn has_base_provider(&self) -> bool;
+
+    /// The bases and stateful providers read when sampling any point, found without evaluating
+    /// them. Unlike [`PointDefinitionLike::has_base_provider`] this says which ones.
+    fn base_dependencies(&self) -> BaseDependencies {
+        let mut dependencies = BaseDependencies::new();
+        for point in self.get_points() {
+            point.collect_dependencies(&mut dependencies);
+        }
+        dependencies
+    }
     fn interpolate_points(
         &self,
         l: &Self::PointData,
@@ -63,14 +83,17 @@ where
         modifiers: Vec<Self::Modifier>,
         operation: Operation,
         context: &BaseProviderContext,
-    ) -> Self::Modifier;
+    ) -> Result<Self::Modifier, ParseErrorKind>;
+    /// `controls` are the point's spline control points, parsed like modifiers from arrays
+    /// flagged [`spline::CONTROL_FLAG`].
     fn create_point_data(
         values: SmallVec<[ValueProvider; 1]>,
         flags: Vec<String>,
         modifiers: Vec<Self::Modifier>,
+        controls: Vec<Self::Modifier>,
         easing: Functions,
         context: &BaseProviderContext,
-    ) -> Self::PointData;
+    ) -> Result<Self::PointData, ParseErrorKind>;
     // fn get_points_mut(&mut self) -> &mut Vec<PointData>;
     fn get_points(&self) -> &[Self::PointData];
 
@@ -79,143 +102,92 @@ where
     fn new(points: Vec<Self::PointData>) -> Self;
 
     /// Deserializes a JSON value into a Modifier. This is used for parsing modifiers from JSON.
+    ///
+    /// Panics on malformed input; see [`PointDefinitionLike::try_deserialize_modifier`].
     #[cfg(feature = "json")]
     fn deserialize_modifier(list: &JsonValue, context: &mut BaseProviderContext) -> Self::Modifier {
-        let mut modifiers: Option<Vec<Self::Modifier>> = None;
-        let mut operation: Option<Operation> = None;
-        let mut values: Option<Vec<ValueProvider>> = None;
-
-        // Group values similar to PointDefinition::group_values
-        for group in group_values(list) {
-            match group.0 {
-                GroupType::Value => {
-                    use crate::prelude::deserialize_values;
-
-                    values = Some(deserialize_values(&group.1, context));
-                }
-                GroupType::Modifier => {
-                    modifiers = Some(
-                        group
-                            .1
-                            .iter()
-                            .map(|m| Self::deserialize_modifier(m, context))
-                            .collect(),
-                    );
-                }
-                GroupType::Flag => {
-                    operation = Some(Operation::from_str(group.1[0].as_str().unwrap()).unwrap());
-                }
-            }
-        }
-
-        // Validate required fields
-        //let values = values.expect("No points found.");
-        //let operation = operation.expect("No operation found.");
+        Self::try_deserialize_modifier(list, &JsonPath::new(), context)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        // Create modifier with collected values
-        Self::create_modifier(
-            values.expect("No values found.").into(),
-            modifiers.unwrap_or_default(),
-            operation.expect("No operation found."),
-            context,
-        )
+    /// Fallible version of [`PointDefinitionLike::deserialize_modifier`].
+    ///
+    /// `path` is the location of `list` in the surrounding point definition and is used
+    /// to report where an error occurred.
+    #[cfg(feature = "json")]
+    fn try_deserialize_modifier(
+        list: &JsonValue,
+        path: &JsonPath,
+        context: &mut BaseProviderContext,
+    ) -> Result<Self::Modifier, ParseError> {
+        raw::parse_modifier::<T, Self>(&raw::RawElement::from_value(list), path, context)
     }
 
     /// Parses a JSON value into a PointDefinition.
     ///
+    /// Panics on malformed input; use [`PointDefinitionLike::try_parse`] to get a [`ParseError`] instead.
+    #[cfg(feature = "json")]
+    fn parse(value: JsonValue, context: &mut BaseProviderContext) -> Self
+    where
+        Self: Sized,
+    {
+        Self::try_parse(&value, context).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Parses a JSON value into a PointDefinition, reporting malformed input as a [`ParseError`].
+    ///
     /// Accepted input shapes (compat with Heck spec):
     /// - An array of points: `[[p0], [p1], ...]` (each point is itself an array)
     /// - A single point shorthand: `[v0, v1, ..., time]` (top-level array that is not an array-of-arrays)
     /// - A single-point-without-time shorthand: `[v0, v1, ...]` — this will be treated as time `0`.
     ///
     /// This method normalizes the input into an array-of-points form and then groups values/modifiers/flags
-    /// for each point. It is defensive: non-array input or empty arrays return `Self::default()`.
+    /// for each point. Null points and points without values are skipped.
+    ///
+    /// Error paths are relative to `value`: `[3][2]` is the third element of the fourth point.
+    /// For the single-point shorthand the point itself is the root, so paths start at its elements.
     #[cfg(feature = "json")]
-    fn parse(value: JsonValue, context: &mut BaseProviderContext) -> Self
+    fn try_parse(value: &JsonValue, context: &mut BaseProviderContext) -> Result<Self, ParseError>
     where
         Self: Sized,
     {
-        // Single-point shorthand (e.g. `[v0, v1, ...]`) — wrap as an array-of-points
-        let root: JsonValue = match value.as_array().unwrap()[0] {
-            JsonValue::Array(_) => value,
-            _ => {
-                let mut cloned: Vec<JsonValue> = value.as_array().unwrap().clone();
-                // append a time value of 0 so [x] becomes [[x, 0]]
-                cloned.push(json!(0));
-                json!([cloned])
-            }
-        };
-
-        // If the root is not an array at this point, return default.
-        let Some(array) = root.as_array() else {
-            return Self::default();
-        };
-
-        let mut points: Vec<Self::PointData> = vec![];
-        for raw_point in array {
-            if raw_point.is_null() {
-                continue;
-            }
+        raw::parse_definition(&raw::RawElement::from_value(value), context)
+    }
 
-            let mut easing = Functions::EaseLinear;
-            let mut modifiers: Option<Vec<Self::Modifier>> = None;
-            let mut flags: Option<Vec<String>> = None;
-            let mut vals: Option<Vec<ValueProvider>> = None;
-
-            // Group the values and flags. (Assuming each raw_point has a structure similar to the C++ JSON)
-            for group in group_values(raw_point) {
-                match group.0 {
-                    GroupType::Value => {
-                        use crate::prelude::deserialize_values;
-
-                        vals = Some(deserialize_values(&group.1, context));
-                    }
-                    GroupType::Modifier => {
-                        modifiers = Some(
-                            group
-                                .1
-                                .iter()
-                                .map(|m| Self::deserialize_modifier(m, context))
-                                .collect(),
-                        );
-                    }
-                    GroupType::Flag => {
-                        // Convert the group values (group.1) into a Vec<String>
-                        let flags_vec: Vec<String> = group
-                            .1
-                            .iter()
-                            .filter_map(|v| v.as_str().map(String::from))
-                            .collect();
-
-                        // Set the flags collected from the group.
-                        flags = Some(flags_vec);
-
-                        // Find the first flag starting with "ease" just like in the C# code.
-                        if let Some(ref flags_inner) = flags
-                            && let Some(easing_string) =
-                                flags_inner.iter().find(|flag| flag.starts_with("ease"))
-                        {
-                            easing =
-                                Functions::from_str(easing_string).unwrap_or(Functions::EaseLinear);
-                        }
-                    }
-                }
-            }
+    /// Parses a point definition straight from JSON text, without building a
+    /// [`serde_json::Value`] first.
+    ///
+    /// Produces the same definition and errors as [`PointDefinitionLike::try_parse`] on the
+    /// equivalent value. Malformed JSON is reported as [`ParseErrorKind::InvalidJson`].
+    #[cfg(feature = "json")]
+    fn try_parse_str(json: &str, context: &mut BaseProviderContext) -> Result<Self, ParseError>
+    where
+        Self: Sized,
+    {
+        Self::try_parse_slice(json.as_bytes(), context)
+    }
 
-            // Create point data only if we have values
-            let Some(vs) = vals else { continue };
-
-            let point_data = Self::create_point_data(
-                vs.into(),
-                flags.unwrap_or_default(),
-                modifiers.unwrap_or_default(),
-                easing,
-                context,
-            );
-            points.push(point_data);
-        }
+    /// Byte slice version of [`PointDefinitionLike::try_parse_str`].
+    #[cfg(feature = "json")]
+    fn try_parse_slice(json: &[u8], context: &mut BaseProviderContext) -> Result<Self, ParseError>
+    where
+        Self: Sized,
+    {
+        raw::parse_definition(&raw::from_slice(json)?, context)
+    }
 
-        Self::new(points)
+    /// Writes the definition back to canonical Heck JSON: always an array of points, with
+    /// provider strings, modifiers, easings and spline flags as they were parsed.
+    ///
+    /// The result parses back into an equivalent definition.
+    #[cfg(feature = "json")]
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(
+            self.get_points()
+                .iter()
+                .map(PointDataLike::to_json)
+                .collect(),
+        )
     }
 
     /// Interpolates the point definition at a given time, returning the interpolated value and a boolean indicating if it's the last point.
@@ -276,26 +248,41 @@ fn search_index<P: PointDataLike<T>, T>(points: &[P], time: f32) -> (usize, usiz
     (l, r)
 }
 
-// Helper method to group values from a JSON value.
-// In a more complete implementation, you'd examine the JSON structure.
-#[cfg(feature = "json")]
-fn group_values(value: &JsonValue) -> std::collections::HashMap<GroupType, Vec<&JsonValue>> {
-    use std::collections::HashMap;
-
-    let JsonValue::Array(array) = value else {
-        return HashMap::new();
-    };
-
-    let mut result: HashMap<GroupType, Vec<&JsonValue>> = HashMap::new();
-    for val in array {
-        // group values by their type in the array
-        let entry = match val {
-            JsonValue::String(s) if !s.starts_with("base") => GroupType::Flag,
-            JsonValue::Array(_) => GroupType::Modifier,
-            _ => GroupType::Value,
-        };
-        result.entry(entry).or_default().push(val);
+/// Splits the values of an `opClamp` modifier into its lower and upper bounds, `count` components
+/// each. Numbers can be split between the bounds, provider strings can't.
+#[cfg(feature = "extended_ops")]
+#[allow(clippy::type_complexity)]
+pub(crate) fn split_clamp_bounds(
+    values: SmallVec<[ValueProvider; 1]>,
+    count: usize,
+    context: &BaseProviderContext,
+) -> Result<(SmallVec<[ValueProvider; 1]>, SmallVec<[ValueProvider; 1]>), ParseErrorKind> {
+    use crate::providers::{AbstractValueProvider, r#static::StaticValues};
+
+    let found: usize = values.iter().map(|v| v.values(context).len()).sum();
+    if found != count * 2 {
+        return Err(ParseErrorKind::WrongComponentCount {
+            expected: count * 2,
+            found,
+        });
     }
 
-    result
+    let mut lower = SmallVec::new();
+    let mut upper = SmallVec::new();
+    let mut position = 0;
+    for value in values {
+        let len = value.values(context).len();
+        match value {
+            ValueProvider::Static(s) if position < count && position + len > count => {
+                let (l, u) = s.values.split_at(count - position);
+                lower.push(ValueProvider::Static(StaticValues::new(l, s.is_rotation)));
+                upper.push(ValueProvider::Static(StaticValues::new(u, s.is_rotation)));
+            }
+            // A provider straddling the bounds is reported by the bound's component count
+            value if position < count => lower.push(value),
+            value => upper.push(value),
+        }
+        position += len;
+    }
+    Ok((lower, upper))
 }
@@ -0,0 +1,135 @@
+use std::fmt;
+
+use smallvec::SmallVec;
+
+use crate::base_provider_context::BaseError;
+
+/// Location of a value inside the point definition JSON.
+///
+/// Each entry is an array index, outermost first. A path of `[3, 2]` is displayed as `[3][2]`
+/// and refers to the third element of the fourth point.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsonPath(SmallVec<[usize; 4]>);
+
+impl JsonPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a new path with `index` appended.
+    pub fn child(&self, index: usize) -> Self {
+        let mut path = self.clone();
+        path.0.push(index);
+        path
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.0
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<&[usize]> for JsonPath {
+    fn from(indices: &[usize]) -> Self {
+        Self(SmallVec::from_slice(indices))
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for index in &self.0 {
+            write!(f, "[{index}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// What went wrong while parsing a point definition.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// A point definition or modifier was expected to be a JSON array.
+    NotAnArray,
+    /// The point definition (or a modifier) is an empty array.
+    EmptyArray,
+    /// A modifier produced a different number of components than the property expects.
+    WrongComponentCount { expected: usize, found: usize },
+    /// A point has no value to take its time from.
+    MissingTime,
+    /// A modifier has no values, only flags or nested modifiers.
+    MissingValues,
+    /// A modifier has no `op*` flag.
+    MissingOperation,
+    /// A modifier flag is not a known operation.
+    UnknownOperation(String),
+    /// The input is not valid JSON. Only returned when parsing JSON text.
+    InvalidJson(String),
+    /// A `base*` string names a base that isn't registered with the context.
+    UnknownBase(String),
+    /// A `base*` provider couldn't be created for another reason.
+    Base(BaseError),
+    /// A rotation operation like `opRotate` in a modifier of a non-rotation property.
+    RotationOnlyOperation(String),
+    /// A point has a different number of `splineControl` arrays than its spline takes.
+    WrongControlCount { expected: usize, found: usize },
+    /// A spline flag the property can't interpolate with, e.g. `splineBezier` on a rotation.
+    UnsupportedSpline(String),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::NotAnArray => write!(f, "expected an array"),
+            ParseErrorKind::EmptyArray => write!(f, "empty array"),
+            ParseErrorKind::WrongComponentCount { expected, found } => write!(
+                f,
+                "modifier point must have {expected} numbers, found {found}"
+            ),
+            ParseErrorKind::MissingTime => write!(f, "point has no time value"),
+            ParseErrorKind::MissingValues => write!(f, "modifier has no values"),
+            ParseErrorKind::MissingOperation => write!(f, "modifier has no operation"),
+            ParseErrorKind::UnknownOperation(op) => write!(f, "unknown operation \"{op}\""),
+            ParseErrorKind::InvalidJson(error) => write!(f, "invalid JSON: {error}"),
+            ParseErrorKind::UnknownBase(base) => write!(f, "unknown base provider \"{base}\""),
+            ParseErrorKind::Base(error) => write!(f, "{error}"),
+            ParseErrorKind::RotationOnlyOperation(op) => {
+                write!(f, "operation \"{op}\" only applies to rotations")
+            }
+            ParseErrorKind::WrongControlCount { expected, found } => write!(
+                f,
+                "point's spline takes {expected} control points, found {found}"
+            ),
+            ParseErrorKind::UnsupportedSpline(spline) => {
+                write!(f, "spline \"{spline}\" is not supported for this property")
+            }
+        }
+    }
+}
+
+/// Error returned by [`PointDefinitionLike::try_parse`](super::PointDefinitionLike::try_parse).
+///
+/// Carries the kind of failure and the [`JsonPath`] of the offending value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub path: JsonPath,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, path: JsonPath) -> Self {
+        Self { kind, path }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_root() {
+            return write!(f, "{}", self.kind);
+        }
+        write!(f, "{} at {}", self.kind, self.path)
+    }
+}
+
+impl std::error::Error for ParseError {}
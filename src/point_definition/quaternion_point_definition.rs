@@ -1,5 +1,3 @@
-use std::rc::Rc;
-
 use glam::{Quat, Vec3, vec3};
 use smallvec::SmallVec;
 
@@ -14,14 +12,49 @@ use crate::{
     point_data::{PointDataLike, quaternion_point_data::QuaternionPointData},
     prelude::{AbstractValueProvider, ValueProvider},
     quaternion_utils::QuaternionUtilsExt,
+    shared::Shared,
     value_types::ValueType,
 };
 
-use super::PointDefinitionLike;
+use super::{PointDefinitionLike, parse_error::ParseErrorKind, spline::SplineKind};
 
 #[derive(Default, Debug, Clone)]
 pub struct QuaternionPointDefinition {
-    points: Rc<[QuaternionPointData]>,
+    points: Shared<[QuaternionPointData]>,
+}
+
+impl QuaternionPointDefinition {
+    /// Spherical spline (SQUAD) between `l_0` and `r_0`, the rotation counterpart of the
+    /// Catmull-Rom spline used for vectors. The control rotations are placed so the angular
+    /// velocity is continuous across points, and missing neighbours at either end of the
+    /// definition fall back to the point itself.
+    fn smooth_rotation_slerp(
+        l_0: Quat,
+        l_sub_1: Option<Quat>,
+        r_0: Quat,
+        r_add_1: Option<Quat>,
+        time: f32,
+    ) -> Quat {
+        // Keep each rotation in the hemisphere of the one before it, so the spline
+        // takes the short way round like `slerp` does
+        let closest = |to: Quat, q: Quat| if to.dot(q) < 0.0 { -q } else { q };
+        let r_0 = closest(l_0, r_0);
+        let p0 = closest(l_0, l_sub_1.unwrap_or(l_0));
+        let p3 = closest(r_0, r_add_1.unwrap_or(r_0));
+
+        // Shoemake's intermediate rotation: q * exp(-(log(q⁻¹ next) + log(q⁻¹ prev)) / 4),
+        // with `from_scaled_axis(v) == exp(v / 2)`
+        let control = |prev: Quat, q: Quat, next: Quat| {
+            let inverse = q.inverse();
+            let tangent = (inverse * next).to_scaled_axis() + (inverse * prev).to_scaled_axis();
+            q * Quat::from_scaled_axis(tangent * -0.25)
+        };
+        let s_l = control(p0, l_0, r_0);
+        let s_r = control(l_0, r_0, p3);
+
+        l_0.slerp(r_0, time)
+            .slerp(s_l.slerp(s_r, time), 2.0 * time * (1.0 - time))
+    }
 }
 
 impl PointDefinitionLike<Quat> for QuaternionPointDefinition {
@@ -45,7 +78,14 @@ impl PointDefinitionLike<Quat> for QuaternionPointDefinition {
         modifiers: Vec<Self::Modifier>,
         operation: Operation,
         context: &BaseProviderContext,
-    ) -> Self::Modifier {
+    ) -> Result<Self::Modifier, ParseErrorKind> {
+        #[cfg(feature = "extended_ops")]
+        if operation == Operation::Clamp {
+            let (lower, upper) = super::split_clamp_bounds(values, Vec3::VALUE_COUNT, context)?;
+            let lower = Self::create_modifier(lower, modifiers.clone(), Operation::None, context)?;
+            let upper = Self::create_modifier(upper, modifiers, Operation::None, context)?;
+            return Ok(QuaternionModifier::clamp(lower, upper));
+        }
         // values are stored as euler angles in the point definition,
         // but we want to convert them to quaternions for the modifier
         // euler angles [x, y, z] in degrees are converted to quaternions using the same convention as Unity (ZXY(Ex) order)
@@ -60,21 +100,27 @@ impl PointDefinitionLike<Quat> for QuaternionPointDefinition {
             }
             _ => {
                 let count: usize = values.iter().map(|v| v.values(context).len()).sum();
-                assert_eq!(count, 3, "Quaternion modifier point must have 3 numbers");
+                if count != Vec3::VALUE_COUNT {
+                    return Err(ParseErrorKind::WrongComponentCount {
+                        expected: Vec3::VALUE_COUNT,
+                        found: count,
+                    });
+                }
                 QuaternionValues::Dynamic(values)
             }
         };
 
-        QuaternionModifier::new(val, modifiers, operation)
+        Ok(QuaternionModifier::new(val, modifiers, operation))
     }
 
     fn create_point_data(
         values: SmallVec<[ValueProvider; 1]>,
-        _flags: Vec<String>,
+        flags: Vec<String>,
         modifiers: Vec<Self::Modifier>,
+        controls: Vec<Self::Modifier>,
         easing: Functions,
         context: &BaseProviderContext,
-    ) -> Self::PointData {
+    ) -> Result<Self::PointData, ParseErrorKind> {
         let (base_values, time) = match values.as_slice() {
             // [vec3, time]
             [ValueProvider::Static(static_val)]
@@ -90,29 +136,51 @@ impl PointDefinitionLike<Quat> for QuaternionPointDefinition {
             }
             _ => {
                 // https://github.com/Aeroluna/Heck/blob/1dc9f470a7f8d3e64d0e3bc34e2f2279190eb8b8/Heck/Animation/PointDefinition/Vector3PointDefinition.cs#L80-L81
-                let time = values.last()
+                let time = values
+                    .last()
                     .and_then(|vp| vp.values(context).last().copied())
-                    .unwrap_or_else(|| panic!("Expected at least one value provider with at least {} values for point data, with the last one being time",
-                        Vec3::VALUE_COUNT + 1));
+                    .ok_or(ParseErrorKind::MissingTime)?;
 
                 (QuaternionValues::Dynamic(values), time)
             }
         };
 
-        QuaternionPointData::new(base_values, time, modifiers, easing)
+        // Catmull-Rom points use SQUAD, other splines don't carry over to rotations
+        let spline = SplineKind::from_flags(&flags);
+        let smooth = match spline {
+            SplineKind::Linear => false,
+            SplineKind::CatmullRom => true,
+            _ => {
+                let flag = spline.flag().unwrap_or_default();
+                return Err(ParseErrorKind::UnsupportedSpline(flag.to_string()));
+            }
+        };
+        spline.check_controls(controls.len())?;
+
+        Ok(QuaternionPointData::new(base_values, time, modifiers, easing).with_smooth(smooth))
     }
 
     fn interpolate_points(
         &self,
         l: &Self::PointData,
         r: &Self::PointData,
-        _l_index: usize,
-        _r_index: usize,
+        l_index: usize,
+        r_index: usize,
         time: f32,
         context: &BaseProviderContext,
     ) -> Quat {
         let point_l = PointDataLike::get_point(l, context);
         let point_r = PointDataLike::get_point(r, context);
+        if r.smooth {
+            let l_sub_1 = l_index
+                .checked_sub(1)
+                .and_then(|i| self.points.get(i))
+                .map(|p| p.get_point(context));
+            let r_add_1 = self.points.get(r_index + 1).map(|p| p.get_point(context));
+
+            return Self::smooth_rotation_slerp(point_l, l_sub_1, point_r, r_add_1, time);
+        }
+
         point_l.slerp(point_r, time)
     }
 
@@ -122,7 +190,7 @@ impl PointDefinitionLike<Quat> for QuaternionPointDefinition {
 
     fn new(points: Vec<Self::PointData>) -> Self {
         Self {
-            points: Rc::from(points),
+            points: Shared::from(points),
         }
     }
 }
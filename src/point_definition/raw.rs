@@ -0,0 +1,344 @@
+//! Borrowed view of point definition JSON.
+//!
+//! [`RawElement`] keeps only what the point definition parser looks at: numbers, strings split
+//! into `base*` providers and flags, and nested arrays. It can be built from a
+//! [`serde_json::Value`] without copying, or deserialized straight from JSON text, where strings
+//! borrow from the input unless they contain escapes. Both [`PointDefinitionLike::try_parse`] and
+//! [`PointDefinitionLike::try_parse_str`] go through [`parse_definition`], so they produce the same
+//! definitions.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::Value as JsonValue;
+use smallvec::SmallVec;
+
+use crate::base_provider_context::{BaseError, BaseProviderContext};
+use crate::easings::functions::Functions;
+use crate::modifiers::operation::Operation;
+use crate::providers::{ValueProvider, r#static::StaticValues};
+
+use super::PointDefinitionLike;
+use super::parse_error::{JsonPath, ParseError, ParseErrorKind};
+use super::spline::CONTROL_FLAG;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum RawElement<'a> {
+    Number(f64),
+    /// A string starting with `base`.
+    Base(Cow<'a, str>),
+    /// Any other string: easings, spline flags and operations.
+    Flag(Cow<'a, str>),
+    /// A point or a modifier.
+    Array(Vec<RawElement<'a>>),
+    /// `null`, booleans and objects. Grouped with the values but contribute no number.
+    Other,
+}
+
+impl<'a> RawElement<'a> {
+    pub(crate) fn from_value(value: &'a JsonValue) -> Self {
+        match value {
+            JsonValue::Number(n) => n.as_f64().map_or(RawElement::Other, RawElement::Number),
+            JsonValue::String(s) => RawElement::from_str(Cow::Borrowed(s)),
+            JsonValue::Array(array) => {
+                RawElement::Array(array.iter().map(RawElement::from_value).collect())
+            }
+            _ => RawElement::Other,
+        }
+    }
+
+    fn from_str(s: Cow<'a, str>) -> Self {
+        match s.starts_with("base") {
+            true => RawElement::Base(s),
+            false => RawElement::Flag(s),
+        }
+    }
+
+    /// Arrays whose first flag is [`CONTROL_FLAG`], the control points of a spline.
+    fn is_control(&self) -> bool {
+        let RawElement::Array(elements) = self else {
+            return false;
+        };
+        elements
+            .iter()
+            .find_map(|e| match e {
+                RawElement::Flag(flag) => Some(flag),
+                _ => None,
+            })
+            .is_some_and(|flag| flag == CONTROL_FLAG)
+    }
+
+    /// Elements that are neither flags nor modifiers.
+    fn is_value(&self) -> bool {
+        !matches!(self, RawElement::Flag(_) | RawElement::Array(_))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawElement<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RawElementVisitor)
+    }
+}
+
+struct RawElementVisitor;
+
+impl<'de> Visitor<'de> for RawElementVisitor {
+    type Value = RawElement<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a point definition element")
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(RawElement::Other)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(RawElement::Number(v as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(RawElement::Number(v as f64))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(RawElement::Number(v))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(RawElement::from_str(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(RawElement::from_str(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(RawElement::from_str(Cow::Owned(v)))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(RawElement::Other)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(RawElement::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(RawElement::Other)
+    }
+}
+
+/// Deserializes JSON text into a [`RawElement`], reporting syntax errors as
+/// [`ParseErrorKind::InvalidJson`].
+pub(crate) fn from_slice(json: &[u8]) -> Result<RawElement<'_>, ParseError> {
+    serde_json::from_slice(json)
+        .map_err(|e| ParseError::new(ParseErrorKind::InvalidJson(e.to_string()), JsonPath::new()))
+}
+
+/// Builds a point definition; see [`PointDefinitionLike::try_parse`] for the accepted shapes.
+pub(crate) fn parse_definition<T, D>(
+    raw: &RawElement,
+    context: &mut BaseProviderContext,
+) -> Result<D, ParseError>
+where
+    T: Default + Clone,
+    D: PointDefinitionLike<T>,
+{
+    let RawElement::Array(array) = raw else {
+        return Err(ParseError::new(ParseErrorKind::NotAnArray, JsonPath::new()));
+    };
+    let Some(first) = array.first() else {
+        return Err(ParseError::new(ParseErrorKind::EmptyArray, JsonPath::new()));
+    };
+
+    // Single-point shorthand (e.g. `[v0, v1, ...]`) with an implicit time of 0
+    if !matches!(first, RawElement::Array(_)) {
+        let time = RawElement::Number(0.0);
+        let elements = array.iter().chain(std::iter::once(&time));
+        let points = parse_point::<T, D, _>(elements, &JsonPath::new(), context)?;
+        return Ok(D::new(points.into_iter().collect()));
+    }
+
+    let mut points = Vec::with_capacity(array.len());
+    for (index, raw_point) in array.iter().enumerate() {
+        // null and non-array points have no values and are skipped
+        let RawElement::Array(elements) = raw_point else {
+            continue;
+        };
+        let path = JsonPath::new().child(index);
+        points.extend(parse_point::<T, D, _>(elements.iter(), &path, context)?);
+    }
+
+    Ok(D::new(points))
+}
+
+// Returns `None` for points without values.
+fn parse_point<'r, 'a: 'r, T, D, I>(
+    elements: I,
+    path: &JsonPath,
+    context: &mut BaseProviderContext,
+) -> Result<Option<D::PointData>, ParseError>
+where
+    T: Default + Clone,
+    D: PointDefinitionLike<T>,
+    I: Iterator<Item = &'r RawElement<'a>> + Clone,
+{
+    let mut modifiers = vec![];
+    let mut controls = vec![];
+    let mut flags = vec![];
+
+    for (index, element) in elements.clone().enumerate() {
+        match element {
+            RawElement::Array(_) if element.is_control() => controls.push(parse_nested::<T, D>(
+                element,
+                &path.child(index),
+                context,
+                true,
+            )?),
+            RawElement::Array(_) => modifiers.push(parse_modifier::<T, D>(
+                element,
+                &path.child(index),
+                context,
+            )?),
+            RawElement::Flag(flag) => flags.push(flag.to_string()),
+            _ => {}
+        }
+    }
+
+    let values = values_from_raw(elements.filter(|e| e.is_value()), context)
+        .map_err(|kind| ParseError::new(kind, path.clone()))?;
+    let Some(values) = values else {
+        return Ok(None);
+    };
+
+    // The first flag starting with "ease" is the easing, like in Heck
+    let easing = flags
+        .iter()
+        .find(|flag| flag.starts_with("ease"))
+        .and_then(|flag| Functions::from_str(flag).ok())
+        .unwrap_or(Functions::EaseLinear);
+
+    D::create_point_data(values, flags, modifiers, controls, easing, context)
+        .map(Some)
+        .map_err(|kind| ParseError::new(kind, path.clone()))
+}
+
+/// Builds a modifier from `[values..., modifiers..., "opX"]`. Only the first flag is read as the
+/// operation.
+pub(crate) fn parse_modifier<T, D>(
+    raw: &RawElement,
+    path: &JsonPath,
+    context: &mut BaseProviderContext,
+) -> Result<D::Modifier, ParseError>
+where
+    T: Default + Clone,
+    D: PointDefinitionLike<T>,
+{
+    parse_nested::<T, D>(raw, path, context, false)
+}
+
+// Modifiers and spline control points share their layout; a control point's flag is
+// `splineControl` instead of an operation and it is built with `opNone`.
+fn parse_nested<T, D>(
+    raw: &RawElement,
+    path: &JsonPath,
+    context: &mut BaseProviderContext,
+    control: bool,
+) -> Result<D::Modifier, ParseError>
+where
+    T: Default + Clone,
+    D: PointDefinitionLike<T>,
+{
+    let error = |kind| ParseError::new(kind, path.clone());
+
+    let elements = match raw {
+        RawElement::Array(elements) if elements.is_empty() => {
+            return Err(error(ParseErrorKind::EmptyArray));
+        }
+        RawElement::Array(elements) => elements,
+        _ => return Err(error(ParseErrorKind::NotAnArray)),
+    };
+
+    let mut modifiers = vec![];
+    let mut operation = None;
+
+    for (index, element) in elements.iter().enumerate() {
+        match element {
+            RawElement::Array(_) => modifiers.push(parse_modifier::<T, D>(
+                element,
+                &path.child(index),
+                context,
+            )?),
+            RawElement::Flag(_) if operation.is_none() && control => {
+                operation = Some(Operation::None);
+            }
+            RawElement::Flag(flag) if operation.is_none() => {
+                let op = Operation::from_str(flag).map_err(|_| {
+                    ParseError::new(
+                        ParseErrorKind::UnknownOperation(flag.to_string()),
+                        path.child(index),
+                    )
+                })?;
+                operation = Some(op);
+            }
+            _ => {}
+        }
+    }
+
+    let values = values_from_raw(elements.iter().filter(|e| e.is_value()), context)
+        .map_err(error)?
+        .ok_or_else(|| error(ParseErrorKind::MissingValues))?;
+    let operation = operation.ok_or_else(|| error(ParseErrorKind::MissingOperation))?;
+
+    D::create_modifier(values, modifiers, operation, context).map_err(error)
+}
+
+/// Turns the value elements of a point or modifier into providers. Consecutive numbers become one
+/// static provider and every `base*` string its own provider.
+///
+/// Returns `None` if there are no value elements.
+pub(crate) fn values_from_raw<'r, 'a: 'r>(
+    values: impl Iterator<Item = &'r RawElement<'a>>,
+    context: &mut BaseProviderContext,
+) -> Result<Option<SmallVec<[ValueProvider; 1]>>, ParseErrorKind> {
+    let mut result = SmallVec::new();
+    let mut has_values = false;
+    // `None` while no static run is open
+    let mut run: Option<SmallVec<[f32; 4]>> = None;
+
+    for value in values {
+        has_values = true;
+        match value {
+            RawElement::Base(base) => {
+                close(&mut result, run.take());
+                let provider = context.try_get_value_provider(base).map_err(|e| match e {
+                    BaseError::UnknownBase(name) => ParseErrorKind::UnknownBase(name),
+                    e => ParseErrorKind::Base(e),
+                })?;
+                result.push(provider);
+            }
+            RawElement::Number(n) => run.get_or_insert_default().push(*n as f32),
+            _ => {
+                run.get_or_insert_default();
+            }
+        }
+    }
+    close(&mut result, run);
+
+    Ok(has_values.then_some(result))
+}
+
+fn close(result: &mut SmallVec<[ValueProvider; 1]>, run: Option<SmallVec<[f32; 4]>>) {
+    if let Some(values) = run {
+        result.push(ValueProvider::Static(StaticValues::new(values, false)));
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
+
+use crate::{
+    base_provider_context::BaseProviderContext,
+    base_value::WrapBaseValueType,
+    point_definition::{base_point_definition::BasePointDefinition, parse_error::ParseError},
+};
+
+/// Named point definitions, as declared in a beatmap's `customData.pointDefinitions`.
+///
+/// Definitions are stored as raw JSON and typed lazily: the same name can be resolved as a
+/// float, vec3, vec4 or quaternion definition depending on the property that uses it. Each
+/// (name, type) pair is parsed once; later lookups return a cheap clone of the cached definition.
+#[derive(Default, Debug, Clone)]
+pub struct PointDefinitionRegistry {
+    entries: HashMap<String, RegistryEntry>,
+}
+
+#[derive(Default, Debug, Clone)]
+struct RegistryEntry {
+    raw: JsonValue,
+    // indexed by `WrapBaseValueType` discriminant
+    typed: [Option<BasePointDefinition>; 4],
+}
+
+impl PointDefinitionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a registry from a V3 `pointDefinitions` object mapping names to definitions.
+    ///
+    /// Returns an empty registry if `value` is not an object.
+    pub fn from_json(value: &JsonValue) -> Self {
+        let mut registry = Self::new();
+        if let Some(definitions) = value.as_object() {
+            for (name, definition) in definitions {
+                registry.insert(name.clone(), definition.clone());
+            }
+        }
+        registry
+    }
+
+    /// Adds or replaces a named definition, dropping any typed definitions parsed from the old one.
+    pub fn insert(&mut self, name: impl Into<String>, definition: JsonValue) {
+        self.entries.insert(
+            name.into(),
+            RegistryEntry {
+                raw: definition,
+                typed: Default::default(),
+            },
+        );
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// The raw JSON registered under `name`.
+    pub fn get_raw(&self, name: &str) -> Option<&JsonValue> {
+        self.entries.get(name).map(|e| &e.raw)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Resolves `name` as a definition of type `ty`, parsing it on first use.
+    ///
+    /// Returns `None` if no definition is registered under `name`.
+    /// Panics if `ty` is [`WrapBaseValueType::Unknown`].
+    pub fn resolve(
+        &mut self,
+        name: &str,
+        ty: WrapBaseValueType,
+        context: &mut BaseProviderContext,
+    ) -> Option<Result<BasePointDefinition, ParseError>> {
+        assert_ne!(
+            ty,
+            WrapBaseValueType::Unknown,
+            "Cannot resolve a point definition with Unknown type"
+        );
+
+        let entry = self.entries.get_mut(name)?;
+        let slot = &mut entry.typed[ty as usize];
+
+        if let Some(definition) = slot {
+            return Some(Ok(definition.clone()));
+        }
+
+        let result = BasePointDefinition::try_parse_typed(&entry.raw, ty, context);
+        if let Ok(definition) = &result {
+            *slot = Some(definition.clone());
+        }
+        Some(result)
+    }
+}
@@ -0,0 +1,238 @@
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use serde_json::Value as JsonValue;
+
+use crate::{
+    base_provider_context::BaseProviderContext,
+    modifiers::{ModifierLike, basic_modifier::BasicModifier},
+    point_data::{PointDataLike, basic_point_data::BasicPointData},
+    value_types::ValueType,
+};
+
+use super::parse_error::ParseErrorKind;
+
+/// Flag of the nested arrays that give a point its spline control points, e.g.
+/// `[1, 2, 0, "splineControl"]`. Like modifiers they can read bases and have nested modifiers.
+pub const CONTROL_FLAG: &str = "splineControl";
+
+/// How the segment leading to a point is interpolated, picked by a `spline*` flag on that point.
+///
+/// The Catmull-Rom families read the points on either side of the segment and use the segment's
+/// own end point where the definition has none. Bezier and Hermite segments take their control
+/// points from [`CONTROL_FLAG`] arrays on the point instead, see [`SplineKind::control_count`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SplineKind {
+    /// Straight line, used without a spline flag.
+    #[default]
+    Linear = 0,
+    /// `splineCatmullRom`: uniform Catmull-Rom spline through the points.
+    CatmullRom,
+    /// `splineCatmullRomCentripetal`: Catmull-Rom spline parameterized by the square root of the
+    /// distance between points, which avoids cusps and self-intersections when points are
+    /// unevenly spaced.
+    CentripetalCatmullRom,
+    /// `splineBezier`: cubic Bezier curve. The control points are the handle after the previous
+    /// point, then the handle before this one.
+    Bezier,
+    /// `splineHermite`: cubic Hermite curve. The control points are the tangent leaving the
+    /// previous point, then the tangent arriving at this one, each as the change over the whole
+    /// segment.
+    Hermite,
+    /// `splineBSpline`: uniform cubic B-spline. Smoother than Catmull-Rom, but only passes near
+    /// points inside a run of B-spline segments; where the run starts or ends it meets the point.
+    BSpline,
+}
+
+impl FromStr for SplineKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "splineCatmullRom" => Ok(Self::CatmullRom),
+            "splineCatmullRomCentripetal" => Ok(Self::CentripetalCatmullRom),
+            "splineBezier" => Ok(Self::Bezier),
+            "splineHermite" => Ok(Self::Hermite),
+            "splineBSpline" => Ok(Self::BSpline),
+            _ => Err(()),
+        }
+    }
+}
+
+impl SplineKind {
+    /// The point flag selecting this spline, `None` for [`SplineKind::Linear`].
+    pub fn flag(self) -> Option<&'static str> {
+        match self {
+            Self::Linear => None,
+            Self::CatmullRom => Some("splineCatmullRom"),
+            Self::CentripetalCatmullRom => Some("splineCatmullRomCentripetal"),
+            Self::Bezier => Some("splineBezier"),
+            Self::Hermite => Some("splineHermite"),
+            Self::BSpline => Some("splineBSpline"),
+        }
+    }
+
+    /// The spline named by the first spline flag in `flags`, like the easing is taken from the
+    /// first easing flag.
+    pub fn from_flags<S: AsRef<str>>(flags: &[S]) -> Self {
+        flags
+            .iter()
+            .find_map(|flag| Self::from_str(flag.as_ref()).ok())
+            .unwrap_or_default()
+    }
+
+    /// How many [`CONTROL_FLAG`] arrays a point with this spline takes.
+    pub fn control_count(self) -> usize {
+        match self {
+            Self::Bezier | Self::Hermite => 2,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn check_controls(self, found: usize) -> Result<(), ParseErrorKind> {
+        let expected = self.control_count();
+        match found == expected {
+            true => Ok(()),
+            false => Err(ParseErrorKind::WrongControlCount { expected, found }),
+        }
+    }
+}
+
+/// Writes a control point like a modifier, with [`CONTROL_FLAG`] in place of the operation.
+#[cfg(feature = "json")]
+pub(crate) fn control_json<T: ValueType>(control: &BasicModifier<T>) -> JsonValue
+where
+    [(); T::VALUE_COUNT]:,
+{
+    let mut json = control.to_json();
+    if let Some(flag) = json.as_array_mut().and_then(|elements| elements.last_mut()) {
+        *flag = CONTROL_FLAG.into();
+    }
+    json
+}
+
+/// Interpolates between `points[l_index]` and `points[r_index]` with the spline of the right
+/// point.
+pub(crate) fn interpolate_points<T: ValueType>(
+    points: &[BasicPointData<T>],
+    l_index: usize,
+    r_index: usize,
+    time: f32,
+    context: &BaseProviderContext,
+) -> T
+where
+    [(); T::VALUE_COUNT]:,
+{
+    let l = &points[l_index];
+    let r = &points[r_index];
+    let p1 = l.get_point(context);
+    let p2 = r.get_point(context);
+
+    let before = || l_index.checked_sub(1).and_then(|i| points.get(i));
+    let after = || points.get(r_index + 1);
+    let control = |i: usize| r.controls().get(i).map(|c| c.get_modified_point(context));
+
+    match r.spline {
+        SplineKind::Linear => T::value_lerp_clamped(p1, p2, time),
+        SplineKind::CatmullRom | SplineKind::CentripetalCatmullRom => {
+            let p0 = before().map_or(p1, |p| p.get_point(context));
+            let p3 = after().map_or(p2, |p| p.get_point(context));
+            match r.spline {
+                SplineKind::CatmullRom => catmull_rom(p0, p1, p2, p3, time),
+                _ => centripetal_catmull_rom(p0, p1, p2, p3, time),
+            }
+        }
+        SplineKind::Bezier => {
+            let c1 = control(0).unwrap_or(p1);
+            let c2 = control(1).unwrap_or(p2);
+            bezier(p1, c1, c2, p2, time)
+        }
+        SplineKind::Hermite => {
+            let m1 = control(0).unwrap_or_default();
+            let m2 = control(1).unwrap_or_default();
+            hermite(p1, m1, p2, m2, time)
+        }
+        SplineKind::BSpline => {
+            // Outside a run of B-spline segments, mirror the other end of the segment so the
+            // curve meets the point and joins the neighbouring segments without a jump
+            let p0 = match l.spline {
+                SplineKind::BSpline => before().map(|p| p.get_point(context)),
+                _ => None,
+            };
+            let p3 = after()
+                .filter(|p| p.spline == SplineKind::BSpline)
+                .map(|p| p.get_point(context));
+            let p0 = p0.unwrap_or_else(|| p1 * 2.0 - p2);
+            let p3 = p3.unwrap_or_else(|| p2 * 2.0 - p1);
+            b_spline(p0, p1, p2, p3, time)
+        }
+    }
+}
+
+fn catmull_rom<T: ValueType>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    let tt = t * t;
+    let ttt = tt * t;
+
+    let q0 = -ttt + (2.0 * tt) - t;
+    let q1 = (3.0 * ttt) - (5.0 * tt) + 2.0;
+    let q2 = (-3.0 * ttt) + (4.0 * tt) + t;
+    let q3 = ttt - tt;
+
+    (p0 * q0 + p1 * q1 + p2 * q2 + p3 * q3) * 0.5
+}
+
+// Tangent form of the centripetal spline, see Yuksel et al., "Parameterization and Applications
+// of Catmull-Rom Curves"
+fn centripetal_catmull_rom<T: ValueType>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    let knot = |a: T, b: T| distance(a, b).sqrt();
+    // Coincident points would divide by zero; fall back to the spacing of the segment itself
+    let dt1 = Some(knot(p1, p2)).filter(|&d| d > 1e-4).unwrap_or(1.0);
+    let dt0 = Some(knot(p0, p1)).filter(|&d| d > 1e-4).unwrap_or(dt1);
+    let dt2 = Some(knot(p2, p3)).filter(|&d| d > 1e-4).unwrap_or(dt1);
+
+    let m1 =
+        ((p1 - p0) * (1.0 / dt0) - (p2 - p0) * (1.0 / (dt0 + dt1)) + (p2 - p1) * (1.0 / dt1)) * dt1;
+    let m2 =
+        ((p2 - p1) * (1.0 / dt1) - (p3 - p1) * (1.0 / (dt1 + dt2)) + (p3 - p2) * (1.0 / dt2)) * dt1;
+    hermite(p1, m1, p2, m2, t)
+}
+
+fn bezier<T: ValueType>(p1: T, c1: T, c2: T, p2: T, t: f32) -> T {
+    let u = 1.0 - t;
+    p1 * (u * u * u) + c1 * (3.0 * u * u * t) + c2 * (3.0 * u * t * t) + p2 * (t * t * t)
+}
+
+fn hermite<T: ValueType>(p1: T, m1: T, p2: T, m2: T, t: f32) -> T {
+    let tt = t * t;
+    let ttt = tt * t;
+
+    let h00 = 2.0 * ttt - 3.0 * tt + 1.0;
+    let h10 = ttt - 2.0 * tt + t;
+    let h01 = -2.0 * ttt + 3.0 * tt;
+    let h11 = ttt - tt;
+
+    p1 * h00 + m1 * h10 + p2 * h01 + m2 * h11
+}
+
+fn b_spline<T: ValueType>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+    let u = 1.0 - t;
+    let tt = t * t;
+    let ttt = tt * t;
+
+    let b0 = u * u * u;
+    let b1 = 3.0 * ttt - 6.0 * tt + 4.0;
+    let b2 = -3.0 * ttt + 3.0 * tt + 3.0 * t + 1.0;
+    let b3 = ttt;
+
+    (p0 * b0 + p1 * b1 + p2 * b2 + p3 * b3) * (1.0 / 6.0)
+}
+
+fn distance<T: ValueType>(a: T, b: T) -> f32 {
+    let difference = b - a;
+    difference
+        .as_slice()
+        .iter()
+        .map(|v| v * v)
+        .sum::<f32>()
+        .sqrt()
+}
@@ -0,0 +1,403 @@
+//! Linting of raw point definition JSON.
+//!
+//! [`validate`] walks a Heck point definition without building it and reports every problem it
+//! finds, including the ones the parser silently works around (misspelled easings fall back to
+//! `easeLinear`, bad swizzle characters to `x`, unparseable smoothing specs to `s1`, misplaced
+//! flags are ignored).
+//!
+//! The check is untyped: it doesn't know which value type the definition is for, nor which bases
+//! are registered. Wrong component counts, missing times and unknown bases are only reported by
+//! [`try_parse`](super::PointDefinitionLike::try_parse), so a clean result doesn't guarantee the
+//! definition loads.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::Value as JsonValue;
+
+use crate::base_provider_context::{smooth_multiplier, swizzle_index};
+use crate::easings::functions::Functions;
+use crate::modifiers::operation::Operation;
+use crate::point_definition::parse_error::{JsonPath, ParseErrorKind};
+use crate::point_definition::spline::{CONTROL_FLAG, SplineKind};
+use crate::providers::generator::{GeneratorKind, GeneratorParam};
+use crate::providers::smooth::SmoothKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The definition still loads but probably does not do what the mapper meant.
+    Warning,
+    /// The definition fails to load, or loads with a silently substituted value.
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiagnosticKind {
+    /// Structural problem that also makes [`try_parse`](super::PointDefinitionLike::try_parse) fail
+    /// for any value type.
+    Parse(ParseErrorKind),
+    /// A point's time is lower than the time of the point before it.
+    TimeNotAscending { previous: f32, time: f32 },
+    /// A point's time is outside of `[0, 1]`.
+    TimeOutOfRange(f32),
+    /// A flag that is neither an easing, a spline nor an operation.
+    UnknownFlag(String),
+    /// A flag the parser ignores where it is placed: an operation or `splineControl` on a point,
+    /// or any flag after the operation of a modifier.
+    IgnoredFlag(String),
+    /// An `ease*` flag that is not a known easing; the parser uses `easeLinear` instead.
+    UnknownEasing(String),
+    /// A swizzle character other than `x`, `y`, `z` or `w` in a base provider.
+    InvalidSwizzle { base: String, swizzle: char },
+    /// An empty part in a base provider, e.g. the trailing `.` of `baseHeadPosition.`. The parser
+    /// reads it as a swizzle without components.
+    EmptySwizzle { base: String },
+    /// A swizzle with more than 4 characters, giving more components than any value type has.
+    LongSwizzle { base: String, swizzle: String },
+    /// A `.sN`, `.eN` or `.dN` smoothing spec whose multiplier is not a finite, non-negative number.
+    InvalidSmoothing { base: String, spec: String },
+    /// A generator parameter like `fN` or `seedN` whose number doesn't parse.
+    InvalidGeneratorParameter { base: String, param: String },
+}
+
+impl DiagnosticKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::TimeOutOfRange(_)
+            | DiagnosticKind::UnknownFlag(_)
+            | DiagnosticKind::IgnoredFlag(_)
+            | DiagnosticKind::LongSwizzle { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::Parse(kind) => write!(f, "{kind}"),
+            DiagnosticKind::TimeNotAscending { previous, time } => {
+                write!(f, "time {time} comes after time {previous}")
+            }
+            DiagnosticKind::TimeOutOfRange(time) => write!(f, "time {time} is outside of [0, 1]"),
+            DiagnosticKind::UnknownFlag(flag) => write!(f, "unknown flag \"{flag}\""),
+            DiagnosticKind::IgnoredFlag(flag) => write!(f, "flag \"{flag}\" is ignored here"),
+            DiagnosticKind::UnknownEasing(easing) => write!(f, "unknown easing \"{easing}\""),
+            DiagnosticKind::InvalidSwizzle { base, swizzle } => {
+                write!(f, "invalid swizzle character '{swizzle}' in \"{base}\"")
+            }
+            DiagnosticKind::EmptySwizzle { base } => write!(f, "empty swizzle in \"{base}\""),
+            DiagnosticKind::LongSwizzle { base, swizzle } => write!(
+                f,
+                "swizzle \"{swizzle}\" in \"{base}\" has more than 4 components"
+            ),
+            DiagnosticKind::InvalidSmoothing { base, spec } => {
+                write!(f, "invalid smoothing spec \"{spec}\" in \"{base}\"")
+            }
+            DiagnosticKind::InvalidGeneratorParameter { base, param } => {
+                write!(f, "invalid generator parameter \"{param}\" in \"{base}\"")
+            }
+        }
+    }
+}
+
+/// A single problem found by [`validate`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub path: JsonPath,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, path: JsonPath) -> Self {
+        Self { kind, path }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity() == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity() {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        if self.path.is_root() {
+            return write!(f, "{severity}: {}", self.kind);
+        }
+        write!(f, "{severity}: {} at {}", self.kind, self.path)
+    }
+}
+
+/// Checks a raw point definition and returns every problem found, in document order.
+///
+/// Accepts the same shapes as [`parse`](super::PointDefinitionLike::parse), and paths follow the
+/// same convention as [`ParseError`](super::parse_error::ParseError). An empty result means
+/// nothing was found; see the module docs for what can't be checked without a value type.
+pub fn validate(value: &JsonValue) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    let Some(array) = value.as_array() else {
+        diagnostics.push(parse_diagnostic(
+            ParseErrorKind::NotAnArray,
+            JsonPath::new(),
+        ));
+        return diagnostics;
+    };
+    let Some(first) = array.first() else {
+        diagnostics.push(parse_diagnostic(
+            ParseErrorKind::EmptyArray,
+            JsonPath::new(),
+        ));
+        return diagnostics;
+    };
+
+    // Single-point shorthand; time is implicitly 0 so there is nothing to order
+    if !first.is_array() {
+        validate_elements(array, &JsonPath::new(), Nesting::Point, &mut diagnostics);
+        return diagnostics;
+    }
+
+    let mut previous_time: Option<f32> = None;
+    for (index, point) in array.iter().enumerate() {
+        let path = JsonPath::new().child(index);
+        let Some(elements) = point.as_array() else {
+            if !point.is_null() {
+                diagnostics.push(parse_diagnostic(ParseErrorKind::NotAnArray, path));
+            }
+            continue;
+        };
+
+        validate_elements(elements, &path, Nesting::Point, &mut diagnostics);
+
+        // The time is the last value of the point; it is only known up front when it is a number
+        let Some((time_index, time)) = elements
+            .iter()
+            .enumerate()
+            .rfind(|(_, v)| is_value(v))
+            .and_then(|(i, v)| v.as_f64().map(|t| (i, t as f32)))
+        else {
+            continue;
+        };
+
+        let time_path = path.child(time_index);
+        if !(0.0..=1.0).contains(&time) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::TimeOutOfRange(time),
+                time_path.clone(),
+            ));
+        }
+        if let Some(previous) = previous_time
+            && time < previous
+        {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::TimeNotAscending { previous, time },
+                time_path,
+            ));
+        }
+        previous_time = Some(time);
+    }
+
+    diagnostics
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Nesting {
+    Point,
+    Modifier,
+    Control,
+}
+
+// Checks the elements of a point, modifier or control point, recursing into nested modifiers.
+fn validate_elements(
+    elements: &[JsonValue],
+    path: &JsonPath,
+    nesting: Nesting,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let is_modifier = nesting != Nesting::Point;
+    let mut has_values = false;
+    let mut flags = vec![];
+    let mut controls = 0;
+    // Malformed control points are already reported, counting them would only add noise
+    let mut has_invalid_control = false;
+
+    for (index, element) in elements.iter().enumerate() {
+        let element_path = path.child(index);
+        match element {
+            JsonValue::String(s) if s.starts_with("base") => {
+                has_values = true;
+                validate_base(s, &element_path, diagnostics);
+            }
+            JsonValue::String(flag) => {
+                // Mirrors the parser: a modifier reads its first flag as the operation and ignores
+                // the rest, a point ignores operations and `splineControl`
+                let kind = match nesting {
+                    Nesting::Point if flag.starts_with("op") || flag == CONTROL_FLAG => {
+                        Some(DiagnosticKind::IgnoredFlag(flag.to_string()))
+                    }
+                    Nesting::Point => check_flag(flag),
+                    _ if !flags.is_empty() => Some(DiagnosticKind::IgnoredFlag(flag.to_string())),
+                    Nesting::Control => None,
+                    Nesting::Modifier => Operation::from_str(flag).err().map(|_| {
+                        DiagnosticKind::Parse(ParseErrorKind::UnknownOperation(flag.to_string()))
+                    }),
+                };
+                if let Some(kind) = kind {
+                    diagnostics.push(Diagnostic::new(kind, element_path));
+                }
+                flags.push(flag.as_str());
+            }
+            JsonValue::Array(modifier) => {
+                if modifier.is_empty() {
+                    diagnostics.push(parse_diagnostic(ParseErrorKind::EmptyArray, element_path));
+                    continue;
+                }
+                let nesting = match !is_modifier && is_control(modifier) {
+                    true => {
+                        controls += 1;
+                        Nesting::Control
+                    }
+                    false => Nesting::Modifier,
+                };
+                let reported = diagnostics.len();
+                validate_elements(modifier, &element_path, nesting, diagnostics);
+                if nesting == Nesting::Control {
+                    has_invalid_control |= diagnostics[reported..].iter().any(Diagnostic::is_error);
+                }
+            }
+            _ => has_values = true,
+        }
+    }
+
+    if is_modifier && !has_values {
+        diagnostics.push(parse_diagnostic(
+            ParseErrorKind::MissingValues,
+            path.clone(),
+        ));
+    }
+    if is_modifier && flags.is_empty() {
+        diagnostics.push(parse_diagnostic(
+            ParseErrorKind::MissingOperation,
+            path.clone(),
+        ));
+    }
+    if !is_modifier
+        && !has_invalid_control
+        && let Err(kind) = SplineKind::from_flags(&flags).check_controls(controls)
+    {
+        diagnostics.push(parse_diagnostic(kind, path.clone()));
+    }
+}
+
+// Mirrors the point definition parser: arrays whose first flag is `splineControl` are control
+// points rather than modifiers.
+fn is_control(elements: &[JsonValue]) -> bool {
+    elements
+        .iter()
+        .find_map(|e| e.as_str().filter(|s| !s.starts_with("base")))
+        .is_some_and(|flag| flag == CONTROL_FLAG)
+}
+
+// Checks the generator parameters, swizzle, velocity and smoothing parts of a base provider string
+// like `baseHeadPosition.zx.s0_5`.
+fn validate_base(base: &str, path: &JsonPath, diagnostics: &mut Vec<Diagnostic>) {
+    let mut parts = base.split('.').peekable();
+    let is_generator = parts.next().and_then(GeneratorKind::from_name).is_some();
+    // Generator parameters come straight after the name, e.g. `baseSine.f2.p0_5`
+    while let Some(param) =
+        parts.next_if(|part| is_generator && GeneratorParam::parse(part).is_some())
+    {
+        if GeneratorParam::parse(param) == Some(GeneratorParam::Invalid) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::InvalidGeneratorParameter {
+                    base: base.to_string(),
+                    param: param.to_string(),
+                },
+                path.clone(),
+            ));
+        }
+    }
+
+    for part in parts {
+        if part == "v" || part == "av" {
+            continue;
+        }
+        if SmoothKind::from_spec(part).is_some() {
+            if smooth_multiplier(part).is_err() {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::InvalidSmoothing {
+                        base: base.to_string(),
+                        spec: part.to_string(),
+                    },
+                    path.clone(),
+                ));
+            }
+            continue;
+        }
+
+        if part.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::EmptySwizzle {
+                    base: base.to_string(),
+                },
+                path.clone(),
+            ));
+            continue;
+        }
+        if part.chars().count() > 4 {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::LongSwizzle {
+                    base: base.to_string(),
+                    swizzle: part.to_string(),
+                },
+                path.clone(),
+            ));
+        }
+        diagnostics.extend(
+            part.chars()
+                .filter(|c| swizzle_index(*c).is_none())
+                .map(|swizzle| {
+                    Diagnostic::new(
+                        DiagnosticKind::InvalidSwizzle {
+                            base: base.to_string(),
+                            swizzle,
+                        },
+                        path.clone(),
+                    )
+                }),
+        );
+    }
+}
+
+// Checks a flag on a point, where operations are ignored
+fn check_flag(flag: &str) -> Option<DiagnosticKind> {
+    if flag.to_ascii_lowercase().starts_with("ease") {
+        return Functions::from_str(flag)
+            .err()
+            .map(|_| DiagnosticKind::UnknownEasing(flag.to_string()));
+    }
+    if SplineKind::from_str(flag).is_ok() {
+        return None;
+    }
+    Some(DiagnosticKind::UnknownFlag(flag.to_string()))
+}
+
+// Mirrors the point definition parser: numbers and base providers are values, other strings are flags.
+fn is_value(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::String(s) => s.starts_with("base"),
+        JsonValue::Array(_) => false,
+        _ => true,
+    }
+}
+
+fn parse_diagnostic(kind: ParseErrorKind, path: JsonPath) -> Diagnostic {
+    Diagnostic::new(DiagnosticKind::Parse(kind), path)
+}
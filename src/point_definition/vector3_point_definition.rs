@@ -1,6 +1,4 @@
-use std::rc::Rc;
-
-use glam::{Vec3, Vec3A};
+use glam::Vec3;
 use smallvec::SmallVec;
 
 use crate::{
@@ -10,61 +8,19 @@ use crate::{
     modifiers::{ModifierValues, basic_modifier::BasicModifier, operation::Operation},
     point_data::{PointDataLike, basic_point_data::BasicPointData},
     prelude::{AbstractValueProvider, ValueProvider},
+    shared::Shared,
     value_types::ValueType,
 };
 
-use super::PointDefinitionLike;
+use super::{
+    PointDefinitionLike,
+    parse_error::ParseErrorKind,
+    spline::{self, SplineKind},
+};
 
 #[derive(Default, Debug, Clone)]
 pub struct Vector3PointDefinition {
-    points: Rc<[BasicPointData<Vec3>]>,
-}
-
-impl Vector3PointDefinition {
-    fn smooth_vector_lerp(
-        &self,
-
-        l_0: Vec3A,
-        l_sub_1: Option<Vec3A>,
-        r_0: Vec3A,
-        r_add_1: Option<Vec3A>,
-
-        time: f32,
-        _context: &BaseProviderContext,
-    ) -> Vec3 {
-        // Convert to Vec3A for SIMD-friendly spline interpolation, convert back at the end
-        // let point_a_a = glam::Vec3A::from(points[l].get_point(context));
-        // let point_b_a = glam::Vec3A::from(points[r].get_point(context));
-
-        // Catmull-Rom Spline
-        // let p0_a = if l > 0 {
-        //     glam::Vec3A::from(points[l - 1].get_point(context))
-        // } else {
-        //     point_a_a
-        // };
-        // let p3_a = if r + 1 < len {
-        // // let p3_a = if r + 1 < points.len() {
-        //     glam::Vec3A::from(points[r + 1].get_point(context))
-        // } else {
-        //     point_b_a
-        // };
-
-        let point_a_a = l_0;
-        let point_b_a = r_0;
-        let p0_a = l_sub_1.unwrap_or(l_0);
-        let p3_a = r_add_1.unwrap_or(r_0);
-
-        let tt = time * time;
-        let ttt = tt * time;
-
-        let q0 = -ttt + (2.0 * tt) - time;
-        let q1 = (3.0 * ttt) - (5.0 * tt) + 2.0;
-        let q2 = (-3.0 * ttt) + (4.0 * tt) + time;
-        let q3 = ttt - tt;
-
-        let res_a = 0.5 * ((p0_a * q0) + (point_a_a * q1) + (point_b_a * q2) + (p3_a * q3));
-        Vec3::from(res_a)
-    }
+    points: Shared<[BasicPointData<Vec3>]>,
 }
 
 impl PointDefinitionLike<Vec3> for Vector3PointDefinition {
@@ -81,7 +37,7 @@ impl PointDefinitionLike<Vec3> for Vector3PointDefinition {
 
     fn new(points: Vec<Self::PointData>) -> Self {
         Self {
-            points: Rc::from(points),
+            points: Shared::from(points),
         }
     }
 
@@ -90,7 +46,19 @@ impl PointDefinitionLike<Vec3> for Vector3PointDefinition {
         modifiers: Vec<BasicModifier<Vec3>>,
         operation: Operation,
         context: &BaseProviderContext,
-    ) -> BasicModifier<Vec3> {
+    ) -> Result<BasicModifier<Vec3>, ParseErrorKind> {
+        if operation.is_rotation() {
+            return Err(ParseErrorKind::RotationOnlyOperation(
+                operation.as_str().to_string(),
+            ));
+        }
+        #[cfg(feature = "extended_ops")]
+        if operation == Operation::Clamp {
+            let (lower, upper) = super::split_clamp_bounds(values, Vec3::VALUE_COUNT, context)?;
+            let lower = Self::create_modifier(lower, modifiers.clone(), Operation::None, context)?;
+            let upper = Self::create_modifier(upper, modifiers, Operation::None, context)?;
+            return Ok(BasicModifier::clamp(lower, upper));
+        }
         let val: ModifierValues<Vec3> = match values.as_slice() {
             // Single static value [x, y, z]
             [ValueProvider::Static(static_val)] if static_val.values.len() == Vec3::VALUE_COUNT => {
@@ -100,25 +68,26 @@ impl PointDefinitionLike<Vec3> for Vector3PointDefinition {
             // Any other case is treated as dynamic and translated/padded at evaluation time.
             _ => {
                 let count: usize = values.iter().map(|v| v.values(context).len()).sum();
-                assert_eq!(
-                    count,
-                    Vec3::VALUE_COUNT,
-                    "modifier point must have {} numbers",
-                    Vec3::VALUE_COUNT
-                );
+                if count != Vec3::VALUE_COUNT {
+                    return Err(ParseErrorKind::WrongComponentCount {
+                        expected: Vec3::VALUE_COUNT,
+                        found: count,
+                    });
+                }
                 ModifierValues::Dynamic(values)
             }
         };
-        Self::Modifier::new(val, modifiers, operation)
+        Ok(Self::Modifier::new(val, modifiers, operation))
     }
 
     fn create_point_data(
         values: SmallVec<[ValueProvider; 1]>,
         flags: Vec<String>,
         modifiers: Vec<Self::Modifier>,
+        controls: Vec<Self::Modifier>,
         easing: Functions,
         context: &BaseProviderContext,
-    ) -> Self::PointData {
+    ) -> Result<Self::PointData, ParseErrorKind> {
         // If one value is present and it contains two floats, the first is the point value and the second is time.
 
         let (value, time) = match &values[..] {
@@ -135,18 +104,22 @@ impl PointDefinitionLike<Vec3> for Vector3PointDefinition {
 
             _ => {
                 // https://github.com/Aeroluna/Heck/blob/1dc9f470a7f8d3e64d0e3bc34e2f2279190eb8b8/Heck/Animation/PointDefinition/Vector3PointDefinition.cs#L80-L81
-                let time = values.last()
+                let time = values
+                    .last()
                     .and_then(|vp| vp.values(context).last().copied())
-                    .unwrap_or_else(|| panic!("Expected at least one value provider with at least {} values for point data, with the last one being time",
-                        Vec3::VALUE_COUNT + 1));
+                    .ok_or(ParseErrorKind::MissingTime)?;
 
                 (ModifierValues::Dynamic(values), time)
             }
         };
 
-        let smooth = flags.iter().any(|f| f == "splineCatmullRom");
+        let spline = SplineKind::from_flags(&flags);
+        spline.check_controls(controls.len())?;
 
-        BasicPointData::new(value, time, smooth, modifiers, easing)
+        Ok(
+            BasicPointData::with_spline(value, time, spline, modifiers, easing)
+                .with_controls(controls),
+        )
     }
 
     fn get_points(&self) -> &[Self::PointData] {
@@ -159,40 +132,13 @@ impl PointDefinitionLike<Vec3> for Vector3PointDefinition {
 
     fn interpolate_points(
         &self,
-        l: &Self::PointData,
-        r: &Self::PointData,
+        _l: &Self::PointData,
+        _r: &Self::PointData,
         l_index: usize,
         r_index: usize,
         time: f32,
         context: &BaseProviderContext,
     ) -> Vec3 {
-        if r.smooth {
-            let point_a_a = PointDataLike::get_point(l, context);
-            let point_b_a = PointDataLike::get_point(r, context);
-            let l_sub_1 = self
-                .points
-                .get(l_index - 1)
-                .map(|p| p.get_point(context))
-                .map(Vec3A::from);
-            let r_add_1 = self
-                .points
-                .get(r_index + 1)
-                .map(|p| p.get_point(context))
-                .map(Vec3A::from);
-
-            return self.smooth_vector_lerp(
-                point_a_a.into(),
-                l_sub_1,
-                point_b_a.into(),
-                r_add_1,
-                time,
-                context,
-            );
-        }
-
-        let point_l = PointDataLike::get_point(l, context);
-        let point_r = PointDataLike::get_point(r, context);
-
-        Vec3::value_lerp_clamped(point_l, point_r, time)
+        spline::interpolate_points(&self.points, l_index, r_index, time, context)
     }
 }
@@ -1,8 +1,10 @@
 use smallvec::smallvec;
 
 use crate::{
-    base_provider_context::BaseProviderContext, base_value::BaseValue,
-    providers::ValueProviderValues, quaternion_utils::QuaternionUtilsExt,
+    base_provider_context::{BaseProviderContext, BaseSlot},
+    base_value::BaseValue,
+    providers::ValueProviderValues,
+    quaternion_utils::QuaternionUtilsExt,
 };
 
 use super::AbstractValueProvider;
@@ -10,18 +12,19 @@ use super::AbstractValueProvider;
 #[derive(Clone, Debug)]
 pub struct BaseProviderValues {
     pub(crate) base: String,
+    /// Resolved from `base` when the provider is created, so sampling doesn't look up the name.
+    pub(crate) slot: BaseSlot,
 }
 
 impl BaseProviderValues {
-    pub fn new(base: String) -> Self {
-        Self { base }
+    pub fn new(base: String, slot: BaseSlot) -> Self {
+        Self { base, slot }
     }
 }
 
 impl AbstractValueProvider for BaseProviderValues {
     fn values(&self, context: &BaseProviderContext) -> ValueProviderValues {
-        let value = context.get_values(&self.base);
-        match value {
+        match *context.get_slot(self.slot) {
             BaseValue::Float(f) => smallvec![f],
             BaseValue::Vector3(v) => smallvec![v.x, v.y, v.z],
             BaseValue::Vector4(v) => smallvec![v.x, v.y, v.z, v.w],
@@ -36,6 +39,6 @@ impl AbstractValueProvider for BaseProviderValues {
     fn is_rotation(&self, context: &BaseProviderContext) -> bool {
         // This is a bit hacky, but it allows the system to know that this provider is providing rotation values without needing an explicit type for it
         // It relies on the convention that quaternions are returned as euler angles in degrees, so if the source value is a quaternion, we consider this a rotation provider
-        matches!(context.get_values(&self.base), BaseValue::Quaternion(_))
+        matches!(context.get_slot(self.slot), BaseValue::Quaternion(_))
     }
 }
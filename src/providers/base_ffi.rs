@@ -8,6 +8,7 @@ use crate::{
 };
 
 use super::AbstractValueProvider;
+use super::custom::CustomProvider;
 
 pub type BaseFFIProvider = unsafe extern "C" fn(&BaseProviderContext, *mut c_void) -> WrappedValues;
 
@@ -24,15 +25,59 @@ impl BaseFFIProviderValues {
 
 impl AbstractValueProvider for BaseFFIProviderValues {
     fn values(&self, context: &BaseProviderContext) -> ValueProviderValues {
-        let c_values: WrappedValues = unsafe { (*self.fetch)(context, self.user_data) };
-        // move to owned values
-        let arr = unsafe { slice::from_raw_parts(c_values.values, c_values.length) };
-        SmallVec::from_slice(arr)
+        unsafe { fetch_values(*self.fetch, context, self.user_data) }
     }
-    
+
     fn is_rotation(&self, _context: &BaseProviderContext) -> bool {
-        // FFI providers are assumed to be non-rotational by default, but this can be overridden by the provider implementation if needed.
-        // TODO:
+        // FFI providers are assumed to be non-rotational, register a rotation base with
+        // `base_provider_context_register_ffi_provider` instead
         false
     }
 }
+
+impl CustomProvider for BaseFFIProviderValues {
+    fn values(&self, context: &BaseProviderContext) -> ValueProviderValues {
+        unsafe { fetch_values(*self.fetch, context, self.user_data) }
+    }
+}
+
+/// A host callback registered with `base_provider_context_register_ffi_provider`.
+pub(crate) struct FFICallbackProvider {
+    pub(crate) fetch: BaseFFIProvider,
+    pub(crate) user_data: *mut c_void,
+}
+
+impl CustomProvider for FFICallbackProvider {
+    fn values(&self, context: &BaseProviderContext) -> ValueProviderValues {
+        unsafe { fetch_values(self.fetch, context, self.user_data) }
+    }
+}
+
+// SAFETY: the host promises callbacks and their user data can be used from any thread that
+// samples the context when it builds with the `sync` feature
+#[cfg(feature = "sync")]
+unsafe impl Send for BaseFFIProviderValues {}
+#[cfg(feature = "sync")]
+unsafe impl Sync for BaseFFIProviderValues {}
+#[cfg(feature = "sync")]
+unsafe impl Send for FFICallbackProvider {}
+#[cfg(feature = "sync")]
+unsafe impl Sync for FFICallbackProvider {}
+
+/// Calls `fetch` and copies the values it returns.
+///
+/// # Safety
+/// - `fetch` must return a pointer to `length` readable floats, or a null pointer with length 0.
+pub(crate) unsafe fn fetch_values(
+    fetch: BaseFFIProvider,
+    context: &BaseProviderContext,
+    user_data: *mut c_void,
+) -> ValueProviderValues {
+    let c_values: WrappedValues = unsafe { fetch(context, user_data) };
+    if c_values.values.is_null() {
+        return SmallVec::new();
+    }
+    // move to owned values
+    let arr = unsafe { slice::from_raw_parts(c_values.values, c_values.length) };
+    SmallVec::from_slice(arr)
+}
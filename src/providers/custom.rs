@@ -0,0 +1,106 @@
+use std::fmt;
+
+use glam::{Quat, Vec3};
+
+use crate::{
+    base_provider_context::BaseProviderContext,
+    base_value::{BaseValue, WrapBaseValueType},
+    providers::ValueProviderValues,
+    quaternion_utils::QuaternionUtilsExt,
+    shared::{MaybeSendSync, Shared},
+};
+
+use super::AbstractValueProvider;
+
+/// Values computed by the host each time a custom provider is sampled.
+///
+/// Implemented for closures taking the context. With the `sync` feature they must be `Send + Sync`.
+pub trait CustomProvider: MaybeSendSync {
+    fn values(&self, context: &BaseProviderContext) -> ValueProviderValues;
+}
+
+impl<F> CustomProvider for F
+where
+    F: Fn(&BaseProviderContext) -> ValueProviderValues + MaybeSendSync,
+{
+    fn values(&self, context: &BaseProviderContext) -> ValueProviderValues {
+        self(context)
+    }
+}
+
+/// A base registered with [`BaseProviderContext::register_custom_provider`] whose values come from
+/// the host instead of being stored in the context.
+///
+/// Rotations are returned as Euler angles in degrees, like quaternion bases.
+#[derive(Clone)]
+pub struct CustomProviderValues {
+    /// Base name this provider is registered under, e.g. `baseMyThing`
+    pub(crate) key: String,
+    component_count: usize,
+    is_rotation: bool,
+    provider: Shared<dyn CustomProvider>,
+}
+
+impl CustomProviderValues {
+    pub fn new(
+        key: String,
+        component_count: usize,
+        is_rotation: bool,
+        provider: impl CustomProvider + 'static,
+    ) -> Self {
+        Self {
+            key,
+            component_count,
+            is_rotation,
+            provider: Shared::new(provider),
+        }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.component_count
+    }
+
+    /// The type the values are read as by [`BaseProviderContext::get_values`].
+    pub fn value_type(&self) -> WrapBaseValueType {
+        match (self.component_count, self.is_rotation) {
+            (1, false) => WrapBaseValueType::Float,
+            (3, false) => WrapBaseValueType::Vec3,
+            (3, true) => WrapBaseValueType::Quat,
+            (4, false) => WrapBaseValueType::Vec4,
+            _ => WrapBaseValueType::Unknown,
+        }
+    }
+
+    pub(crate) fn base_value(&self, context: &BaseProviderContext) -> BaseValue {
+        let values = self.values(context);
+        match self.is_rotation {
+            true => BaseValue::Quaternion(Quat::from_unity_euler_degrees(Vec3::new(
+                values[0], values[1], values[2],
+            ))),
+            false => BaseValue::from_slice(&values, false),
+        }
+    }
+}
+
+impl AbstractValueProvider for CustomProviderValues {
+    fn values(&self, context: &BaseProviderContext) -> ValueProviderValues {
+        // Keep the declared size so swizzles and smoothing can rely on it
+        let mut values = self.provider.values(context);
+        values.resize(self.component_count, 0.0);
+        values
+    }
+
+    fn is_rotation(&self, _context: &BaseProviderContext) -> bool {
+        self.is_rotation
+    }
+}
+
+impl fmt::Debug for CustomProviderValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomProviderValues")
+            .field("key", &self.key)
+            .field("component_count", &self.component_count)
+            .field("is_rotation", &self.is_rotation)
+            .finish_non_exhaustive()
+    }
+}
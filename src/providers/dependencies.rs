@@ -0,0 +1,46 @@
+use std::collections::BTreeSet;
+
+use super::ValueProvider;
+
+/// What sampling a provider, modifier or point definition reads from the context, found without
+/// evaluating it.
+///
+/// Hosts can use this to only update the bases a map actually uses, and editors to show what a
+/// definition depends on. Custom providers are listed by their own name, the bases their callback
+/// reads can't be seen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BaseDependencies {
+    /// Bases read directly or through swizzles, smoothing and velocity, e.g. `baseHeadPosition`.
+    /// Generators add `baseSongTime`.
+    pub bases: BTreeSet<String>,
+    /// Smoothing and velocity providers, by the string they were parsed from, e.g.
+    /// `baseHeadPosition.s2`. Their values only change in `update_providers`.
+    pub stateful: BTreeSet<String>,
+}
+
+impl BaseDependencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bases.is_empty() && self.stateful.is_empty()
+    }
+
+    pub fn contains_base(&self, base: &str) -> bool {
+        self.bases.contains(base)
+    }
+
+    /// Adds everything `other` depends on, e.g. to collect the dependencies of a whole map.
+    pub fn merge(&mut self, other: &BaseDependencies) {
+        self.bases.extend(other.bases.iter().cloned());
+        self.stateful.extend(other.stateful.iter().cloned());
+    }
+
+    /// Adds the dependencies of each provider in `providers`.
+    pub fn add_providers<'a>(&mut self, providers: impl IntoIterator<Item = &'a ValueProvider>) {
+        for provider in providers {
+            provider.collect_dependencies(self);
+        }
+    }
+}
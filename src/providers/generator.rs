@@ -0,0 +1,174 @@
+use std::f32::consts::TAU;
+
+use smallvec::smallvec;
+
+use crate::{
+    base_provider_context::{BaseProviderContext, BaseSlot},
+    base_value::BaseValue,
+    providers::ValueProviderValues,
+};
+
+use super::AbstractValueProvider;
+
+/// Built-in providers computed from `baseSongTime` instead of host input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorKind {
+    /// `baseSine`: sine wave between -1 and 1.
+    Sine,
+    /// `baseSquare`: 1 for the first half of each cycle and -1 for the second.
+    Square,
+    /// `baseTriangle`: triangle wave between -1 and 1, in phase with `baseSine`.
+    Triangle,
+    /// `baseNoise`: smooth 1D Perlin noise, roughly between -1 and 1.
+    Noise,
+    /// `baseNoise3`: three independent `baseNoise` channels, e.g. for shaking a position.
+    Noise3,
+    /// `baseRandom`: random value between 0 and 1, picking a new one every cycle.
+    Random,
+    /// `baseRandom3`: three independent `baseRandom` channels.
+    Random3,
+}
+
+impl GeneratorKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "baseSine" => Some(Self::Sine),
+            "baseSquare" => Some(Self::Square),
+            "baseTriangle" => Some(Self::Triangle),
+            "baseNoise" => Some(Self::Noise),
+            "baseNoise3" => Some(Self::Noise3),
+            "baseRandom" => Some(Self::Random),
+            "baseRandom3" => Some(Self::Random3),
+            _ => None,
+        }
+    }
+}
+
+/// A parameter following a generator name, e.g. the `f2` in `baseSine.f2.x`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum GeneratorParam {
+    /// `fN`: cycles per second of song time.
+    Frequency(f32),
+    /// `pN`: offset in cycles.
+    Phase(f32),
+    /// `seedN`: picks a different noise or random sequence.
+    Seed(u32),
+    /// Looks like a parameter, but its number doesn't parse.
+    Invalid,
+}
+
+impl GeneratorParam {
+    /// Parses `part`, or returns `None` if it isn't a generator parameter. Numbers use `_` for the
+    /// decimal point like smoothing specs, e.g. `p0_25`.
+    pub(crate) fn parse(part: &str) -> Option<Self> {
+        let number = |s: &str| s.replace('_', ".").parse::<f32>().ok();
+        let param = if let Some(seed) = part.strip_prefix("seed") {
+            seed.parse().ok().map(GeneratorParam::Seed)
+        } else if let Some(frequency) = part.strip_prefix('f') {
+            number(frequency).map(GeneratorParam::Frequency)
+        } else {
+            number(part.strip_prefix('p')?).map(GeneratorParam::Phase)
+        };
+        Some(param.unwrap_or(GeneratorParam::Invalid))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GeneratorProviderValues {
+    pub(crate) kind: GeneratorKind,
+    pub(crate) frequency: f32,
+    pub(crate) phase: f32,
+    pub(crate) seed: u32,
+    song_time: BaseSlot,
+    /// Generator name and parameters this was parsed from, e.g. `baseSine.f2`
+    pub(crate) key: String,
+}
+
+impl GeneratorProviderValues {
+    pub fn new(kind: GeneratorKind, key: String, song_time: BaseSlot) -> Self {
+        Self {
+            kind,
+            frequency: 1.0,
+            phase: 0.0,
+            seed: 0,
+            song_time,
+            key,
+        }
+    }
+
+    pub(crate) fn apply(&mut self, param: GeneratorParam) {
+        match param {
+            GeneratorParam::Frequency(frequency) => self.frequency = frequency,
+            GeneratorParam::Phase(phase) => self.phase = phase,
+            GeneratorParam::Seed(seed) => self.seed = seed,
+            GeneratorParam::Invalid => {}
+        }
+    }
+}
+
+impl AbstractValueProvider for GeneratorProviderValues {
+    fn values(&self, context: &BaseProviderContext) -> ValueProviderValues {
+        let time = match context.get_slot(self.song_time) {
+            BaseValue::Float(time) => *time,
+            _ => 0.0,
+        };
+        let x = time * self.frequency + self.phase;
+
+        match self.kind {
+            GeneratorKind::Sine => smallvec![(x * TAU).sin()],
+            GeneratorKind::Square => smallvec![if x.rem_euclid(1.0) < 0.5 { 1.0 } else { -1.0 }],
+            GeneratorKind::Triangle => {
+                smallvec![4.0 * ((x - 0.25).rem_euclid(1.0) - 0.5).abs() - 1.0]
+            }
+            GeneratorKind::Noise => smallvec![noise(x, self.seed)],
+            GeneratorKind::Noise3 => (0..3)
+                .map(|i| noise(x, channel_seed(self.seed, i)))
+                .collect(),
+            GeneratorKind::Random => smallvec![random(x, self.seed)],
+            GeneratorKind::Random3 => (0..3)
+                .map(|i| random(x, channel_seed(self.seed, i)))
+                .collect(),
+        }
+    }
+
+    fn is_rotation(&self, _context: &BaseProviderContext) -> bool {
+        false
+    }
+}
+
+fn channel_seed(seed: u32, channel: u32) -> u32 {
+    seed.wrapping_add(channel.wrapping_mul(0x9E37_79B9))
+}
+
+/// Integer hash, so the same seed gives the same values on every platform.
+fn hash(cell: i32, seed: u32) -> u32 {
+    let mut h = (cell as u32).wrapping_mul(0x9E37_79B1) ^ seed.wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297A_2D39);
+    h ^= h >> 15;
+    h
+}
+
+/// Value in `[0, 1)` for `cell`.
+fn unit(cell: i32, seed: u32) -> f32 {
+    (hash(cell, seed) >> 8) as f32 / (1 << 24) as f32
+}
+
+fn random(x: f32, seed: u32) -> f32 {
+    unit(x.floor() as i32, seed)
+}
+
+fn noise(x: f32, seed: u32) -> f32 {
+    let cell = x.floor();
+    let t = x - cell;
+    let cell = cell as i32;
+    let gradient = |cell: i32| unit(cell, seed) * 2.0 - 1.0;
+
+    let left = gradient(cell) * t;
+    let right = gradient(cell.wrapping_add(1)) * (t - 1.0);
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    // 1D gradient noise peaks at 0.5
+    (left + (right - left) * fade) * 2.0
+}
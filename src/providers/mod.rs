@@ -1,13 +1,17 @@
 use crate::base_provider_context::BaseProviderContext;
-use std::{cell::RefCell, rc::Rc};
+use crate::shared::{MaybeSendSync, Shared, StateCell};
 
 pub mod base;
 #[cfg(feature = "ffi")]
 pub mod base_ffi;
+pub mod custom;
+pub mod dependencies;
+pub mod generator;
 pub mod partial;
 pub mod smooth;
 pub mod smooth_rot;
 pub mod r#static;
+pub mod velocity;
 
 #[cfg(feature = "json")]
 use serde_json::Value as JsonValue;
@@ -41,7 +45,7 @@ pub trait AbstractValueProvider {
 /// from the source values
 ///
 /// Delta is time based
-pub trait UpdateableValues: AbstractValueProvider {
+pub trait UpdateableValues: AbstractValueProvider + MaybeSendSync {
     /// Update the values from the source
     /// delta is the amount to progress from the source to target
     fn update(&mut self, delta: f32, context: &BaseProviderContext);
@@ -54,8 +58,14 @@ pub enum ValueProvider {
     Static(r#static::StaticValues),
     BaseProvider(base::BaseProviderValues),
     PartialProvider(partial::PartialProviderValues),
-    SmoothProviders(Rc<RefCell<smooth::SmoothProvidersValues>>),
-    SmoothRotationProviders(Rc<RefCell<smooth_rot::SmoothRotationProvidersValues>>),
+    SmoothProviders(Shared<StateCell<smooth::SmoothProvidersValues>>),
+    SmoothRotationProviders(Shared<StateCell<smooth_rot::SmoothRotationProvidersValues>>),
+    /// Values computed by the host, see [`BaseProviderContext::register_custom_provider`].
+    Custom(custom::CustomProviderValues),
+    /// Built-in oscillators, noise and random values, e.g. `baseSine`.
+    Generator(generator::GeneratorProviderValues),
+    /// Rate of change of another provider, from the `v` and `av` specs.
+    Velocity(Shared<StateCell<velocity::VelocityProviderValues>>),
 }
 
 impl AbstractValueProvider for ValueProvider {
@@ -72,6 +82,9 @@ impl AbstractValueProvider for ValueProvider {
                 let borrow = v.borrow();
                 borrow.values(context)
             }
+            ValueProvider::Custom(v) => v.values(context),
+            ValueProvider::Generator(v) => v.values(context),
+            ValueProvider::Velocity(v) => v.borrow().values(context),
         }
     }
 
@@ -82,6 +95,9 @@ impl AbstractValueProvider for ValueProvider {
             ValueProvider::PartialProvider(v) => v.is_rotation(_context),
             ValueProvider::SmoothProviders(v) => v.borrow().is_rotation(_context),
             ValueProvider::SmoothRotationProviders(v) => v.borrow().is_rotation(_context),
+            ValueProvider::Custom(v) => v.is_rotation(_context),
+            ValueProvider::Generator(_) => false,
+            ValueProvider::Velocity(_) => false,
         }
     }
 }
@@ -94,11 +110,79 @@ impl UpdateableValues for ValueProvider {
             ValueProvider::PartialProvider(_v) => {}
             ValueProvider::SmoothProviders(v) => v.borrow_mut().update(delta, context),
             ValueProvider::SmoothRotationProviders(v) => v.borrow_mut().update(delta, context),
+            ValueProvider::Custom(_) => {}
+            ValueProvider::Generator(_) => {}
+            ValueProvider::Velocity(v) => v.borrow_mut().update(delta, context),
         }
     }
 }
 
 impl ValueProvider {
+    /// The `base*` string this provider was parsed from, or `None` for static values.
+    pub fn key(&self) -> Option<String> {
+        match self {
+            ValueProvider::Static(_) => None,
+            ValueProvider::BaseProvider(v) => Some(v.base.clone()),
+            ValueProvider::PartialProvider(v) => Some(v.key.clone()),
+            ValueProvider::SmoothProviders(v) => Some(v.borrow().key.clone()),
+            ValueProvider::SmoothRotationProviders(v) => Some(v.borrow().key.clone()),
+            ValueProvider::Custom(v) => Some(v.key.clone()),
+            ValueProvider::Generator(v) => Some(v.key.clone()),
+            ValueProvider::Velocity(v) => Some(v.borrow().key.clone()),
+        }
+    }
+
+    /// Whether the provider holds smoothing or velocity state, directly or through the provider it
+    /// swizzles.
+    pub fn is_stateful(&self) -> bool {
+        match self {
+            ValueProvider::SmoothProviders(_)
+            | ValueProvider::SmoothRotationProviders(_)
+            | ValueProvider::Velocity(_) => true,
+            ValueProvider::PartialProvider(v) => v.source.is_stateful(),
+            _ => false,
+        }
+    }
+
+    /// Adds the bases and stateful providers this provider reads to `dependencies`.
+    pub fn collect_dependencies(&self, dependencies: &mut dependencies::BaseDependencies) {
+        match self {
+            ValueProvider::Static(_) => {}
+            ValueProvider::BaseProvider(v) => {
+                dependencies.bases.insert(v.base.clone());
+            }
+            ValueProvider::PartialProvider(v) => v.source.collect_dependencies(dependencies),
+            ValueProvider::SmoothProviders(v) => {
+                let v = v.borrow();
+                dependencies.stateful.insert(v.key.clone());
+                v.source_provider.collect_dependencies(dependencies);
+            }
+            ValueProvider::SmoothRotationProviders(v) => {
+                let v = v.borrow();
+                dependencies.stateful.insert(v.key.clone());
+                v.source_provider.collect_dependencies(dependencies);
+            }
+            ValueProvider::Custom(v) => {
+                dependencies.bases.insert(v.key.clone());
+            }
+            ValueProvider::Generator(_) => {
+                dependencies.bases.insert("baseSongTime".to_string());
+            }
+            ValueProvider::Velocity(v) => {
+                let v = v.borrow();
+                dependencies.stateful.insert(v.key.clone());
+                v.source_provider.collect_dependencies(dependencies);
+            }
+        }
+    }
+
+    /// The bases and stateful providers this provider reads, see [`dependencies::BaseDependencies`].
+    pub fn base_dependencies(&self) -> dependencies::BaseDependencies {
+        let mut dependencies = dependencies::BaseDependencies::new();
+        self.collect_dependencies(&mut dependencies);
+        dependencies
+    }
+
     /// Check if the provider is updateable
     pub fn is_updateable(&self) -> bool {
         matches!(
@@ -106,6 +190,7 @@ impl ValueProvider {
             ValueProvider::PartialProvider(_)
                 | ValueProvider::SmoothProviders(_)
                 | ValueProvider::SmoothRotationProviders(_)
+                | ValueProvider::Velocity(_)
         )
     }
 }
@@ -121,34 +206,36 @@ pub fn deserialize_values(
     value: &[&JsonValue],
     context: &mut BaseProviderContext,
 ) -> Vec<ValueProvider> {
-    let mut result = Vec::new();
-    let mut start = 0;
-
-    for (i, v) in value.iter().enumerate() {
-        if let JsonValue::String(s) = v {
-            close(&mut result, value.to_vec(), start, i);
-            start = i + 1;
-
-            let base = context.get_value_provider(s);
-            result.push(base);
-        }
-    }
+    use crate::point_definition::raw::{RawElement, values_from_raw};
 
-    close(&mut result, value.to_vec(), start, value.len());
-    result
+    let raw: SmallVec<[RawElement; 8]> = value.iter().map(|v| RawElement::from_value(v)).collect();
+    values_from_raw(raw.iter(), context)
+        .unwrap_or_else(|e| panic!("{e}"))
+        .map(SmallVec::into_vec)
+        .unwrap_or_default()
 }
 
+/// Inverse of [`deserialize_values`]: writes static values as numbers and every other
+/// provider as its `base*` string.
 #[cfg(feature = "json")]
-fn close(result: &mut Vec<ValueProvider>, raw_values: Vec<&JsonValue>, open: usize, end: usize) {
-    use r#static::StaticValues;
-
-    if end <= open {
-        return;
-    }
-
-    let values: SmallVec<[f32; 4]> = raw_values[open..end]
+pub fn serialize_values(values: &[ValueProvider]) -> Vec<JsonValue> {
+    values
         .iter()
-        .filter_map(|v| v.as_f64().map(|i| i as f32))
-        .collect();
-    result.push(ValueProvider::Static(StaticValues::new(values, false)));
+        .flat_map(|v| match v {
+            ValueProvider::Static(s) => s.values.iter().map(|f| json_number(*f)).collect(),
+            _ => vec![JsonValue::String(v.key().unwrap_or_default())],
+        })
+        .collect()
+}
+
+/// Converts an `f32` to a JSON number using its shortest decimal form,
+/// so `0.1` is written as `0.1` instead of `0.10000000149011612`.
+#[cfg(feature = "json")]
+pub(crate) fn json_number(value: f32) -> JsonValue {
+    value
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or(JsonValue::Null, JsonValue::Number)
 }
@@ -11,13 +11,33 @@ use super::AbstractValueProvider;
 pub struct PartialProviderValues {
     pub(crate) source: Box<ValueProvider>,
     pub(crate) parts: SmallVec<[usize; 4]>,
+    /// Full provider string this was parsed from, e.g. `baseHeadPosition.zx`
+    pub(crate) key: String,
 }
 
 impl PartialProviderValues {
+    /// The key is derived from the source's key and the swizzle, e.g. `baseHeadPosition.zx`.
     pub fn new(source: impl Into<ValueProvider>, parts: impl Into<SmallVec<[usize; 4]>>) -> Self {
+        let source = source.into();
+        let parts = parts.into();
+        let swizzle: String = parts
+            .iter()
+            .map(|&part| b"xyzw".get(part).map_or('?', |&c| c as char))
+            .collect();
+        let key = format!("{}.{swizzle}", source.key().unwrap_or_default());
+        Self::with_key(source, parts, key)
+    }
+
+    /// Like [`PartialProviderValues::new`], keeping the string the provider was parsed from.
+    pub fn with_key(
+        source: impl Into<ValueProvider>,
+        parts: impl Into<SmallVec<[usize; 4]>>,
+        key: String,
+    ) -> Self {
         Self {
             source: Box::new(source.into()),
             parts: parts.into(),
+            key,
         }
     }
 }
@@ -7,25 +7,101 @@ use crate::{base_provider_context::BaseProviderContext, providers::ValueProvider
 
 use super::AbstractValueProvider;
 
+/// How a smoothing provider follows its source, chosen by the first letter of the spec.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmoothKind {
+    /// `sN`: moves `N * delta` of the remaining distance each update, so it depends on the frame
+    /// rate.
+    Linear,
+    /// `eN`: exponential decay with rate `N`, independent of the frame rate.
+    Exponential,
+    /// `dN`: critically damped spring with angular frequency `N`. Keeps its own velocity, so it
+    /// eases in and out instead of snapping towards a moving source.
+    Spring,
+}
+
+impl SmoothKind {
+    /// The kind a spec like `s1`, `e0_5` or `d10` selects, or `None` if it isn't a smoothing spec.
+    pub fn from_spec(spec: &str) -> Option<Self> {
+        match spec.chars().next()? {
+            's' => Some(Self::Linear),
+            'e' => Some(Self::Exponential),
+            'd' => Some(Self::Spring),
+            _ => None,
+        }
+    }
+
+    /// The spec selecting this kind with `mult`, e.g. `s0_5`.
+    pub(crate) fn spec(self, mult: f32) -> String {
+        let prefix = match self {
+            Self::Linear => 's',
+            Self::Exponential => 'e',
+            Self::Spring => 'd',
+        };
+        format!("{prefix}{}", mult.to_string().replace('.', "_"))
+    }
+
+    /// Fraction of the remaining distance to cover this update. Springs use [`spring_step`].
+    pub(crate) fn factor(self, mult: f32, delta: f32) -> f32 {
+        match self {
+            Self::Linear => (mult * delta).clamp(0.0, 1.0),
+            Self::Exponential => 1.0 - (-(mult * delta).max(0.0)).exp(),
+            Self::Spring => unreachable!("springs are stepped with spring_step"),
+        }
+    }
+}
+
+/// Advances a critically damped spring towards zero by `delta` seconds.
+///
+/// Uses the exact solution, so splitting an update into smaller steps gives the same result.
+pub(crate) fn spring_step(offset: f32, velocity: f32, omega: f32, delta: f32) -> (f32, f32) {
+    let decay = (-omega * delta).exp();
+    let temp = (velocity + omega * offset) * delta;
+    ((offset + temp) * decay, (velocity - omega * temp) * decay)
+}
+
 #[derive(Clone, Debug)]
 pub struct SmoothProvidersValues {
     pub(crate) source_provider: crate::providers::ValueProvider,
     pub(crate) mult: f32,
+    pub(crate) kind: SmoothKind,
     pub(crate) values: SmallVec<[f32; 4]>,
+    pub(crate) velocities: SmallVec<[f32; 4]>,
+    /// Full provider string this was parsed from, e.g. `baseHeadPosition.s0_5`
+    pub(crate) key: String,
 }
 
 impl SmoothProvidersValues {
     // Initialize from a source provider; sample initial length from context
+    // The key is derived from the source's key, e.g. `baseHeadPosition.s0_5`
     pub fn new(
         source_provider: crate::providers::ValueProvider,
         mult: f32,
         context: &BaseProviderContext,
+    ) -> Self {
+        let key = format!(
+            "{}.{}",
+            source_provider.key().unwrap_or_default(),
+            SmoothKind::Linear.spec(mult)
+        );
+        Self::with_kind(source_provider, mult, SmoothKind::Linear, key, context)
+    }
+
+    pub fn with_kind(
+        source_provider: crate::providers::ValueProvider,
+        mult: f32,
+        kind: SmoothKind,
+        key: String,
+        context: &BaseProviderContext,
     ) -> Self {
         let src = source_provider.values(context);
         Self {
             source_provider,
             mult,
+            kind,
             values: smallvec![0.0; src.len()],
+            velocities: smallvec![0.0; src.len()],
+            key,
         }
     }
 }
@@ -41,13 +117,24 @@ impl AbstractValueProvider for SmoothProvidersValues {
 
 impl UpdateableValues for SmoothProvidersValues {
     fn update(&mut self, delta: f32, context: &BaseProviderContext) {
-        let mult_delta = self.mult * delta;
-        let mult_delta_clamped = mult_delta.clamp(0.0, 1.0);
-
         let src: ValueProviderValues = self.source_provider.values(context);
+
+        if self.kind == SmoothKind::Spring {
+            for ((value, velocity), target) in
+                self.values.iter_mut().zip(&mut self.velocities).zip(&src)
+            {
+                let (offset, new_velocity) =
+                    spring_step(*value - target, *velocity, self.mult, delta);
+                *value = target + offset;
+                *velocity = new_velocity;
+            }
+            return;
+        }
+
+        let factor = self.kind.factor(self.mult, delta);
         for (i, value) in self.values.iter_mut().enumerate() {
             if i < src.len() {
-                *value = value.lerp(src[i], mult_delta_clamped);
+                *value = value.lerp(src[i], factor);
             }
         }
     }
@@ -6,6 +6,7 @@ use crate::{
 };
 
 use super::AbstractValueProvider;
+use super::smooth::{SmoothKind, spring_step};
 
 use glam::{Quat, Vec3};
 use log::warn;
@@ -14,23 +15,69 @@ use log::warn;
 pub struct SmoothRotationProvidersValues {
     pub(crate) source_provider: crate::providers::ValueProvider,
     pub(crate) mult: f32,
+    pub(crate) kind: SmoothKind,
     pub(crate) last_quaternion: Quat,
+    /// Angular velocity as a scaled axis in radians per second, used by [`SmoothKind::Spring`]
+    pub(crate) angular_velocity: Vec3,
     pub(crate) values: Vec3,
+    /// Full provider string this was parsed from, e.g. `baseHeadRotation.s14`
+    pub(crate) key: String,
     warned: bool,
 }
 
 impl SmoothRotationProvidersValues {
+    /// The key is derived from the source's key, e.g. `baseHeadRotation.s14`.
     pub fn new(source_provider: crate::providers::ValueProvider, mult: f32) -> Self {
+        let key = format!(
+            "{}.{}",
+            source_provider.key().unwrap_or_default(),
+            SmoothKind::Linear.spec(mult)
+        );
+        Self::with_kind(source_provider, mult, SmoothKind::Linear, key)
+    }
+
+    pub fn with_kind(
+        source_provider: crate::providers::ValueProvider,
+        mult: f32,
+        kind: SmoothKind,
+        key: String,
+    ) -> Self {
         Self {
             source_provider,
             mult,
+            kind,
             last_quaternion: Quat::IDENTITY,
+            angular_velocity: Vec3::ZERO,
             values: Default::default(),
+            key,
             warned: false,
         }
     }
 }
 
+impl SmoothRotationProvidersValues {
+    // Runs the spring on the rotation from the target to the current orientation
+    fn spring_towards(&mut self, target: Quat, delta: f32) -> Quat {
+        let mut offset = self.last_quaternion * target.inverse();
+        // Take the short way round
+        if offset.w < 0.0 {
+            offset = -offset;
+        }
+        let offset = offset.to_scaled_axis().to_array();
+        let velocity = self.angular_velocity.to_array();
+
+        let mut new_offset = [0.0; 3];
+        let mut new_velocity = [0.0; 3];
+        for i in 0..3 {
+            (new_offset[i], new_velocity[i]) =
+                spring_step(offset[i], velocity[i], self.mult, delta);
+        }
+        self.angular_velocity = Vec3::from_array(new_velocity);
+
+        (Quat::from_scaled_axis(Vec3::from_array(new_offset)) * target).normalize()
+    }
+}
+
 impl AbstractValueProvider for SmoothRotationProvidersValues {
     fn values(&self, _context: &BaseProviderContext) -> ValueProviderValues {
         ValueProviderValues::from_slice(&self.values.to_array())
@@ -43,7 +90,6 @@ impl AbstractValueProvider for SmoothRotationProvidersValues {
 
 impl UpdateableValues for SmoothRotationProvidersValues {
     fn update(&mut self, delta: f32, context: &BaseProviderContext) {
-        let mult_delta = delta * self.mult;
         let src = self.source_provider.values(context);
 
         let quat = if self.source_provider.is_rotation(context) && src.len() >= 3 {
@@ -62,7 +108,12 @@ impl UpdateableValues for SmoothRotationProvidersValues {
             Quat::IDENTITY
         };
 
-        self.last_quaternion = self.last_quaternion.slerp(quat, mult_delta.clamp(0.0, 1.0));
+        self.last_quaternion = match self.kind {
+            SmoothKind::Spring => self.spring_towards(quat, delta),
+            kind => self
+                .last_quaternion
+                .slerp(quat, kind.factor(self.mult, delta)),
+        };
 
         let euler = self.last_quaternion.to_unity_euler_degrees();
 
@@ -0,0 +1,94 @@
+use glam::{Quat, Vec3};
+use smallvec::SmallVec;
+
+use super::UpdateableValues;
+
+use crate::{
+    base_provider_context::BaseProviderContext, providers::ValueProviderValues,
+    quaternion_utils::QuaternionUtilsExt,
+};
+
+use super::AbstractValueProvider;
+
+/// Per-second rate of change of another provider, created from the `v` and `av` specs.
+///
+/// `v` differentiates each component. `av` gives the angular velocity of a rotation as a vector
+/// in world space: the rotation axis scaled by the turn rate in degrees per second. Both read zero
+/// until the second update.
+#[derive(Clone, Debug)]
+pub struct VelocityProviderValues {
+    pub(crate) source_provider: crate::providers::ValueProvider,
+    pub(crate) angular: bool,
+    last_values: Option<ValueProviderValues>,
+    pub(crate) values: SmallVec<[f32; 4]>,
+    /// Full provider string this was parsed from, e.g. `baseRightHandPosition.v`
+    pub(crate) key: String,
+}
+
+impl VelocityProviderValues {
+    pub fn new(
+        source_provider: crate::providers::ValueProvider,
+        angular: bool,
+        key: String,
+        context: &BaseProviderContext,
+    ) -> Self {
+        let len = match angular {
+            true => 3,
+            false => source_provider.values(context).len(),
+        };
+        Self {
+            source_provider,
+            angular,
+            last_values: None,
+            values: SmallVec::from_elem(0.0, len),
+            key,
+        }
+    }
+
+    fn angular_velocity(last: &[f32], current: &[f32], delta: f32) -> Vec3 {
+        if last.len() < 3 || current.len() < 3 {
+            return Vec3::ZERO;
+        }
+        let last = Quat::from_unity_euler_degrees(Vec3::new(last[0], last[1], last[2]));
+        let current = Quat::from_unity_euler_degrees(Vec3::new(current[0], current[1], current[2]));
+
+        let mut change = current * last.inverse();
+        // Take the short way round
+        if change.w < 0.0 {
+            change = -change;
+        }
+        change.to_scaled_axis() * (180.0 / std::f32::consts::PI) / delta
+    }
+}
+
+impl AbstractValueProvider for VelocityProviderValues {
+    fn values(&self, _context: &BaseProviderContext) -> ValueProviderValues {
+        ValueProviderValues::from_slice(self.values.as_slice())
+    }
+
+    fn is_rotation(&self, _context: &BaseProviderContext) -> bool {
+        false
+    }
+}
+
+impl UpdateableValues for VelocityProviderValues {
+    fn update(&mut self, delta: f32, context: &BaseProviderContext) {
+        // A zero delta would divide by zero, keep the last rate instead
+        if delta <= 0.0 {
+            return;
+        }
+
+        let src = self.source_provider.values(context);
+        if let Some(last) = &self.last_values {
+            if self.angular {
+                let velocity = Self::angular_velocity(last, &src, delta);
+                self.values.copy_from_slice(&velocity.to_array());
+            } else {
+                for ((value, current), last) in self.values.iter_mut().zip(&src).zip(last) {
+                    *value = (current - last) / delta;
+                }
+            }
+        }
+        self.last_values = Some(src);
+    }
+}
@@ -0,0 +1,366 @@
+//! Recording of base inputs for deterministic replay.
+//!
+//! [`BaseProviderContext::start_recording`] captures every base write and every
+//! [`update_providers`](BaseProviderContext::update_providers) call, each tagged with the frame it
+//! happened in. [`ReplayPlayer`] feeds a [`Recording`] back into a fresh context frame by frame,
+//! so head-tracked or score-reactive animations can be reproduced without the game running.
+//!
+//! Frames are counted by `update_providers` calls: writes before the first update belong to
+//! frame 0, and each update ends its frame. Recording starts with a snapshot of every registered
+//! base, so the replay doesn't depend on values set before it started. Custom providers are
+//! computed by the host and are not recorded; register them again before replaying.
+//!
+//! Layout of [`Recording::encode`], little-endian, counts and indices as LEB128 varints:
+//!
+//! ```text
+//! magic "TRRC" | version u16
+//! base count  | (length | utf8 bytes)*
+//! event count | (frame advance | event)*
+//! event       = 0 | base index | value type u8 | component f32*   base write
+//!             | 1 | delta f32                                      update_providers
+//! ```
+//!
+//! The frame advance is the difference to the previous event's frame. Quaternions are stored as
+//! their 4 raw components.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use glam::{Quat, Vec3, Vec4};
+
+use crate::base_provider_context::{BaseError, BaseProviderContext};
+use crate::base_value::{BaseValue, WrapBaseValueType};
+use crate::codec::{ByteReader, CodecError, write_varint};
+
+pub const RECORDING_MAGIC: [u8; 4] = *b"TRRC";
+/// Bumped whenever the layout changes; older recordings fail with
+/// [`RecordingError::UnsupportedVersion`].
+///
+/// cbindgen:ignore
+pub const RECORDING_VERSION: u16 = 1;
+
+const EVENT_SET: u8 = 0;
+const EVENT_UPDATE: u8 = 1;
+
+/// A single recorded call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedEvent {
+    /// A base was set to `value`. `base` indexes [`Recording::bases`].
+    Set {
+        frame: u32,
+        base: usize,
+        value: BaseValue,
+    },
+    /// `update_providers(delta)` was called, ending `frame`.
+    Update { frame: u32, delta: f32 },
+}
+
+impl RecordedEvent {
+    pub fn frame(&self) -> u32 {
+        match self {
+            RecordedEvent::Set { frame, .. } | RecordedEvent::Update { frame, .. } => *frame,
+        }
+    }
+}
+
+/// Base inputs captured by [`BaseProviderContext::start_recording`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    bases: Vec<String>,
+    base_indices: HashMap<String, usize>,
+    events: Vec<RecordedEvent>,
+    frame: u32,
+}
+
+impl Recording {
+    /// Names of the recorded bases, indexed by [`RecordedEvent::Set`].
+    pub fn bases(&self) -> &[String] {
+        &self.bases
+    }
+
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Number of `update_providers` calls recorded.
+    pub fn frame_count(&self) -> u32 {
+        self.frame
+    }
+
+    pub(crate) fn record_set(&mut self, base: &str, value: BaseValue) {
+        let base = match self.base_indices.get(base) {
+            Some(&index) => index,
+            None => {
+                self.bases.push(base.to_string());
+                self.base_indices
+                    .insert(base.to_string(), self.bases.len() - 1);
+                self.bases.len() - 1
+            }
+        };
+        self.events.push(RecordedEvent::Set {
+            frame: self.frame,
+            base,
+            value,
+        });
+    }
+
+    pub(crate) fn record_update(&mut self, delta: f32) {
+        self.events.push(RecordedEvent::Update {
+            frame: self.frame,
+            delta,
+        });
+        self.frame += 1;
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&RECORDING_MAGIC);
+        bytes.extend_from_slice(&RECORDING_VERSION.to_le_bytes());
+
+        write_varint(&mut bytes, self.bases.len());
+        for base in &self.bases {
+            write_varint(&mut bytes, base.len());
+            bytes.extend_from_slice(base.as_bytes());
+        }
+
+        write_varint(&mut bytes, self.events.len());
+        let mut frame = 0;
+        for event in &self.events {
+            write_varint(&mut bytes, (event.frame() - frame) as usize);
+            frame = event.frame();
+            match event {
+                RecordedEvent::Set { base, value, .. } => {
+                    bytes.push(EVENT_SET);
+                    write_varint(&mut bytes, *base);
+                    bytes.push(value.get_type() as u8);
+                    let components: &[f32] = match value {
+                        BaseValue::Float(f) => &[*f],
+                        BaseValue::Vector3(v) => &v.to_array(),
+                        BaseValue::Vector4(v) => &v.to_array(),
+                        BaseValue::Quaternion(q) => &q.to_array(),
+                    };
+                    for component in components {
+                        bytes.extend_from_slice(&component.to_le_bytes());
+                    }
+                }
+                RecordedEvent::Update { delta, .. } => {
+                    bytes.push(EVENT_UPDATE);
+                    bytes.extend_from_slice(&delta.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, RecordingError> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.take(4)? != RECORDING_MAGIC {
+            return Err(RecordingError::BadMagic);
+        }
+        let version = u16::from_le_bytes(reader.take(2)?.try_into().unwrap());
+        if version != RECORDING_VERSION {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+
+        let mut recording = Recording::default();
+        for _ in 0..reader.count()? {
+            let len = reader.varint()?;
+            let base = std::str::from_utf8(reader.take(len)?)
+                .map_err(|_| RecordingError::InvalidUtf8)?
+                .to_string();
+            recording
+                .base_indices
+                .insert(base.clone(), recording.bases.len());
+            recording.bases.push(base);
+        }
+
+        let mut frame = 0u32;
+        for _ in 0..reader.count()? {
+            let advance =
+                u32::try_from(reader.varint()?).map_err(|_| RecordingError::InvalidVarint)?;
+            frame = frame
+                .checked_add(advance)
+                .ok_or(RecordingError::InvalidVarint)?;
+            let event = match reader.u8()? {
+                EVENT_SET => {
+                    let base = reader.varint()?;
+                    if base >= recording.bases.len() {
+                        return Err(RecordingError::InvalidBase(base));
+                    }
+                    let value = read_value(&mut reader)?;
+                    RecordedEvent::Set { frame, base, value }
+                }
+                EVENT_UPDATE => {
+                    recording.frame = frame.saturating_add(1);
+                    RecordedEvent::Update {
+                        frame,
+                        delta: reader.f32()?,
+                    }
+                }
+                tag => return Err(RecordingError::InvalidTag(tag)),
+            };
+            recording.events.push(event);
+        }
+
+        if !reader.is_at_end() {
+            return Err(RecordingError::TrailingBytes);
+        }
+        Ok(recording)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordingError {
+    /// The data ends in the middle of a value.
+    UnexpectedEof,
+    /// The data does not start with [`RECORDING_MAGIC`].
+    BadMagic,
+    /// The recording was written by a different format version.
+    UnsupportedVersion(u16),
+    /// The value type byte is not a known [`WrapBaseValueType`].
+    UnknownType(u8),
+    /// A tag byte has a value the format does not define.
+    InvalidTag(u8),
+    /// A varint is longer than a `usize`, or a frame number overflows.
+    InvalidVarint,
+    /// A base index points outside of the base table.
+    InvalidBase(usize),
+    /// A base name is not UTF-8.
+    InvalidUtf8,
+    /// There is data left after the last event.
+    TrailingBytes,
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingError::UnexpectedEof => write!(f, "unexpected end of recording data"),
+            RecordingError::BadMagic => write!(f, "not a base input recording"),
+            RecordingError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported recording version {version}, expected {RECORDING_VERSION}"
+            ),
+            RecordingError::UnknownType(ty) => write!(f, "unknown value type {ty}"),
+            RecordingError::InvalidTag(tag) => write!(f, "invalid tag {tag}"),
+            RecordingError::InvalidVarint => write!(f, "invalid varint"),
+            RecordingError::InvalidBase(index) => write!(f, "invalid base reference {index}"),
+            RecordingError::InvalidUtf8 => write!(f, "base name is not valid UTF-8"),
+            RecordingError::TrailingBytes => write!(f, "trailing bytes after recording"),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl From<CodecError> for RecordingError {
+    fn from(error: CodecError) -> Self {
+        match error {
+            CodecError::UnexpectedEof => RecordingError::UnexpectedEof,
+            CodecError::InvalidVarint => RecordingError::InvalidVarint,
+        }
+    }
+}
+
+/// The frame applied by [`ReplayPlayer::step`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayFrame {
+    pub frame: u32,
+    /// The delta `update_providers` was called with, or `None` if the recording stopped before
+    /// this frame ended.
+    pub delta: Option<f32>,
+}
+
+/// Plays a [`Recording`] back into a context one frame at a time.
+///
+/// Bases the context doesn't know are registered with the recorded type, so a fresh context
+/// works for recordings that used custom bases.
+#[derive(Debug, Clone)]
+pub struct ReplayPlayer {
+    recording: Recording,
+    position: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            position: 0,
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.events.len()
+    }
+
+    /// Applies the base writes of the next frame and its `update_providers` call. Returns `None`
+    /// once every frame has been played.
+    pub fn step(
+        &mut self,
+        context: &mut BaseProviderContext,
+    ) -> Result<Option<ReplayFrame>, BaseError> {
+        let Some(frame) = self
+            .recording
+            .events
+            .get(self.position)
+            .map(RecordedEvent::frame)
+        else {
+            return Ok(None);
+        };
+
+        while let Some(event) = self.recording.events.get(self.position) {
+            self.position += 1;
+            match event {
+                RecordedEvent::Set { base, value, .. } => {
+                    let base = &self.recording.bases[*base];
+                    if context.has_base(base) {
+                        context.try_set_values(base, *value)?;
+                    } else {
+                        context.register_base(base, value.get_type(), *value)?;
+                    }
+                }
+                RecordedEvent::Update { delta, .. } => {
+                    context.update_providers(*delta);
+                    return Ok(Some(ReplayFrame {
+                        frame,
+                        delta: Some(*delta),
+                    }));
+                }
+            }
+        }
+
+        Ok(Some(ReplayFrame { frame, delta: None }))
+    }
+}
+
+fn read_value(reader: &mut ByteReader) -> Result<BaseValue, RecordingError> {
+    let ty = match reader.u8()? {
+        ty if ty == WrapBaseValueType::Vec3 as u8 => WrapBaseValueType::Vec3,
+        ty if ty == WrapBaseValueType::Quat as u8 => WrapBaseValueType::Quat,
+        ty if ty == WrapBaseValueType::Vec4 as u8 => WrapBaseValueType::Vec4,
+        ty if ty == WrapBaseValueType::Float as u8 => WrapBaseValueType::Float,
+        ty => return Err(RecordingError::UnknownType(ty)),
+    };
+
+    Ok(match ty {
+        WrapBaseValueType::Vec3 => {
+            BaseValue::Vector3(Vec3::new(reader.f32()?, reader.f32()?, reader.f32()?))
+        }
+        WrapBaseValueType::Quat => BaseValue::Quaternion(Quat::from_xyzw(
+            reader.f32()?,
+            reader.f32()?,
+            reader.f32()?,
+            reader.f32()?,
+        )),
+        WrapBaseValueType::Vec4 => BaseValue::Vector4(Vec4::new(
+            reader.f32()?,
+            reader.f32()?,
+            reader.f32()?,
+            reader.f32()?,
+        )),
+        WrapBaseValueType::Float | WrapBaseValueType::Unknown => BaseValue::Float(reader.f32()?),
+    })
+}
@@ -0,0 +1,67 @@
+//! Shared ownership and interior mutability used across the crate.
+//!
+//! By default these are the single-threaded `Rc` and `RefCell`. With the `sync` feature they
+//! become `Arc` and a read-write lock, so contexts, point definitions and tracks are `Send + Sync`
+//! and can be sampled from worker threads.
+
+use cfg_if::cfg_if;
+
+cfg_if! {
+    if #[cfg(feature = "sync")] {
+        pub use std::sync::{Arc as Shared, Weak as SharedWeak};
+
+        /// Bound added to trait objects held by the crate, e.g. custom providers and callbacks.
+        pub trait MaybeSendSync: Send + Sync {}
+        impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+
+        /// Holds the state of smoothing and velocity providers.
+        ///
+        /// Sampling takes a read lock, which never blocks other readers. Only
+        /// [`update_providers`](crate::base_provider_context::BaseProviderContext::update_providers)
+        /// takes the write lock, so workers only wait if they sample during an update. A lock rather
+        /// than atomics, so a sample never mixes components from before and after an update.
+        #[derive(Debug, Default)]
+        pub struct StateCell<T: ?Sized>(std::sync::RwLock<T>);
+
+        impl<T> StateCell<T> {
+            pub fn new(value: T) -> Self {
+                Self(std::sync::RwLock::new(value))
+            }
+
+            pub fn into_inner(self) -> T {
+                self.0.into_inner().unwrap_or_else(std::sync::PoisonError::into_inner)
+            }
+        }
+
+        impl<T: Clone> Clone for StateCell<T> {
+            fn clone(&self) -> Self {
+                Self::new(self.borrow().clone())
+            }
+        }
+
+        impl<T: ?Sized> StateCell<T> {
+            pub fn borrow(&self) -> std::sync::RwLockReadGuard<'_, T> {
+                // State stays usable if an update panicked on another thread
+                self.0.read().unwrap_or_else(std::sync::PoisonError::into_inner)
+            }
+
+            pub fn borrow_mut(&self) -> std::sync::RwLockWriteGuard<'_, T> {
+                self.0.write().unwrap_or_else(std::sync::PoisonError::into_inner)
+            }
+
+            pub fn get_mut(&mut self) -> &mut T {
+                self.0.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner)
+            }
+        }
+    } else {
+        pub use std::rc::{Rc as Shared, Weak as SharedWeak};
+
+        /// Bound added to trait objects held by the crate. Only requires `Send + Sync` with the
+        /// `sync` feature.
+        pub trait MaybeSendSync {}
+        impl<T: ?Sized> MaybeSendSync for T {}
+
+        /// Holds the state of smoothing and velocity providers.
+        pub type StateCell<T> = std::cell::RefCell<T>;
+    }
+}
@@ -1,7 +1,6 @@
-use glam::FloatExt;
-use glam::{Quat, Vec3, Vec4};
+use glam::{Vec3, Vec4};
 
-use crate::base_value::{BaseValue, WrapBaseValueType};
+use crate::base_value::WrapBaseValueType;
 
 /// Represents a type that can be used as a value in the system, such as a float, vector, or quaternion.
 /// This trait defines the necessary operations and conversions for these types, allowing them to be used
@@ -31,6 +30,9 @@ pub trait ValueType:
 
     fn from_slice(values: &[f32]) -> Self;
 
+    /// The components of this value, in the order they are written in point definitions.
+    fn as_slice(&self) -> &[f32];
+
     #[inline]
     fn value_lerp(a: Self, b: Self, t: f32) -> Self {
         a + (b - a) * t
@@ -54,6 +56,10 @@ impl ValueType for f32 {
         values[0]
     }
 
+    fn as_slice(&self) -> &[f32] {
+        std::slice::from_ref(self)
+    }
+
     fn from_translate_slice(values: &[f32]) -> Self {
         values[0]
     }
@@ -79,6 +85,10 @@ impl ValueType for Vec3 {
         Vec3::from_slice(values)
     }
 
+    fn as_slice(&self) -> &[f32] {
+        self.as_ref()
+    }
+
     fn from_translate_slice(values: &[f32]) -> Self {
         Vec3::from_slice(values)
     }
@@ -104,6 +114,10 @@ impl ValueType for Vec4 {
         Vec4::from_slice(values)
     }
 
+    fn as_slice(&self) -> &[f32] {
+        self.as_ref()
+    }
+
     fn from_translate_slice(values: &[f32]) -> Self {
         Vec4::from_slice(values)
     }
@@ -120,4 +134,4 @@ impl ValueType for Vec4 {
     fn base_type() -> WrapBaseValueType {
         WrapBaseValueType::Vec4
     }
-}
\ No newline at end of file
+}
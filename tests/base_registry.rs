@@ -0,0 +1,153 @@
+use glam::{Quat, Vec3};
+use serde_json::json;
+use tracks_rs::base_provider_context::{BaseError, BaseProviderContext};
+use tracks_rs::base_value::{BaseValue, WrapBaseValueType};
+use tracks_rs::point_definition::parse_error::ParseErrorKind;
+use tracks_rs::point_definition::{FloatPointDefinition, PointDefinitionLike};
+use tracks_rs::prelude::AbstractValueProvider;
+
+#[test]
+fn builtin_bases_are_registered() {
+    let ctx = BaseProviderContext::new();
+
+    assert_eq!(ctx.base_type("baseCombo"), Some(WrapBaseValueType::Float));
+    assert_eq!(
+        ctx.base_type("baseNote0Color"),
+        Some(WrapBaseValueType::Vec4)
+    );
+    assert_eq!(
+        ctx.base_type("baseRightHandPosition"),
+        Some(WrapBaseValueType::Vec3)
+    );
+    assert_eq!(
+        ctx.get_values("baseHeadRotation"),
+        BaseValue::Quaternion(Quat::IDENTITY)
+    );
+    assert_eq!(ctx.base_names().next(), Some("baseCombo"));
+    assert_eq!(ctx.base_type("baseSaberLength"), None);
+}
+
+#[test]
+fn custom_bases_work_like_builtin_ones() {
+    let mut ctx = BaseProviderContext::new();
+    ctx.register_base(
+        "baseLeftHandVelocity",
+        WrapBaseValueType::Vec3,
+        BaseValue::Vector3(Vec3::ZERO),
+    )
+    .unwrap();
+    ctx.register_base(
+        "baseSaberLength",
+        WrapBaseValueType::Float,
+        BaseValue::Float(1.0),
+    )
+    .unwrap();
+
+    assert_eq!(ctx.get_values("baseSaberLength"), BaseValue::Float(1.0));
+    assert_eq!(ctx.base_names().last(), Some("baseSaberLength"));
+
+    let swizzled = ctx.get_value_provider("baseLeftHandVelocity.zx");
+    let smoothed = ctx.get_value_provider("baseLeftHandVelocity.zx.s2");
+    ctx.set_values(
+        "baseLeftHandVelocity",
+        BaseValue::Vector3(Vec3::new(1.0, 2.0, 3.0)),
+    );
+    assert_eq!(swizzled.values(&ctx).as_slice(), &[3.0, 1.0]);
+
+    ctx.update_providers(1.0);
+    assert_eq!(smoothed.values(&ctx).as_slice(), &[3.0, 1.0]);
+
+    let definition =
+        FloatPointDefinition::try_parse(&json!([["baseSaberLength", 0]]), &mut ctx).unwrap();
+    ctx.set_values("baseSaberLength", BaseValue::Float(2.5));
+    assert_eq!(definition.interpolate(0.0, &ctx).0, 2.5);
+}
+
+#[test]
+fn registration_is_validated() {
+    let mut ctx = BaseProviderContext::new();
+
+    assert_eq!(
+        ctx.register_base("baseCombo", WrapBaseValueType::Float, BaseValue::Float(0.0)),
+        Err(BaseError::AlreadyRegistered("baseCombo".to_string()))
+    );
+    for name in ["saberLength", "baseSaber.Length"] {
+        assert_eq!(
+            ctx.register_base(name, WrapBaseValueType::Float, BaseValue::Float(0.0)),
+            Err(BaseError::InvalidName(name.to_string()))
+        );
+    }
+    assert_eq!(
+        ctx.register_base("baseFoo", WrapBaseValueType::Vec3, BaseValue::Float(0.0)),
+        Err(BaseError::TypeMismatch {
+            base: "baseFoo".to_string(),
+            expected: WrapBaseValueType::Vec3,
+            found: WrapBaseValueType::Float,
+        })
+    );
+    assert!(!ctx.has_base("baseFoo"));
+}
+
+#[test]
+fn unknown_bases_are_errors() {
+    let mut ctx = BaseProviderContext::new();
+
+    assert_eq!(
+        ctx.try_get_values("baseFoo"),
+        Err(BaseError::UnknownBase("baseFoo".to_string()))
+    );
+    assert_eq!(
+        ctx.try_set_values("baseFoo", BaseValue::Float(1.0)),
+        Err(BaseError::UnknownBase("baseFoo".to_string()))
+    );
+    assert!(matches!(
+        ctx.try_set_values("baseCombo", BaseValue::Vector3(Vec3::ONE)),
+        Err(BaseError::TypeMismatch { .. })
+    ));
+    assert_eq!(
+        ctx.try_get_value_provider("baseFoo.xy.s2").unwrap_err(),
+        BaseError::UnknownBase("baseFoo".to_string())
+    );
+
+    let err =
+        FloatPointDefinition::try_parse(&json!([[0, 0], ["baseFoo.x", 1]]), &mut ctx).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::UnknownBase("baseFoo".to_string()));
+    assert_eq!(err.path.to_string(), "[1]");
+}
+
+#[test]
+fn slots_read_and_write_bases() {
+    let mut ctx = BaseProviderContext::new();
+    ctx.register_base(
+        "baseSaberLength",
+        WrapBaseValueType::Float,
+        BaseValue::Float(1.0),
+    )
+    .unwrap();
+
+    let combo = ctx.base_slot("baseCombo").unwrap();
+    let saber = ctx.base_slot("baseSaberLength").unwrap();
+    assert_eq!(ctx.base_slot("baseFoo"), None);
+    // Built-in bases have the same slots everywhere
+    assert_eq!(
+        BaseProviderContext::new().base_slot("baseCombo"),
+        Some(combo)
+    );
+
+    ctx.set_slot(saber, BaseValue::Float(2.0)).unwrap();
+    assert_eq!(ctx.get_values("baseSaberLength"), BaseValue::Float(2.0));
+
+    let provider = ctx.get_value_provider("baseCombo");
+    ctx.set_values("baseCombo", BaseValue::Float(5.0));
+    assert_eq!(ctx.get_slot(combo), &BaseValue::Float(5.0));
+    assert_eq!(provider.values(&ctx).as_slice(), &[5.0]);
+
+    assert_eq!(
+        ctx.set_slot(saber, BaseValue::Vector3(Vec3::ONE)),
+        Err(BaseError::TypeMismatch {
+            base: "baseSaberLength".to_string(),
+            expected: WrapBaseValueType::Float,
+            found: WrapBaseValueType::Vec3,
+        })
+    );
+}
@@ -0,0 +1,451 @@
+use serde_json::json;
+use tracks_rs::{
+    animation::{
+        events::EventType,
+        track::{PathPropertyHandle, PropertyNames, ValuePropertyHandle},
+        tracks_holder::TracksHolder,
+    },
+    base_provider_context::BaseProviderContext,
+    base_value::{BaseValue, WrapBaseValueType},
+    beatmap::{BeatmapError, load_custom_events, v2},
+    easings::functions::Functions,
+    point_definition::{
+        PointDefinitionLike, parse_error::ParseErrorKind, registry::PointDefinitionRegistry,
+    },
+};
+
+#[test]
+fn loads_animate_track_and_path_events() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let custom_data = json!({
+        "customEvents": [
+            {
+                "b": 4.0,
+                "t": "AnimateTrack",
+                "d": {
+                    "track": ["left", "right"],
+                    "duration": 2.0,
+                    "easing": "easeOutQuad",
+                    "repeat": 1,
+                    "dissolve": [[0, 0], [1, 1]],
+                    "color": null
+                }
+            },
+            {
+                "b": 2.0,
+                "t": "AssignPathAnimation",
+                "d": {
+                    "track": "left",
+                    "definitePosition": [[0, 0, 0, 0], [0, 10, 0, 1]]
+                }
+            }
+        ]
+    });
+
+    let loaded = load_custom_events(&custom_data, 120.0, &mut tracks, &mut context).unwrap();
+
+    assert_eq!(tracks.len(), 2);
+    let left = tracks.get_track_key("left").unwrap();
+    let right = tracks.get_track_key("right").unwrap();
+
+    assert!(loaded.unsupported.is_empty());
+    assert_eq!(loaded.events.len(), 5);
+
+    // sorted by start time, beats converted with the bpm
+    let path = &loaded.events[0];
+    assert_eq!(path.start_song_time, 1.0);
+    assert_eq!(path.track_key, left);
+    assert_eq!(
+        path.property,
+        EventType::AssignPathAnimation(PathPropertyHandle::ById(PropertyNames::DefinitePosition))
+    );
+    let path_points = path.point_data.as_ref().unwrap();
+    assert_eq!(path_points.get_type(), WrapBaseValueType::Vec3);
+    assert_eq!(
+        path_points.interpolate(0.5, &context).0,
+        BaseValue::Vector3(glam::Vec3::new(0.0, 5.0, 0.0))
+    );
+
+    let dissolve: Vec<_> = loaded
+        .events
+        .iter()
+        .filter(|e| {
+            e.property
+                == EventType::AnimateTrack(ValuePropertyHandle::ById(PropertyNames::Dissolve))
+        })
+        .collect();
+    assert_eq!(dissolve.len(), 2);
+    assert_eq!(dissolve[0].track_key, left);
+    assert_eq!(dissolve[1].track_key, right);
+    assert_eq!(dissolve[0].start_song_time, 2.0);
+    assert_eq!(dissolve[0].raw_duration, 2.0);
+    assert_eq!(dissolve[0].easing, Functions::EaseOutQuad);
+    assert_eq!(dissolve[0].repeat, 1);
+    assert_eq!(
+        dissolve[0].point_data.as_ref().unwrap().get_type(),
+        WrapBaseValueType::Float
+    );
+
+    let color = loaded
+        .events
+        .iter()
+        .find(|e| {
+            e.property == EventType::AnimateTrack(ValuePropertyHandle::ById(PropertyNames::Color))
+        })
+        .unwrap();
+    assert!(color.point_data.is_none());
+}
+
+#[test]
+fn reports_unsupported_event_types_and_registers_their_tracks() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let custom_data = json!({
+        "customEvents": [
+            { "b": 0, "t": "AssignPlayerToTrack", "d": { "track": "player" } },
+            { "b": 0, "t": "AnimateComponent", "d": { "track": "env" } },
+            { "b": 1, "t": "AssignPlayerToTrack", "d": { "track": "player" } }
+        ]
+    });
+
+    let loaded = load_custom_events(&custom_data, 60.0, &mut tracks, &mut context).unwrap();
+
+    assert!(loaded.events.is_empty());
+    assert_eq!(
+        loaded.unsupported,
+        vec![
+            "AssignPlayerToTrack".to_string(),
+            "AnimateComponent".to_string()
+        ]
+    );
+    assert!(tracks.get_track_by_name("player").is_some());
+    assert!(tracks.get_track_by_name("env").is_some());
+}
+
+#[test]
+fn reuses_existing_tracks() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let custom_data = json!({
+        "customEvents": [
+            { "b": 0, "t": "AnimateTrack", "d": { "track": "a", "position": [0, 0, 0] } },
+            { "b": 1, "t": "AnimateTrack", "d": { "track": ["a", "b"], "scale": [1, 1, 1] } }
+        ]
+    });
+
+    load_custom_events(&custom_data, 60.0, &mut tracks, &mut context).unwrap();
+    load_custom_events(&custom_data, 60.0, &mut tracks, &mut context).unwrap();
+
+    assert_eq!(tracks.len(), 2);
+}
+
+#[test]
+fn reports_point_definition_errors() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let custom_data = json!({
+        "customEvents": [
+            {
+                "b": 0,
+                "t": "AnimateTrack",
+                "d": { "track": "a", "position": [[0, 0, 0, [1, 1, "opAdd"], 0]] }
+            }
+        ]
+    });
+
+    let error = load_custom_events(&custom_data, 60.0, &mut tracks, &mut context).unwrap_err();
+    let BeatmapError::PointDefinition {
+        event,
+        property,
+        error,
+    } = error
+    else {
+        panic!("expected a point definition error, got {error:?}");
+    };
+    assert_eq!(event, 0);
+    assert_eq!(property, "position");
+    assert_eq!(
+        error.kind,
+        ParseErrorKind::WrongComponentCount {
+            expected: 3,
+            found: 2
+        }
+    );
+    assert_eq!(error.path.indices(), &[0, 3]);
+}
+
+#[test]
+fn missing_custom_events_is_empty() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let loaded = load_custom_events(&json!({}), 60.0, &mut tracks, &mut context).unwrap();
+    assert!(loaded.events.is_empty());
+    assert!(tracks.is_empty());
+
+    assert!(load_custom_events(&json!([]), 60.0, &mut tracks, &mut context).is_err());
+}
+
+#[test]
+fn rejects_non_positive_bpm() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    for bpm in [0.0, -120.0] {
+        assert_eq!(
+            load_custom_events(&json!({}), bpm, &mut tracks, &mut context).unwrap_err(),
+            BeatmapError::InvalidBpm(bpm)
+        );
+    }
+    for bpm in [f32::NAN, f32::INFINITY] {
+        assert!(load_custom_events(&json!({}), bpm, &mut tracks, &mut context).is_err());
+    }
+}
+
+#[test]
+fn resolves_named_point_definitions() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let custom_data = json!({
+        "pointDefinitions": {
+            "fadeIn": [[0, 0], [1, 1]],
+            "rise": [[0, 0, 0, 0], [0, 4, 0, 1]]
+        },
+        "customEvents": [
+            {
+                "b": 0,
+                "t": "AnimateTrack",
+                "d": { "track": "a", "dissolve": "fadeIn", "position": "rise" }
+            },
+            {
+                "b": 1,
+                "t": "AssignPathAnimation",
+                "d": { "track": "a", "dissolveArrow": "fadeIn", "scale": [1, 1, 1] }
+            }
+        ]
+    });
+
+    let loaded = load_custom_events(&custom_data, 60.0, &mut tracks, &mut context).unwrap();
+    assert_eq!(loaded.events.len(), 4);
+    assert_eq!(loaded.point_definitions.len(), 2);
+
+    let find = |property: EventType| {
+        loaded
+            .events
+            .iter()
+            .find(|e| e.property == property)
+            .and_then(|e| e.point_data.clone())
+            .unwrap()
+    };
+
+    let dissolve = find(EventType::AnimateTrack(ValuePropertyHandle::ById(
+        PropertyNames::Dissolve,
+    )));
+    assert_eq!(dissolve.get_type(), WrapBaseValueType::Float);
+    assert_eq!(dissolve.interpolate(0.5, &context).0, BaseValue::Float(0.5));
+
+    let position = find(EventType::AnimateTrack(ValuePropertyHandle::ById(
+        PropertyNames::Position,
+    )));
+    assert_eq!(position.get_type(), WrapBaseValueType::Vec3);
+    assert_eq!(
+        position.interpolate(0.5, &context).0,
+        BaseValue::Vector3(glam::Vec3::new(0.0, 2.0, 0.0))
+    );
+
+    let dissolve_arrow = find(EventType::AssignPathAnimation(PathPropertyHandle::ById(
+        PropertyNames::DissolveArrow,
+    )));
+    assert_eq!(dissolve_arrow.get_type(), WrapBaseValueType::Float);
+}
+
+#[test]
+fn reports_unknown_point_definition_names() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let custom_data = json!({
+        "pointDefinitions": { "fadeIn": [[0, 0], [1, 1]] },
+        "customEvents": [
+            { "b": 0, "t": "AnimateTrack", "d": { "track": "a", "dissolve": "fadeOut" } }
+        ]
+    });
+
+    let error = load_custom_events(&custom_data, 60.0, &mut tracks, &mut context).unwrap_err();
+    assert_eq!(
+        error,
+        BeatmapError::UnknownPointDefinition {
+            event: 0,
+            property: "dissolve".to_string(),
+            name: "fadeOut".to_string()
+        }
+    );
+}
+
+#[test]
+fn registry_types_the_same_name_lazily() {
+    let mut context = BaseProviderContext::new();
+    let mut registry = PointDefinitionRegistry::from_json(&json!({ "one": [1] }));
+
+    assert!(registry.contains("one"));
+    assert!(
+        registry
+            .resolve("two", WrapBaseValueType::Float, &mut context)
+            .is_none()
+    );
+
+    let float = registry
+        .resolve("one", WrapBaseValueType::Float, &mut context)
+        .unwrap()
+        .unwrap();
+    assert_eq!(float.interpolate(0.0, &context).0, BaseValue::Float(1.0));
+
+    let vec3 = registry
+        .resolve("one", WrapBaseValueType::Vec3, &mut context)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        vec3.interpolate(0.0, &context).0,
+        BaseValue::Vector3(glam::Vec3::new(1.0, 0.0, 0.0))
+    );
+
+    registry.insert("one", json!([2]));
+    let float = registry
+        .resolve("one", WrapBaseValueType::Float, &mut context)
+        .unwrap()
+        .unwrap();
+    assert_eq!(float.interpolate(0.0, &context).0, BaseValue::Float(2.0));
+}
+
+#[test]
+fn converts_v2_custom_data_to_v3() {
+    let v2_data = json!({
+        "_pointDefinitions": [
+            { "_name": "spin", "_points": [[0, 0, 0, 0], [0, 180, 0, 1, "easeInOutSine"]] }
+        ],
+        "_customEvents": [
+            {
+                "_time": 8,
+                "_type": "AnimateTrack",
+                "_data": {
+                    "_track": "notes",
+                    "_duration": 4,
+                    "_easing": "easeOutCubic",
+                    "_position": [[0, 0, 0, 0], [0, 1, 0, 1]],
+                    "_rotation": "spin",
+                    "_localRotation": [[0, 0, 90, 0]],
+                    "_interactable": [[0, 0]]
+                }
+            },
+            {
+                "_time": 8,
+                "_type": "AssignTrackParent",
+                "_data": { "_childrenTracks": ["notes"], "_parentTrack": "parent" }
+            }
+        ]
+    });
+
+    assert!(v2::is_v2(&v2_data));
+
+    let v3_data = v2::to_v3(&v2_data);
+    assert!(!v2::is_v2(&v3_data));
+    assert_eq!(
+        v3_data,
+        json!({
+            "pointDefinitions": {
+                "spin": [[0, 0, 0, 0], [0, 180, 0, 1, "easeInOutSine"]]
+            },
+            "customEvents": [
+                {
+                    "b": 8,
+                    "t": "AnimateTrack",
+                    "d": {
+                        "track": "notes",
+                        "duration": 4,
+                        "easing": "easeOutCubic",
+                        "offsetPosition": [[0, 0, 0, 0], [0, 1, 0, 1]],
+                        "offsetWorldRotation": "spin",
+                        "localRotation": [[0, 0, 90, 0]],
+                        "interactable": [[0, 0]]
+                    }
+                },
+                {
+                    "b": 8,
+                    "t": "AssignTrackParent",
+                    "d": { "childrenTracks": ["notes"], "parentTrack": "parent" }
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn loads_v2_custom_events() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let v2_data = json!({
+        "_pointDefinitions": [{ "_name": "fade", "_points": [[1, 0], [0, 1]] }],
+        "_customEvents": [
+            {
+                "_time": 2,
+                "_type": "AnimateTrack",
+                "_data": { "_track": "a", "_duration": 1, "_dissolve": "fade", "_position": [[0, 1, 0, 0]] }
+            },
+            {
+                "_time": 3,
+                "_type": "AssignPathAnimation",
+                "_data": { "_track": ["a", "b"], "_rotation": [[0, 90, 0, 0]] }
+            }
+        ]
+    });
+
+    let loaded = load_custom_events(&v2_data, 60.0, &mut tracks, &mut context).unwrap();
+
+    assert_eq!(tracks.len(), 2);
+    assert_eq!(loaded.point_definitions.len(), 1);
+    assert_eq!(loaded.events.len(), 4);
+
+    let properties: Vec<_> = loaded.events.iter().map(|e| e.property.clone()).collect();
+    assert!(
+        properties.contains(&EventType::AnimateTrack(ValuePropertyHandle::ById(
+            PropertyNames::Dissolve
+        )))
+    );
+    assert!(
+        properties.contains(&EventType::AnimateTrack(ValuePropertyHandle::ById(
+            PropertyNames::OffsetPosition
+        )))
+    );
+    assert!(
+        properties.contains(&EventType::AssignPathAnimation(PathPropertyHandle::ById(
+            PropertyNames::OffsetRotation
+        )))
+    );
+
+    let dissolve = loaded
+        .events
+        .iter()
+        .find(|e| {
+            e.property
+                == EventType::AnimateTrack(ValuePropertyHandle::ById(PropertyNames::Dissolve))
+        })
+        .unwrap();
+    assert_eq!(dissolve.start_song_time, 2.0);
+    assert_eq!(dissolve.raw_duration, 1.0);
+    assert_eq!(
+        dissolve
+            .point_data
+            .as_ref()
+            .unwrap()
+            .interpolate(0.25, &context)
+            .0,
+        BaseValue::Float(0.75)
+    );
+}
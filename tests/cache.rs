@@ -0,0 +1,275 @@
+use serde_json::json;
+use tracks_rs::{
+    base_provider_context::BaseProviderContext,
+    base_value::{BaseValue, WrapBaseValueType},
+    point_definition::{
+        PointDefinitionLike,
+        base_point_definition::BasePointDefinition,
+        cache::{self, CACHE_VERSION, CacheError},
+        spline::SplineKind,
+    },
+};
+
+fn parse(
+    value: serde_json::Value,
+    ty: WrapBaseValueType,
+    context: &mut BaseProviderContext,
+) -> BasePointDefinition {
+    BasePointDefinition::try_parse_typed(&value, ty, context).unwrap()
+}
+
+fn assert_same(a: &BasePointDefinition, b: &BasePointDefinition, context: &BaseProviderContext) {
+    assert_eq!(a.get_type(), b.get_type());
+    assert_eq!(a.get_count(), b.get_count());
+    assert_eq!(a.to_json(), b.to_json());
+    for time in [0.0, 0.1, 0.25, 0.5, 0.75, 0.9, 1.0] {
+        assert_eq!(a.interpolate(time, context), b.interpolate(time, context));
+    }
+}
+
+#[test]
+fn round_trips_every_value_type() {
+    let mut context = BaseProviderContext::new();
+    context.set_values("baseCombo", BaseValue::Float(3.0));
+    context.set_values(
+        "baseHeadPosition",
+        BaseValue::Vector3(glam::Vec3::new(1.0, 2.0, 3.0)),
+    );
+
+    let definitions = [
+        (
+            json!([
+                [0, 0],
+                ["baseCombo", [2, "opMul"], 0.5, "easeInQuad"],
+                [1, 1, "easeOutBounce"]
+            ]),
+            WrapBaseValueType::Float,
+        ),
+        (
+            json!([
+                [0, 0],
+                [
+                    1,
+                    0.25,
+                    [2, "splineControl"],
+                    ["baseCombo", [1, "opSub"], "splineControl"],
+                    "splineBezier"
+                ],
+                [
+                    0,
+                    0.5,
+                    [1, "splineControl"],
+                    [-1, "splineControl"],
+                    "splineHermite"
+                ],
+                [1, 0.75, "splineBSpline"],
+                [0, 1, "splineCatmullRomCentripetal"]
+            ]),
+            WrapBaseValueType::Float,
+        ),
+        (
+            json!([
+                [0, 0, 0, 0],
+                [1, 2, 3, [1, 1, 1, [0.5, 0.5, 0.5, "opMul"], "opAdd"], 0.5],
+                [
+                    "baseHeadPosition.zyx",
+                    1,
+                    "easeInOutSine",
+                    "splineCatmullRom"
+                ]
+            ]),
+            WrapBaseValueType::Vec3,
+        ),
+        (
+            json!([
+                [1, 0, 0, 1, 0],
+                [
+                    "baseCombo",
+                    0,
+                    "baseCombo",
+                    1,
+                    [0.5, 0.5, 0.5, 1, "opMul"],
+                    1
+                ]
+            ]),
+            WrapBaseValueType::Vec4,
+        ),
+        (
+            json!([
+                [0, 45, 0, 0],
+                [0, 90, 0, [0, 0, 30, "opAdd"], 0.5, "easeOutCubic"],
+                ["baseHeadPosition", 1, "splineCatmullRom"]
+            ]),
+            WrapBaseValueType::Quat,
+        ),
+    ];
+
+    for (value, ty) in definitions {
+        let definition = parse(value, ty, &mut context);
+        let bytes = cache::encode(&definition, 42);
+        let decoded = cache::decode(&bytes, &mut context).unwrap();
+        assert_same(&definition, &decoded, &context);
+    }
+}
+
+#[test]
+fn header_stores_type_version_and_source_hash() {
+    let mut context = BaseProviderContext::new();
+    let source = br#"[[0, 0, 0, 0], [1, 1, 1, 1]]"#;
+    let definition = BasePointDefinition::try_parse_typed_str(
+        std::str::from_utf8(source).unwrap(),
+        WrapBaseValueType::Vec3,
+        &mut context,
+    )
+    .unwrap();
+
+    let hash = cache::source_hash(source);
+    assert_eq!(hash, cache::source_hash(source));
+    assert_ne!(
+        hash,
+        cache::source_hash(br#"[[0, 0, 0, 0], [1, 1, 1, 0.5]]"#)
+    );
+
+    let header = cache::read_header(&cache::encode(&definition, hash)).unwrap();
+    assert_eq!(header.version, CACHE_VERSION);
+    assert_eq!(header.ty, WrapBaseValueType::Vec3);
+    assert_eq!(header.source_hash, hash);
+}
+
+#[test]
+fn interns_repeated_provider_keys() {
+    let mut context = BaseProviderContext::new();
+    let once = parse(
+        json!([["baseCombo", 0]]),
+        WrapBaseValueType::Float,
+        &mut context,
+    );
+    let many = parse(
+        json!([["baseCombo", 0], ["baseCombo", 0.5], ["baseCombo", 1]]),
+        WrapBaseValueType::Float,
+        &mut context,
+    );
+
+    let once = cache::encode(&once, 0);
+    let many = cache::encode(&many, 0);
+    let occurrences = |bytes: &[u8]| bytes.windows(9).filter(|w| w == b"baseCombo").count();
+    assert_eq!(occurrences(&once), 1);
+    assert_eq!(occurrences(&many), 1);
+}
+
+#[test]
+fn decoding_registers_smoothing_providers() {
+    let mut context = BaseProviderContext::new();
+    let definition = parse(
+        json!([["baseCombo.s1", 0]]),
+        WrapBaseValueType::Float,
+        &mut context,
+    );
+    let bytes = cache::encode(&definition, 0);
+
+    let mut host_context = BaseProviderContext::new();
+    let decoded = cache::decode(&bytes, &mut host_context).unwrap();
+    host_context.set_values("baseCombo", BaseValue::Float(10.0));
+
+    let (before, _) = decoded.interpolate(0.0, &host_context);
+    host_context.update_providers(0.5);
+    let (after, _) = decoded.interpolate(0.0, &host_context);
+    assert_ne!(before, after);
+}
+
+#[test]
+fn decoding_requires_custom_bases() {
+    let mut context = BaseProviderContext::new();
+    context
+        .register_base(
+            "baseSaberLength",
+            WrapBaseValueType::Float,
+            BaseValue::Float(1.0),
+        )
+        .unwrap();
+    let definition = parse(
+        json!([["baseSaberLength", 0]]),
+        WrapBaseValueType::Float,
+        &mut context,
+    );
+    let bytes = cache::encode(&definition, 0);
+
+    assert_eq!(
+        cache::decode(&bytes, &mut BaseProviderContext::new()).unwrap_err(),
+        CacheError::UnknownBase("baseSaberLength".to_string())
+    );
+    assert!(cache::decode(&bytes, &mut context).is_ok());
+}
+
+#[test]
+fn rejects_corrupt_data() {
+    let mut context = BaseProviderContext::new();
+    let definition = parse(
+        json!([[0, 0], ["baseCombo", [2, "opMul"], 1, "easeInQuad"]]),
+        WrapBaseValueType::Float,
+        &mut context,
+    );
+    let bytes = cache::encode(&definition, 0);
+
+    assert_eq!(
+        cache::decode(b"JSON", &mut context).unwrap_err(),
+        CacheError::BadMagic
+    );
+
+    let mut old = bytes.clone();
+    old[4..6].copy_from_slice(&(CACHE_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        cache::decode(&old, &mut context).unwrap_err(),
+        CacheError::UnsupportedVersion(CACHE_VERSION + 1)
+    );
+
+    let mut unknown_type = bytes.clone();
+    unknown_type[6] = 200;
+    assert_eq!(
+        cache::decode(&unknown_type, &mut context).unwrap_err(),
+        CacheError::UnknownType(200)
+    );
+
+    for len in 0..bytes.len() {
+        assert!(cache::decode(&bytes[..len], &mut context).is_err(), "{len}");
+    }
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        cache::decode(&trailing, &mut context).unwrap_err(),
+        CacheError::TrailingBytes
+    );
+}
+
+#[test]
+fn rejects_corrupt_splines() {
+    let mut context = BaseProviderContext::new();
+    for (json, ty) in [
+        (json!([[0, 0, 0, 0.125]]), WrapBaseValueType::Quat),
+        (json!([[0, 0.125]]), WrapBaseValueType::Float),
+    ] {
+        let bytes = cache::encode(&parse(json, ty, &mut context), 0);
+        // The point header is the time, easing index, spline and control count
+        let time = bytes
+            .windows(4)
+            .position(|w| w == 0.125f32.to_le_bytes())
+            .unwrap();
+
+        let mut controls = bytes.clone();
+        controls[time + 6] = 2;
+        assert_eq!(
+            cache::decode(&controls, &mut context).unwrap_err(),
+            CacheError::UnexpectedControls(2)
+        );
+
+        let mut bezier = bytes.clone();
+        bezier[time + 5] = SplineKind::Bezier as u8;
+        let expected = match ty {
+            WrapBaseValueType::Quat => CacheError::UnsupportedSpline(SplineKind::Bezier),
+            // Bezier takes two control points
+            _ => CacheError::UnexpectedControls(0),
+        };
+        assert_eq!(cache::decode(&bezier, &mut context).unwrap_err(), expected);
+    }
+}
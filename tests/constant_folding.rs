@@ -0,0 +1,152 @@
+use glam::{Quat, Vec3};
+use serde_json::json;
+use tracks_rs::{
+    base_provider_context::BaseProviderContext,
+    base_value::BaseValue,
+    point_definition::PointDefinitionLike,
+    quaternion_utils::QuaternionUtilsExt,
+    test_helpers::{
+        parse_float_point_definition, parse_quaternion_point_definition,
+        parse_vector3_point_definition,
+    },
+};
+
+#[test]
+fn static_trees_fold_but_serialize_as_written() {
+    let mut context = BaseProviderContext::new();
+    let written = json!([
+        [
+            1.0,
+            2.0,
+            3.0,
+            0.0,
+            [1.0, 1.0, 1.0, [2.0, 2.0, 2.0, "opMul"], "opAdd"],
+            [0.5, 0.5, 0.5, "opMul"]
+        ],
+        [0.0, 0.0, 0.0, 1.0]
+    ]);
+    let definition = parse_vector3_point_definition(written.clone(), &mut context);
+
+    let (value, _) = definition.interpolate(0.0, &context);
+    assert_eq!(value, Vec3::new(1.5, 2.0, 2.5));
+    assert!(!definition.has_base_provider());
+    assert_eq!(definition.to_json(), written);
+}
+
+#[test]
+fn static_parts_of_dynamic_trees() {
+    let mut context = BaseProviderContext::new();
+    let definition = parse_float_point_definition(
+        json!([[
+            "baseCombo",
+            [2, "opAdd"],
+            [3, "opSub"],
+            [4, "opMul"],
+            [8, "opDiv"],
+            ["baseMultiplier", "opMul"],
+            [1, [2, "opAdd"], "opAdd"],
+            0
+        ]]),
+        &mut context,
+    );
+    assert!(definition.has_base_provider());
+
+    for (combo, multiplier) in [(0.0, 1.0), (10.0, 2.0), (-3.5, 8.0)] {
+        context.set_values("baseCombo", BaseValue::Float(combo));
+        context.set_values("baseMultiplier", BaseValue::Float(multiplier));
+        let expected = (combo + 2.0 - 3.0) * 4.0 / 8.0 * multiplier + 3.0;
+        let (value, _) = definition.interpolate(0.0, &context);
+        assert!((value - expected).abs() < 1e-5, "{value} != {expected}");
+    }
+}
+
+#[test]
+fn op_none_replaces_dynamic_parts() {
+    let mut context = BaseProviderContext::new();
+    let definition = parse_float_point_definition(
+        json!([[
+            "baseCombo",
+            ["baseCombo", "opAdd"],
+            [5, "opNone"],
+            [1, "opAdd"],
+            ["baseMultiplier", "opMul"],
+            0
+        ]]),
+        &mut context,
+    );
+
+    context.set_values("baseCombo", BaseValue::Float(100.0));
+    context.set_values("baseMultiplier", BaseValue::Float(2.0));
+    assert_eq!(definition.interpolate(0.0, &context).0, 12.0);
+}
+
+#[test]
+fn quaternion_trees() {
+    let mut context = BaseProviderContext::new();
+    let euler = |x, y, z| Quat::from_unity_euler_degrees(Vec3::new(x, y, z));
+
+    let definition = parse_quaternion_point_definition(
+        json!([
+            [0, 10, 0, 0, [0, 20, 0, [0, 2, 0, "opMul"], "opAdd"]],
+            [0, 0, 0, 0.5, [0, 30, 0, "opRotate"], [10, 0, 0, "opAdd"]],
+            [
+                "baseHeadRotation",
+                [0, 5, 0, "opAdd"],
+                [0, 5, 0, "opAdd"],
+                1
+            ]
+        ]),
+        &mut context,
+    );
+
+    let (start, _) = definition.interpolate(0.0, &context);
+    assert!(start.abs_diff_eq(euler(0.0, 50.0, 0.0), 1e-5));
+    let (middle, _) = definition.interpolate(0.5, &context);
+    assert!(middle.abs_diff_eq(euler(10.0, 30.0, 0.0), 1e-5));
+
+    context.set_values("baseHeadRotation", BaseValue::from(euler(0.0, 45.0, 0.0)));
+    let (end, _) = definition.interpolate(1.0, &context);
+    assert!(end.abs_diff_eq(euler(0.0, 55.0, 0.0), 1e-5));
+}
+
+#[test]
+fn partly_dynamic_trees_match_the_unfolded_walk_bit_for_bit() {
+    let mut context = BaseProviderContext::new();
+    // Reassociating either of these runs would change the result
+    let dynamic_first = parse_float_point_definition(
+        json!([[
+            "baseCombo",
+            [1e8, "opSub"],
+            [1e8, "opAdd"],
+            [3, "opMul"],
+            [7, "opDiv"],
+            0
+        ]]),
+        &mut context,
+    );
+    let static_first = parse_float_point_definition(
+        json!([[
+            1e8,
+            [3, "opAdd"],
+            [3, "opAdd"],
+            [3, "opAdd"],
+            [3, "opAdd"],
+            ["baseCombo", "opAdd"],
+            [0, "opDiv"],
+            0
+        ]]),
+        &mut context,
+    );
+
+    for combo in [1.0, -2.5, 1e30, 0.1] {
+        context.set_values("baseCombo", BaseValue::Float(combo));
+
+        let expected = (combo - 1e8 + 1e8) * 3.0 / 7.0;
+        let (value, _) = dynamic_first.interpolate(0.0, &context);
+        assert_eq!(value.to_bits(), expected.to_bits(), "{value} != {expected}");
+
+        let expected = (1e8f32 + 3.0 + 3.0 + 3.0 + 3.0 + combo) / 0.0;
+        let (value, _) = static_first.interpolate(0.0, &context);
+        assert_eq!(value.to_bits(), expected.to_bits(), "{value} != {expected}");
+    }
+}
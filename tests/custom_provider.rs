@@ -0,0 +1,188 @@
+#[cfg(feature = "ffi")]
+use std::ffi::{CString, c_void};
+
+use glam::{Quat, Vec3};
+use serde_json::json;
+use smallvec::smallvec;
+use tracks_rs::base_provider_context::{BaseError, BaseProviderContext};
+use tracks_rs::base_value::{BaseValue, WrapBaseValueType};
+#[cfg(feature = "ffi")]
+use tracks_rs::ffi::base_provider_context::base_provider_context_register_ffi_provider;
+#[cfg(feature = "ffi")]
+use tracks_rs::ffi::types::WrappedValues;
+use tracks_rs::point_definition::{FloatPointDefinition, PointDefinitionLike};
+use tracks_rs::prelude::AbstractValueProvider;
+use tracks_rs::providers::ValueProvider;
+use tracks_rs::quaternion_utils::QuaternionUtilsExt;
+use tracks_rs::shared::{Shared, StateCell};
+
+#[test]
+fn closure_providers_are_sampled_on_demand() {
+    let mut ctx = BaseProviderContext::new();
+    let length = Shared::new(StateCell::new(1.0));
+    let source = length.clone();
+    ctx.register_custom_provider(
+        "baseSaberLength",
+        1,
+        false,
+        move |_: &BaseProviderContext| smallvec![*source.borrow()],
+    )
+    .unwrap();
+
+    let provider = ctx.get_value_provider("baseSaberLength");
+    assert!(matches!(provider, ValueProvider::Custom(_)));
+    assert_eq!(provider.values(&ctx).as_slice(), &[1.0]);
+
+    *length.borrow_mut() = 2.0;
+    assert_eq!(provider.values(&ctx).as_slice(), &[2.0]);
+    assert_eq!(ctx.get_values("baseSaberLength"), BaseValue::Float(2.0));
+    assert_eq!(
+        ctx.base_type("baseSaberLength"),
+        Some(WrapBaseValueType::Float)
+    );
+
+    let definition =
+        FloatPointDefinition::try_parse(&json!([["baseSaberLength", 0]]), &mut ctx).unwrap();
+    *length.borrow_mut() = 3.0;
+    assert_eq!(definition.interpolate(0.0, &ctx).0, 3.0);
+}
+
+#[test]
+fn custom_providers_can_be_swizzled_and_smoothed() {
+    let mut ctx = BaseProviderContext::new();
+    // Reads another base, so it changes when the host sets `baseSongTime`
+    ctx.register_custom_provider("baseMyThing", 3, false, |ctx: &BaseProviderContext| {
+        let time = ctx.get_values("baseSongTime").as_float().unwrap();
+        smallvec![time, time * 2.0, time * 3.0]
+    })
+    .unwrap();
+
+    let swizzled = ctx.get_value_provider("baseMyThing.zx");
+    let smoothed = ctx.get_value_provider("baseMyThing.s1");
+    ctx.set_values("baseSongTime", BaseValue::Float(2.0));
+    assert_eq!(swizzled.values(&ctx).as_slice(), &[6.0, 2.0]);
+
+    ctx.update_providers(0.5);
+    assert_eq!(smoothed.values(&ctx).as_slice(), &[1.0, 2.0, 3.0]);
+    ctx.update_providers(1.0);
+    assert_eq!(smoothed.values(&ctx).as_slice(), &[2.0, 4.0, 6.0]);
+}
+
+#[test]
+fn custom_rotations_and_declared_sizes() {
+    let mut ctx = BaseProviderContext::new();
+    ctx.register_custom_provider("baseMyRotation", 3, true, |_: &BaseProviderContext| {
+        smallvec![0.0, 90.0, 0.0]
+    })
+    .unwrap();
+    // Returns fewer values than declared
+    ctx.register_custom_provider("baseMyColor", 4, false, |_: &BaseProviderContext| {
+        smallvec![1.0, 0.5]
+    })
+    .unwrap();
+
+    let rotation = ctx.get_value_provider("baseMyRotation.s1");
+    assert!(rotation.is_rotation(&ctx));
+    assert!(matches!(
+        rotation,
+        ValueProvider::SmoothRotationProviders(_)
+    ));
+    let BaseValue::Quaternion(q) = ctx.get_values("baseMyRotation") else {
+        panic!("expected a quaternion");
+    };
+    let expected = Quat::from_unity_euler_degrees(Vec3::new(0.0, 90.0, 0.0));
+    assert!(q.abs_diff_eq(expected, 1e-5), "{q} {expected}");
+
+    let color = ctx.get_value_provider("baseMyColor");
+    assert_eq!(color.values(&ctx).as_slice(), &[1.0, 0.5, 0.0, 0.0]);
+}
+
+#[test]
+fn custom_provider_registration_is_validated() {
+    let mut ctx = BaseProviderContext::new();
+    let zero = |_: &BaseProviderContext| smallvec![0.0];
+
+    assert_eq!(
+        ctx.register_custom_provider("baseCombo", 1, false, zero),
+        Err(BaseError::AlreadyRegistered("baseCombo".to_string()))
+    );
+    assert_eq!(
+        ctx.register_custom_provider("myThing", 1, false, zero),
+        Err(BaseError::InvalidName("myThing".to_string()))
+    );
+    for (count, is_rotation) in [(2, false), (0, false), (4, true)] {
+        assert_eq!(
+            ctx.register_custom_provider("baseMyThing", count, is_rotation, zero),
+            Err(BaseError::InvalidComponentCount {
+                base: "baseMyThing".to_string(),
+                count,
+                is_rotation,
+            })
+        );
+    }
+
+    ctx.register_custom_provider("baseMyThing", 1, false, zero)
+        .unwrap();
+    assert_eq!(
+        ctx.register_base(
+            "baseMyThing",
+            WrapBaseValueType::Float,
+            BaseValue::Float(0.0)
+        ),
+        Err(BaseError::AlreadyRegistered("baseMyThing".to_string()))
+    );
+    assert_eq!(
+        ctx.try_set_values("baseMyThing", BaseValue::Float(1.0)),
+        Err(BaseError::ReadOnly("baseMyThing".to_string()))
+    );
+}
+
+#[cfg(feature = "ffi")]
+unsafe extern "C" fn ffi_fetch(
+    _ctx: &BaseProviderContext,
+    user_data: *mut c_void,
+) -> WrappedValues {
+    let values = unsafe { &*(user_data as *const [f32; 3]) };
+    WrappedValues {
+        values: values.as_ptr(),
+        length: values.len(),
+    }
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_callbacks_resolve_as_bases() {
+    let mut ctx = BaseProviderContext::new();
+    let mut values = [1.0f32, 2.0, 3.0];
+    let name = CString::new("baseMyThing").unwrap();
+
+    let registered = unsafe {
+        base_provider_context_register_ffi_provider(
+            &mut ctx,
+            name.as_ptr(),
+            Some(ffi_fetch),
+            values.as_mut_ptr() as *mut c_void,
+            3,
+            false,
+        )
+    };
+    assert!(registered);
+
+    let provider = ctx.get_value_provider("baseMyThing.s1");
+    values = [4.0, 5.0, 6.0];
+    ctx.update_providers(1.0);
+    assert_eq!(provider.values(&ctx).as_slice(), &[4.0, 5.0, 6.0]);
+
+    // Name already taken
+    let registered = unsafe {
+        base_provider_context_register_ffi_provider(
+            &mut ctx,
+            name.as_ptr(),
+            Some(ffi_fetch),
+            values.as_mut_ptr() as *mut c_void,
+            3,
+            false,
+        )
+    };
+    assert!(!registered);
+}
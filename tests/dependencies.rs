@@ -0,0 +1,133 @@
+use std::collections::BTreeSet;
+
+use serde_json::json;
+use smallvec::smallvec;
+use tracks_rs::{
+    animation::tracks_holder::TracksHolder,
+    base_provider_context::BaseProviderContext,
+    beatmap::load_custom_events,
+    point_definition::PointDefinitionLike,
+    providers::dependencies::BaseDependencies,
+    test_helpers::{
+        parse_float_point_definition, parse_quaternion_point_definition,
+        parse_vector3_point_definition,
+    },
+};
+
+fn set(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn static_definitions_have_no_dependencies() {
+    let mut context = BaseProviderContext::new();
+    let definition = parse_vector3_point_definition(
+        json!([[0, 0, 0, 0], [1, 2, 3, 1, [1, 1, 1, "opAdd"], "easeInQuad"]]),
+        &mut context,
+    );
+    assert!(definition.base_dependencies().is_empty());
+}
+
+#[test]
+fn collects_bases_through_modifiers_swizzles_and_state() {
+    let mut context = BaseProviderContext::new();
+    let definition = parse_vector3_point_definition(
+        json!([
+            ["baseHeadPosition.s2", 0],
+            [
+                "baseLeftHandPosition.zyx",
+                ["baseRightHandPosition.v", "opAdd"],
+                1
+            ],
+            [0, 0, 0, 2, [1, 1, 1, [0, "baseCombo", 0, "opAdd"], "opMul"]]
+        ]),
+        &mut context,
+    );
+    let dependencies = definition.base_dependencies();
+
+    assert_eq!(
+        dependencies.bases,
+        set(&[
+            "baseHeadPosition",
+            "baseLeftHandPosition",
+            "baseRightHandPosition",
+            "baseCombo"
+        ])
+    );
+    assert_eq!(
+        dependencies.stateful,
+        set(&["baseHeadPosition.s2", "baseRightHandPosition.v"])
+    );
+    assert!(definition.has_base_provider());
+}
+
+#[test]
+fn generators_and_custom_providers() {
+    let mut context = BaseProviderContext::new();
+    context
+        .register_custom_provider("baseBeat", 1, false, |_: &BaseProviderContext| {
+            smallvec![0.0]
+        })
+        .unwrap();
+
+    let float = parse_float_point_definition(
+        json!([["baseSine.f2", 0], [1, 1, ["baseBeat", "opMul"]]]),
+        &mut context,
+    );
+    assert_eq!(
+        float.base_dependencies().bases,
+        set(&["baseSongTime", "baseBeat"])
+    );
+
+    let rotation = parse_quaternion_point_definition(
+        json!([["baseHeadRotation.s4", 0], ["baseLeftHandRotation.av", 1]]),
+        &mut context,
+    );
+    let dependencies = rotation.base_dependencies();
+    assert_eq!(
+        dependencies.bases,
+        set(&["baseHeadRotation", "baseLeftHandRotation"])
+    );
+    assert_eq!(
+        dependencies.stateful,
+        set(&["baseHeadRotation.s4", "baseLeftHandRotation.av"])
+    );
+}
+
+#[test]
+fn whole_map_dependencies() {
+    let mut context = BaseProviderContext::new();
+    let mut tracks = TracksHolder::new();
+    let custom_data = json!({
+        "customEvents": [
+            {
+                "b": 0.0,
+                "t": "AnimateTrack",
+                "d": {
+                    "track": "a",
+                    "duration": 1.0,
+                    "position": "headFollow",
+                    "dissolve": [["baseCombo", 0]]
+                }
+            },
+            {
+                "b": 1.0,
+                "t": "AssignPathAnimation",
+                "d": {
+                    "track": "a",
+                    "color": [[1, 1, 1, 1, 0]]
+                }
+            }
+        ],
+        "pointDefinitions": {
+            "headFollow": [["baseHeadPosition.s2", 0]]
+        }
+    });
+    let loaded = load_custom_events(&custom_data, 120.0, &mut tracks, &mut context).unwrap();
+
+    let mut expected = BaseDependencies::new();
+    expected.bases = set(&["baseCombo", "baseHeadPosition"]);
+    expected.stateful = set(&["baseHeadPosition.s2"]);
+    assert_eq!(loaded.base_dependencies(), expected);
+    assert!(loaded.base_dependencies().contains_base("baseHeadPosition"));
+}
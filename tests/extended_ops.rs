@@ -0,0 +1,173 @@
+use glam::{Quat, Vec3};
+use serde_json::json;
+use tracks_rs::{
+    base_provider_context::BaseProviderContext,
+    base_value::{BaseValue, WrapBaseValueType},
+    modifiers::operation::Operation,
+    point_definition::{
+        FloatPointDefinition, PointDefinitionLike, base_point_definition::BasePointDefinition,
+        cache, parse_error::ParseErrorKind, quaternion_point_definition::QuaternionPointDefinition,
+    },
+    quaternion_utils::QuaternionUtilsExt,
+    test_helpers::{
+        parse_float_point_definition, parse_quaternion_point_definition,
+        parse_vector3_point_definition,
+    },
+};
+
+#[test]
+fn float_operations() {
+    let mut context = BaseProviderContext::new();
+    let cases = [
+        (json!([[3, [5, "opMin"], 0]]), 3.0),
+        (json!([[3, [5, "opMax"], 0]]), 5.0),
+        (json!([[-1, [3, "opMod"], 0]]), 2.0),
+        (json!([[2, [3, "opPow"], 0]]), 8.0),
+        (json!([[-2, [5, "opAbs"], 0]]), 2.0),
+        (json!([[3, [-5, "opAdd"], [0, "opAbs"], 0]]), 2.0),
+        (json!([[7, [0, 1, "opClamp"], 0]]), 1.0),
+        (json!([[-7, [0, 1, "opClamp"], 0]]), 0.0),
+        (json!([[0.25, [0, 1, "opClamp"], 0]]), 0.25),
+        (json!([[-7, [-5, -2, "opClamp"], 0]]), -5.0),
+    ];
+
+    for (definition, expected) in cases {
+        let point_definition = parse_float_point_definition(definition.clone(), &mut context);
+        let (value, _) = point_definition.interpolate(0.0, &context);
+        assert_eq!(value, expected, "{definition}");
+    }
+}
+
+#[test]
+fn clamps_head_tracked_position() {
+    let mut context = BaseProviderContext::new();
+    let point_definition = parse_vector3_point_definition(
+        json!([[
+            "baseHeadPosition",
+            [-0.5, 0, -0.5, 0.5, 2, 0.5, "opClamp"],
+            [0, 1, 0, "opMax"],
+            0
+        ]]),
+        &mut context,
+    );
+
+    context.set_values(
+        "baseHeadPosition",
+        BaseValue::Vector3(Vec3::new(1.5, 0.25, -3.0)),
+    );
+    let (value, _) = point_definition.interpolate(0.0, &context);
+    // z is clamped to -0.5, then raised to 0
+    assert_eq!(value, Vec3::new(0.5, 1.0, 0.0));
+}
+
+#[test]
+fn quaternion_operations_apply_to_euler_angles() {
+    let mut context = BaseProviderContext::new();
+    let point_definition = parse_quaternion_point_definition(
+        json!([[90, -10, 45, 0, [0, 0, 0, 30, 30, 30, "opClamp"]]]),
+        &mut context,
+    );
+
+    let (value, _) = point_definition.interpolate(0.0, &context);
+    let expected = Quat::from_unity_euler_degrees(Vec3::new(30.0, 0.0, 30.0));
+    assert!(value.abs_diff_eq(expected, 1e-5), "{value} != {expected}");
+}
+
+#[test]
+fn clamp_bounds_can_be_providers() {
+    let mut context = BaseProviderContext::new();
+    // Head position bounded between zero and the left hand's position
+    let point_definition = parse_vector3_point_definition(
+        json!([[
+            "baseHeadPosition",
+            [0, 0, 0, "baseLeftHandPosition", "opClamp"],
+            0
+        ]]),
+        &mut context,
+    );
+    assert_eq!(
+        point_definition.to_json(),
+        json!([[
+            "baseHeadPosition",
+            0.0,
+            [0.0, 0.0, 0.0, "baseLeftHandPosition", "opClamp"]
+        ]])
+    );
+
+    context.set_values(
+        "baseHeadPosition",
+        BaseValue::Vector3(Vec3::new(2.0, -1.0, 0.5)),
+    );
+    context.set_values(
+        "baseLeftHandPosition",
+        BaseValue::Vector3(Vec3::new(1.0, 1.0, 1.0)),
+    );
+    let (value, _) = point_definition.interpolate(0.0, &context);
+    assert_eq!(value, Vec3::new(1.0, 0.0, 0.5));
+}
+
+#[test]
+fn clamp_takes_both_bounds() {
+    let mut context = BaseProviderContext::new();
+
+    let error = FloatPointDefinition::try_parse(&json!([[0, [1, "opClamp"], 0]]), &mut context)
+        .unwrap_err();
+    assert_eq!(
+        error.kind,
+        ParseErrorKind::WrongComponentCount {
+            expected: 2,
+            found: 1
+        }
+    );
+
+    // A provider can't be split between the bounds
+    let error = QuaternionPointDefinition::try_parse(
+        &json!([[0, 0, 0, 0, [0, 0, "baseHeadPosition", 0, "opClamp"]]]),
+        &mut context,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.kind,
+        ParseErrorKind::WrongComponentCount {
+            expected: 3,
+            found: 5
+        }
+    );
+}
+
+#[test]
+fn round_trips_through_flags() {
+    use std::str::FromStr;
+
+    for flag in ["opMin", "opMax", "opMod", "opPow", "opAbs", "opClamp"] {
+        let operation = Operation::from_str(flag).unwrap();
+        assert_eq!(operation.as_str(), flag);
+    }
+    assert!(Operation::from_str("opSqrt").is_err());
+}
+
+#[test]
+fn clamp_round_trips_through_the_cache() {
+    let mut context = BaseProviderContext::new();
+    context.set_values("baseCombo", BaseValue::Float(3.0));
+
+    for (definition, ty) in [
+        (
+            json!([[5, [0, "baseCombo", [2, "opMul"], "opClamp"], 0]]),
+            WrapBaseValueType::Float,
+        ),
+        (
+            json!([[90, -10, 45, 0, [0, 0, 0, 30, 30, 30, "opClamp"]]]),
+            WrapBaseValueType::Quat,
+        ),
+    ] {
+        let parsed = BasePointDefinition::try_parse_typed(&definition, ty, &mut context).unwrap();
+        let decoded = cache::decode(&cache::encode(&parsed, 0), &mut context).unwrap();
+
+        assert_eq!(decoded.to_json(), parsed.to_json());
+        assert_eq!(
+            decoded.interpolate(0.0, &context),
+            parsed.interpolate(0.0, &context)
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use serde_json::json;
+use tracks_rs::base_provider_context::{BaseError, BaseProviderContext};
+use tracks_rs::base_value::{BaseValue, WrapBaseValueType};
+use tracks_rs::point_definition::validate::{DiagnosticKind, validate};
+use tracks_rs::point_definition::{FloatPointDefinition, PointDefinitionLike};
+use tracks_rs::prelude::AbstractValueProvider;
+use tracks_rs::providers::ValueProvider;
+
+fn sample(ctx: &mut BaseProviderContext, provider: &str, time: f32) -> Vec<f32> {
+    let provider = ctx.get_value_provider(provider);
+    ctx.set_values("baseSongTime", BaseValue::Float(time));
+    provider.values(ctx).to_vec()
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-4, "{actual} {expected}");
+}
+
+#[test]
+fn oscillators_follow_song_time() {
+    let mut ctx = BaseProviderContext::new();
+    assert!(matches!(
+        ctx.get_value_provider("baseSine"),
+        ValueProvider::Generator(_)
+    ));
+
+    for (time, sine, square, triangle) in [
+        (0.0, 0.0, 1.0, 0.0),
+        (0.25, 1.0, 1.0, 1.0),
+        (0.5, 0.0, -1.0, 0.0),
+        (0.75, -1.0, -1.0, -1.0),
+        (1.25, 1.0, 1.0, 1.0),
+    ] {
+        assert_close(sample(&mut ctx, "baseSine", time)[0], sine);
+        assert_close(sample(&mut ctx, "baseSquare", time)[0], square);
+        assert_close(sample(&mut ctx, "baseTriangle", time)[0], triangle);
+    }
+
+    // Twice as fast, a quarter cycle ahead
+    assert_close(sample(&mut ctx, "baseSine.f2.p0_25", 0.0)[0], 1.0);
+    assert_close(sample(&mut ctx, "baseSine.f2.p0_25", 0.25)[0], -1.0);
+}
+
+#[test]
+fn noise_is_smooth_and_seeded() {
+    let mut ctx = BaseProviderContext::new();
+
+    // Zero on every whole cycle, continuous in between
+    assert_close(sample(&mut ctx, "baseNoise", 3.0)[0], 0.0);
+    let mut last = sample(&mut ctx, "baseNoise", 0.0)[0];
+    for step in 1..=400 {
+        let value = sample(&mut ctx, "baseNoise", step as f32 / 100.0)[0];
+        assert!((-1.0..=1.0).contains(&value), "{value}");
+        assert!((value - last).abs() < 0.1, "{last} -> {value}");
+        last = value;
+    }
+
+    let a = sample(&mut ctx, "baseNoise.seed1", 0.5)[0];
+    let b = sample(&mut ctx, "baseNoise.seed2", 0.5)[0];
+    assert_ne!(a, b);
+    assert_eq!(
+        sample(&mut BaseProviderContext::new(), "baseNoise.seed1", 0.5)[0],
+        a
+    );
+
+    let channels = sample(&mut ctx, "baseNoise3", 0.5);
+    assert_eq!(channels.len(), 3);
+    assert!(channels[0] != channels[1] && channels[1] != channels[2]);
+}
+
+#[test]
+fn random_values_hold_for_a_cycle() {
+    let mut ctx = BaseProviderContext::new();
+
+    let first = sample(&mut ctx, "baseRandom.f4.seed7", 0.0)[0];
+    assert!((0.0..1.0).contains(&first));
+    assert_eq!(sample(&mut ctx, "baseRandom.f4.seed7", 0.2)[0], first);
+    assert_ne!(sample(&mut ctx, "baseRandom.f4.seed7", 0.3)[0], first);
+
+    // No frequency means a fixed value per seed
+    let fixed = sample(&mut ctx, "baseRandom3.f0.seed7", 0.0);
+    assert_eq!(fixed.len(), 3);
+    assert_eq!(sample(&mut ctx, "baseRandom3.f0.seed7", 100.0), fixed);
+}
+
+#[test]
+fn generators_compose_with_swizzles_and_smoothing() {
+    let mut ctx = BaseProviderContext::new();
+    let swizzled = ctx.get_value_provider("baseNoise3.f2.zx");
+    let smoothed = ctx.get_value_provider("baseSquare.p0_5.s1");
+    assert_eq!(swizzled.key().unwrap(), "baseNoise3.f2.zx");
+
+    ctx.set_values("baseSongTime", BaseValue::Float(0.3));
+    let noise = ctx.get_value_provider("baseNoise3.f2").values(&ctx);
+    assert_eq!(swizzled.values(&ctx).as_slice(), &[noise[2], noise[0]]);
+
+    ctx.update_providers(0.5);
+    assert_eq!(smoothed.values(&ctx).as_slice(), &[-0.5]);
+
+    let definition =
+        FloatPointDefinition::try_parse(&json!([["baseSine.f0_5", 0]]), &mut ctx).unwrap();
+    ctx.set_values("baseSongTime", BaseValue::Float(0.5));
+    assert_close(definition.interpolate(0.0, &ctx).0, 1.0);
+}
+
+#[test]
+fn generator_names_are_reserved_and_validated() {
+    let mut ctx = BaseProviderContext::new();
+    assert_eq!(
+        ctx.register_base("baseSine", WrapBaseValueType::Float, BaseValue::Float(0.0)),
+        Err(BaseError::AlreadyRegistered("baseSine".to_string()))
+    );
+
+    let diagnostics = validate(&json!([
+        ["baseNoise3.f2.seed3.xy.s0_5", 0],
+        ["baseSine.fast", 1]
+    ]));
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![DiagnosticKind::InvalidGeneratorParameter {
+            base: "baseSine.fast".to_string(),
+            param: "fast".to_string()
+        }]
+    );
+}
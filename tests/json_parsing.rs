@@ -1,9 +1,12 @@
 use serde_json::json;
 use tracks_rs::{
     base_provider_context::BaseProviderContext,
-    base_value::BaseValue,
+    base_value::{BaseValue, WrapBaseValueType},
     point_definition::{
-        PointDefinitionLike, Vector4PointDefinition, basic_point_definition::BasicPointDefinition,
+        PointDefinitionLike, Vector4PointDefinition,
+        base_point_definition::BasePointDefinition,
+        basic_point_definition::BasicPointDefinition,
+        parse_error::{JsonPath, ParseErrorKind},
         quaternion_point_definition::QuaternionPointDefinition,
         vector3_point_definition::Vector3PointDefinition,
     },
@@ -348,3 +351,307 @@ fn panics_when_float_modifier_receives_vec3_from_base_head_s10() {
         &mut context,
     );
 }
+
+#[test]
+fn try_parse_reports_wrong_modifier_component_count_with_path() {
+    let mut context = BaseProviderContext::new();
+
+    let err = Vector3PointDefinition::try_parse(
+        &json!([
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0, [2.0, 2.0, "opAdd"], 1.0]
+        ]),
+        &mut context,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::WrongComponentCount {
+            expected: 3,
+            found: 2
+        }
+    );
+    assert_eq!(err.path, JsonPath::from([1, 3].as_slice()));
+    assert_eq!(
+        err.to_string(),
+        "modifier point must have 3 numbers, found 2 at [1][3]"
+    );
+}
+
+#[test]
+fn try_parse_reports_nested_modifier_path() {
+    let mut context = BaseProviderContext::new();
+
+    let err = BasicPointDefinition::<f32>::try_parse(
+        &json!([[0.0, [1.0, [2.0, 3.0, "opMul"], "opAdd"], 0.0]]),
+        &mut context,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::WrongComponentCount {
+            expected: 1,
+            found: 2
+        }
+    );
+    assert_eq!(err.path.indices(), &[0, 1, 1]);
+}
+
+#[test]
+fn try_parse_reports_unknown_operation() {
+    let mut context = BaseProviderContext::new();
+
+    let err =
+        BasicPointDefinition::<f32>::try_parse(&json!([[0.0, [1.0, "opAddd"], 0.0]]), &mut context)
+            .unwrap_err();
+
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::UnknownOperation("opAddd".to_string())
+    );
+    assert_eq!(err.path.indices(), &[0, 1, 1]);
+}
+
+#[test]
+#[cfg(not(feature = "extended_ops"))]
+fn try_parse_rejects_extended_operations_without_the_feature() {
+    let mut context = BaseProviderContext::new();
+
+    let err = BasicPointDefinition::<f32>::try_parse(
+        &json!([[0.0, [1.0, "opClamp"], 0.0]]),
+        &mut context,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::UnknownOperation("opClamp".to_string())
+    );
+}
+
+#[test]
+fn try_parse_reports_missing_operation() {
+    let mut context = BaseProviderContext::new();
+
+    let err = BasicPointDefinition::<f32>::try_parse(&json!([[0.0, [1.0], 0.0]]), &mut context)
+        .unwrap_err();
+
+    assert_eq!(err.kind, ParseErrorKind::MissingOperation);
+    assert_eq!(err.path.indices(), &[0, 1]);
+}
+
+#[test]
+fn try_parse_reports_empty_and_non_array_input() {
+    let mut context = BaseProviderContext::new();
+
+    let err = BasicPointDefinition::<f32>::try_parse(&json!([]), &mut context).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::EmptyArray);
+    assert!(err.path.is_root());
+
+    let err = QuaternionPointDefinition::try_parse(&json!(1.0), &mut context).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::NotAnArray);
+    assert_eq!(err.to_string(), "expected an array");
+}
+
+#[test]
+fn try_parse_matches_parse_on_valid_input() {
+    let mut context = BaseProviderContext::new();
+    let js = json!([
+        [0.0, 0.0, 0.0, 0.0],
+        [1.0, 2.0, 3.0, [1.0, 1.0, 1.0, "opAdd"], 1.0, "easeInQuad"]
+    ]);
+
+    let parsed = Vector3PointDefinition::parse(js.clone(), &mut context);
+    let tried = Vector3PointDefinition::try_parse(&js, &mut context).unwrap();
+
+    assert_eq!(parsed.get_count(), tried.get_count());
+    for time in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert_eq!(
+            parsed.interpolate(time, &context),
+            tried.interpolate(time, &context)
+        );
+    }
+}
+
+#[test]
+fn to_json_writes_canonical_heck_json() {
+    let mut context = BaseProviderContext::new();
+
+    let definition = Vector3PointDefinition::parse(
+        json!([
+            [0.0, 0.1, 0.0, 0.0],
+            [
+                "baseHeadPosition.zx.s0_5",
+                1.5,
+                [
+                    1.0,
+                    2.0,
+                    3.0,
+                    ["baseCombo", "baseCombo", "baseCombo", "opMul"],
+                    "opAdd"
+                ],
+                1.0,
+                "easeInOutQuad",
+                "splineCatmullRom"
+            ]
+        ]),
+        &mut context,
+    );
+
+    assert_eq!(
+        definition.to_json(),
+        json!([
+            [0.0, 0.1, 0.0, 0.0],
+            [
+                "baseHeadPosition.zx.s0_5",
+                1.5,
+                1.0,
+                [
+                    1.0,
+                    2.0,
+                    3.0,
+                    ["baseCombo", "baseCombo", "baseCombo", "opMul"],
+                    "opAdd"
+                ],
+                "easeInOutQuad",
+                "splineCatmullRom"
+            ]
+        ])
+    );
+}
+
+#[test]
+fn to_json_round_trips_through_parse() {
+    let mut context = BaseProviderContext::new();
+    context.set_values(
+        "baseHeadRotation",
+        BaseValue::from(glam::Quat::from_unity_euler_degrees(glam::Vec3::new(
+            10.0, 20.0, 30.0,
+        ))),
+    );
+
+    let js = json!([
+        [0.0, 45.0, 0.0, 0.0],
+        [
+            "baseHeadRotation.s14",
+            [0.0, 90.0, 0.0, "opAdd"],
+            0.5,
+            "easeOutCubic"
+        ],
+        ["baseHeadRotation.yxz", 1.0]
+    ]);
+
+    let definition = QuaternionPointDefinition::parse(js, &mut context);
+    context.update_providers(1.0);
+
+    let serialized = definition.to_json();
+    let reparsed = QuaternionPointDefinition::parse(serialized.clone(), &mut context);
+
+    assert_eq!(reparsed.to_json(), serialized);
+    for time in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert_eq!(
+            definition.interpolate(time, &context),
+            reparsed.interpolate(time, &context)
+        );
+    }
+}
+
+#[test]
+fn to_json_expands_single_point_shorthand() {
+    let mut context = BaseProviderContext::new();
+
+    let float = BasicPointDefinition::<f32>::parse(json!([0.5]), &mut context);
+    assert_eq!(float.to_json(), json!([[0.5, 0.0]]));
+
+    let color = Vector4PointDefinition::parse(json!([[1, 0, 0, 1, 0]]), &mut context);
+    assert_eq!(color.to_json(), json!([[1.0, 0.0, 0.0, 1.0, 0.0]]));
+}
+
+#[test]
+fn try_parse_str_matches_try_parse() {
+    let mut context = BaseProviderContext::new();
+
+    let definitions = [
+        r#"[1, 2, 3]"#,
+        r#"[[0, 0, 0, 0], null, {"ignored": true}, [1, 2, 3, 1, "easeInOutSine"]]"#,
+        r#"[[0, 1, 0, 0], [2, 2, 2, 0.5], ["baseHeadPosition.zx.s0_5", 1.5, [1, 2, 3, "opAdd"], 1, "splineCatmullRom"]]"#,
+        r#"[["baseCombo", "baseCombo", "baseCombo", [2, 2, 2, [0.5, 0.5, 0.5, "opMul"], "opMul"], 0.5], [4, 5, 6, 1, "easeBogus"]]"#,
+        r#"[[1e2, -0.25, 18446744073709551615, true, 0]]"#,
+        r#"[["base\u0043ombo", 2, 3, 0], ["baseCombo", "easeOut\u0043ubic", 4, 5, 1]]"#,
+    ];
+
+    for text in definitions {
+        let value: serde_json::Value = serde_json::from_str(text).unwrap();
+        let from_value = Vector3PointDefinition::try_parse(&value, &mut context).unwrap();
+        let from_str = Vector3PointDefinition::try_parse_str(text, &mut context).unwrap();
+
+        assert_eq!(from_str.to_json(), from_value.to_json(), "{text}");
+        assert_eq!(from_str.get_count(), from_value.get_count(), "{text}");
+        for time in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            assert_eq!(
+                from_str.interpolate(time, &context),
+                from_value.interpolate(time, &context),
+                "{text}"
+            );
+        }
+    }
+}
+
+#[test]
+fn try_parse_str_reports_the_same_errors_as_try_parse() {
+    let mut context = BaseProviderContext::new();
+
+    let definitions = [
+        r#"1.0"#,
+        r#"[]"#,
+        r#"[[0.0, [1.0, "opAddd"], 0.0]]"#,
+        r#"[[0.0, [1.0], 0.0]]"#,
+        r#"[[0.0, [], 0.0]]"#,
+        r#"[[0.0, ["opAdd"], 0.0]]"#,
+        r#"[[0.0, [1.0, [1.0, 2.0, "opMul"], "opAdd"], 0.0]]"#,
+    ];
+
+    for text in definitions {
+        let value: serde_json::Value = serde_json::from_str(text).unwrap();
+        let from_value = BasicPointDefinition::<f32>::try_parse(&value, &mut context).unwrap_err();
+        let from_str = BasicPointDefinition::<f32>::try_parse_str(text, &mut context).unwrap_err();
+        assert_eq!(from_str, from_value, "{text}");
+    }
+}
+
+#[test]
+fn try_parse_str_reports_invalid_json() {
+    let mut context = BaseProviderContext::new();
+
+    let err = BasicPointDefinition::<f32>::try_parse_str("[[0, 1]", &mut context).unwrap_err();
+    assert!(matches!(err.kind, ParseErrorKind::InvalidJson(_)));
+    assert!(err.path.is_root());
+    assert!(err.to_string().starts_with("invalid JSON: "));
+
+    let err =
+        BasicPointDefinition::<f32>::try_parse_slice(b"[[0, 1]] x", &mut context).unwrap_err();
+    assert!(matches!(err.kind, ParseErrorKind::InvalidJson(_)));
+}
+
+#[test]
+fn try_parse_typed_str_matches_try_parse_typed() {
+    let mut context = BaseProviderContext::new();
+    let text = r#"[[0, 0, 0, 1, 0], [1, 0, 0, 1, 1, "easeInOutSine"]]"#;
+    let value: serde_json::Value = serde_json::from_str(text).unwrap();
+
+    let from_value =
+        BasePointDefinition::try_parse_typed(&value, WrapBaseValueType::Vec4, &mut context)
+            .unwrap();
+    let from_str =
+        BasePointDefinition::try_parse_typed_str(text, WrapBaseValueType::Vec4, &mut context)
+            .unwrap();
+
+    assert_eq!(from_str.get_type(), WrapBaseValueType::Vec4);
+    assert_eq!(from_str.to_json(), from_value.to_json());
+    assert_eq!(
+        from_str.interpolate(0.5, &context),
+        from_value.interpolate(0.5, &context)
+    );
+}
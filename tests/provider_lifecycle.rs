@@ -0,0 +1,103 @@
+use serde_json::json;
+use tracks_rs::base_provider_context::{BaseProviderContext, ProviderStats};
+use tracks_rs::base_value::BaseValue;
+use tracks_rs::point_definition::{FloatPointDefinition, PointDefinitionLike};
+use tracks_rs::prelude::AbstractValueProvider;
+
+fn parse(ctx: &mut BaseProviderContext, key: &str) -> FloatPointDefinition {
+    FloatPointDefinition::try_parse(&json!([[key, 0]]), ctx).unwrap()
+}
+
+#[test]
+fn smoothing_providers_are_shared_while_in_use() {
+    let mut ctx = BaseProviderContext::new();
+    let a = ctx.get_value_provider("baseCombo.s1");
+    let b = ctx.get_value_provider("baseCombo.s1");
+    // Swizzles reuse the cached smoothing provider
+    let x = ctx.get_value_provider("baseCombo.s1.x");
+
+    assert_eq!(ctx.provider_stats().updatable_alive, 1);
+
+    ctx.set_values("baseCombo", BaseValue::Float(4.0));
+    ctx.update_providers(0.5);
+    assert_eq!(a.values(&ctx).as_slice(), &[2.0]);
+    assert_eq!(b.values(&ctx).as_slice(), &[2.0]);
+    assert_eq!(x.values(&ctx).as_slice(), &[2.0]);
+}
+
+#[test]
+fn dropped_providers_stop_updating() {
+    let mut ctx = BaseProviderContext::new();
+    let first = parse(&mut ctx, "baseCombo.s1");
+    let second = parse(&mut ctx, "baseHeadPosition.s2.x");
+
+    assert_eq!(
+        ctx.provider_stats(),
+        ProviderStats {
+            // `baseHeadPosition.s2.x` is rebuilt from the cached `baseHeadPosition.s2`
+            cached: 2,
+            updatable_alive: 2,
+            updatable_registered: 2,
+        }
+    );
+
+    drop(first);
+    let stats = ctx.provider_stats();
+    assert_eq!(stats.updatable_alive, 1);
+    assert_eq!(stats.updatable_registered, 2);
+
+    ctx.prune_providers();
+    assert_eq!(
+        ctx.provider_stats(),
+        ProviderStats {
+            cached: 1,
+            updatable_alive: 1,
+            updatable_registered: 1,
+        }
+    );
+
+    // A new provider is created for the dropped key
+    let again = parse(&mut ctx, "baseCombo.s1");
+    assert_eq!(ctx.provider_stats().updatable_alive, 2);
+
+    drop((second, again));
+    ctx.update_providers(1.0);
+    assert_eq!(ctx.provider_stats().updatable_alive, 0);
+}
+
+#[test]
+fn registrations_are_pruned_automatically() {
+    let mut ctx = BaseProviderContext::new();
+    // Hundreds of maps, each using its own smoothing providers
+    for map in 0..500 {
+        let definition = parse(&mut ctx, &format!("baseCombo.s{}", map + 1));
+        drop(definition);
+    }
+
+    let stats = ctx.provider_stats();
+    assert_eq!(stats.updatable_alive, 0);
+    assert!(stats.updatable_registered <= 128, "{stats:?}");
+    assert!(stats.cached <= 128, "{stats:?}");
+}
+
+#[test]
+fn clear_providers_forgets_everything() {
+    let mut ctx = BaseProviderContext::new();
+    let definition = parse(&mut ctx, "baseCombo.s1");
+    ctx.set_values("baseCombo", BaseValue::Float(2.0));
+    ctx.update_providers(1.0);
+
+    ctx.clear_providers();
+    assert_eq!(ctx.provider_stats(), ProviderStats::default());
+
+    // Still usable, but no longer updated
+    ctx.set_values("baseCombo", BaseValue::Float(10.0));
+    ctx.update_providers(1.0);
+    assert_eq!(definition.interpolate(0.0, &ctx).0, 2.0);
+
+    // Bases and their values survive
+    assert_eq!(ctx.get_values("baseCombo"), BaseValue::Float(10.0));
+    let fresh = parse(&mut ctx, "baseCombo.s1");
+    ctx.update_providers(1.0);
+    assert_eq!(fresh.interpolate(0.0, &ctx).0, 10.0);
+}
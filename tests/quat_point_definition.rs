@@ -184,7 +184,6 @@ fn base_provider_updates_reflect_in_quaternion_definition_no_smoothing() {
     let (q_after, _last2) = def.interpolate(0.5, &ctx);
 
     // it should be 0.5 interpolation between identity and the new base quaternion, not the original one, since there is no smoothing
-    let q_after = q_after;
     quat_approx_assert(q_after, initial_quat.slerp(head_rot, 0.5), 1e-3);
 }
 
@@ -254,3 +253,189 @@ fn base_provider_updates_with_smoothing_swizzle_and_operator() {
         );
     }
 }
+
+fn euler(x: f32, y: f32, z: f32) -> Quat {
+    Quat::from_unity_euler_degrees(Vec3::new(x, y, z))
+}
+
+#[test]
+fn op_rotate_composes_quaternions() {
+    let mut ctx = BaseProviderContext::new();
+
+    let rotated =
+        QuaternionPointDefinition::parse(json!([[90, 0, 0, 0, [0, 90, 0, "opRotate"]]]), &mut ctx);
+    let (value, _) = rotated.interpolate(0.0, &ctx);
+    assert!(value.abs_diff_eq(euler(90.0, 0.0, 0.0) * euler(0.0, 90.0, 0.0), 1e-5));
+
+    // Adding the same Euler angles gives a different rotation
+    let added =
+        QuaternionPointDefinition::parse(json!([[90, 0, 0, 0, [0, 90, 0, "opAdd"]]]), &mut ctx);
+    let (euler_added, _) = added.interpolate(0.0, &ctx);
+    assert!(euler_added.abs_diff_eq(euler(90.0, 90.0, 0.0), 1e-5));
+    assert!(!value.abs_diff_eq(euler_added, 1e-3));
+    assert!(!value.abs_diff_eq(-euler_added, 1e-3));
+}
+
+#[test]
+fn op_rotate_offsets_head_rotation_in_local_space() {
+    let mut ctx = BaseProviderContext::new();
+    let head = euler(80.0, 30.0, -20.0);
+    ctx.set_values("baseHeadRotation", BaseValue::from(head));
+
+    let definition = QuaternionPointDefinition::parse(
+        json!([
+            ["baseHeadRotation", [0, 45, 0, "opRotate"], 0],
+            [
+                "baseHeadRotation",
+                [0, 45, 0, "opRotate"],
+                [0, 45, 0, "opInverseRotate"],
+                1
+            ]
+        ]),
+        &mut ctx,
+    );
+
+    let (start, _) = definition.interpolate(0.0, &ctx);
+    assert!(start.abs_diff_eq(head * euler(0.0, 45.0, 0.0), 1e-5));
+    let (end, _) = definition.interpolate(1.0, &ctx);
+    assert!(end.abs_diff_eq(head, 1e-5));
+}
+
+#[test]
+fn euler_operations_after_op_rotate_use_its_angles() {
+    let mut ctx = BaseProviderContext::new();
+    let definition = QuaternionPointDefinition::parse(
+        json!([[0, 0, 0, 0, [0, 90, 0, "opRotate"], [0, 10, 0, "opAdd"]]]),
+        &mut ctx,
+    );
+
+    let (value, _) = definition.interpolate(0.0, &ctx);
+    quat_approx_assert(value, euler(0.0, 100.0, 0.0), 1e-3);
+}
+
+#[test]
+fn rotation_operations_are_rejected_outside_rotations() {
+    use tracks_rs::point_definition::{
+        parse_error::ParseErrorKind, vector3_point_definition::Vector3PointDefinition,
+    };
+
+    let mut ctx = BaseProviderContext::new();
+    let err =
+        Vector3PointDefinition::try_parse(&json!([[0, 0, 0, 0, [0, 90, 0, "opRotate"]]]), &mut ctx)
+            .unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::RotationOnlyOperation("opRotate".to_string())
+    );
+    assert_eq!(err.path.indices(), &[0, 4]);
+}
+
+/// World-space angular velocity in radians per unit of time, from `a` to `b` over `dt`.
+fn angular_velocity(a: Quat, b: Quat, dt: f32) -> Vec3 {
+    let delta = b * a.inverse();
+    let delta = if delta.w < 0.0 { -delta } else { delta };
+    delta.to_scaled_axis() / dt
+}
+
+/// Angular velocity just before and just after `time`.
+fn velocities_around(
+    definition: &QuaternionPointDefinition,
+    ctx: &BaseProviderContext,
+    time: f32,
+) -> (Vec3, Vec3) {
+    let h = 1e-3;
+    let sample = |t: f32| definition.interpolate(t, ctx).0;
+    (
+        angular_velocity(sample(time - 2.0 * h), sample(time - h), h),
+        angular_velocity(sample(time + h), sample(time + 2.0 * h), h),
+    )
+}
+
+#[test]
+fn smooth_rotations_pass_through_points() {
+    let mut ctx = BaseProviderContext::new();
+    let definition = QuaternionPointDefinition::parse(
+        json!([
+            [0, 0, 0, 0],
+            [0, 90, 0, 1, "splineCatmullRom"],
+            [45, 90, 0, 2, "splineCatmullRom"],
+            [45, 180, 30, 3, "splineCatmullRom"]
+        ]),
+        &mut ctx,
+    );
+    // The flag is kept when serializing
+    assert_eq!(
+        definition.to_json()[1],
+        json!([0.0, 90.0, 0.0, 1.0, "splineCatmullRom"])
+    );
+
+    for (time, expected) in [
+        (0.0, euler(0.0, 0.0, 0.0)),
+        (1.0, euler(0.0, 90.0, 0.0)),
+        (2.0, euler(45.0, 90.0, 0.0)),
+        (3.0, euler(45.0, 180.0, 30.0)),
+    ] {
+        let (value, _) = definition.interpolate(time, &ctx);
+        quat_approx_assert(value, expected, 1e-2);
+    }
+    // The spline leaves the slerp path between points
+    let (smooth, _) = definition.interpolate(1.5, &ctx);
+    not_quat_approx_assert(
+        smooth,
+        euler(0.0, 90.0, 0.0).slerp(euler(45.0, 90.0, 0.0), 0.5),
+        0.1,
+    );
+}
+
+#[test]
+fn smooth_rotations_have_continuous_angular_velocity() {
+    let mut ctx = BaseProviderContext::new();
+    let points = json!([
+        [0, 0, 0, 0],
+        [0, 90, 0, 1],
+        [60, 90, 0, 2],
+        [60, 150, 45, 3],
+        [0, 200, 0, 4]
+    ]);
+    let with_flag = |flag: &str| {
+        let mut points = points.clone();
+        for point in points.as_array_mut().unwrap() {
+            point.as_array_mut().unwrap().push(flag.into());
+        }
+        points
+    };
+    let smooth = QuaternionPointDefinition::parse(with_flag("splineCatmullRom"), &mut ctx);
+    let slerp = QuaternionPointDefinition::parse(with_flag("easeLinear"), &mut ctx);
+
+    for time in [1.0, 2.0, 3.0] {
+        let (before, after) = velocities_around(&smooth, &ctx, time);
+        assert!(
+            (before - after).length() < 0.02 * before.length(),
+            "angular velocity jumps at {time}: {before} vs {after}"
+        );
+
+        // Plain slerp turns sharply at every point
+        let (before, after) = velocities_around(&slerp, &ctx, time);
+        assert!(
+            (before - after).length() > 0.5 * before.length(),
+            "slerp unexpectedly smooth at {time}: {before} vs {after}"
+        );
+    }
+}
+
+#[test]
+fn smooth_rotations_take_the_short_way_round() {
+    let mut ctx = BaseProviderContext::new();
+    let definition = QuaternionPointDefinition::parse(
+        json!([
+            [0, 170, 0, 0],
+            [0, -170, 0, 1, "splineCatmullRom"],
+            [0, -150, 0, 2, "splineCatmullRom"]
+        ]),
+        &mut ctx,
+    );
+
+    // 20 degrees through 180, not 340 through 0
+    let (value, _) = definition.interpolate(0.5, &ctx);
+    assert!(value.angle_between(euler(0.0, 180.0, 0.0)) < 5f32.to_radians());
+}
@@ -0,0 +1,246 @@
+use glam::{Quat, Vec3};
+use serde_json::json;
+use tracks_rs::animation::coroutine_manager::CoroutineManager;
+use tracks_rs::animation::events::{EventData, EventType};
+use tracks_rs::animation::track::{Track, V2_POSITION, ValuePropertyHandle};
+use tracks_rs::animation::tracks_holder::{TrackKey, TracksHolder};
+use tracks_rs::base_provider_context::BaseProviderContext;
+use tracks_rs::base_value::{BaseValue, WrapBaseValueType};
+use tracks_rs::easings::functions::Functions;
+#[cfg(feature = "ffi")]
+use tracks_rs::ffi::base_provider_context::{
+    base_provider_context_start_recording, base_provider_context_stop_recording,
+    tracks_free_recording,
+};
+use tracks_rs::recording::{
+    RECORDING_VERSION, RecordedEvent, Recording, RecordingError, ReplayFrame, ReplayPlayer,
+};
+use tracks_rs::test_helpers::parse_vector3_point_definition;
+
+/// Track and event animating its position from the smoothed head position and a custom base.
+fn setup(context: &mut BaseProviderContext) -> (TracksHolder, TrackKey, EventData) {
+    let mut holder = TracksHolder::new();
+    let track = holder.add_track(Track::default());
+    let event = EventData {
+        raw_duration: 8.0,
+        easing: Functions::EaseLinear,
+        repeat: 0,
+        start_song_time: 0.0,
+        property: EventType::AnimateTrack(ValuePropertyHandle::new(V2_POSITION)),
+        track_key: track,
+        point_data: Some(
+            parse_vector3_point_definition(
+                json!([
+                    ["baseHeadPosition.d6", 0],
+                    [
+                        "baseHeadPosition.zyx",
+                        [0, "baseSaberLength", 0, "opAdd"],
+                        1
+                    ]
+                ]),
+                context,
+            )
+            .into(),
+        ),
+    };
+    (holder, track, event)
+}
+
+fn position(holder: &TracksHolder, track: TrackKey) -> Option<BaseValue> {
+    holder
+        .get_track(track)?
+        .properties
+        .get_by_handle(&ValuePropertyHandle::new(V2_POSITION))?
+        .get_value()
+}
+
+#[test]
+fn replays_match_the_recorded_run() {
+    let mut context = BaseProviderContext::new();
+    context
+        .register_base(
+            "baseSaberLength",
+            WrapBaseValueType::Float,
+            BaseValue::Float(1.0),
+        )
+        .unwrap();
+    context.set_values("baseCombo", BaseValue::Float(12.0));
+    let (mut holder, track, event) = setup(&mut context);
+    let mut manager = CoroutineManager::default();
+
+    context.start_recording();
+    manager.start_event_coroutine(120.0, 0.0, &context, &mut holder, event);
+    let mut live = Vec::new();
+    for frame in 0..90 {
+        let time = frame as f32 / 72.0;
+        context.set_values("baseSongTime", BaseValue::Float(time));
+        context.set_values(
+            "baseHeadPosition",
+            BaseValue::Vector3(Vec3::new(time.sin(), 1.7, time.cos())),
+        );
+        if frame % 10 == 0 {
+            context.set_values("baseSaberLength", BaseValue::Float(1.0 + time));
+        }
+        context.update_providers(1.0 / 72.0);
+        manager.poll_events(time, &context, &mut holder);
+        live.push(position(&holder, track));
+    }
+    let recording = context.stop_recording().unwrap();
+    assert!(!context.is_recording());
+    assert_eq!(recording.frame_count(), 90);
+
+    let recording = Recording::decode(&recording.encode()).unwrap();
+
+    // Fresh context that doesn't know `baseSaberLength` yet
+    let mut replay_context = BaseProviderContext::new();
+    let mut player = ReplayPlayer::new(recording);
+    // The snapshot registers custom bases before the event is parsed
+    assert_eq!(
+        player.step(&mut replay_context).unwrap(),
+        Some(ReplayFrame {
+            frame: 0,
+            delta: Some(1.0 / 72.0)
+        })
+    );
+    assert_eq!(
+        replay_context.get_values("baseCombo"),
+        BaseValue::Float(12.0)
+    );
+
+    let mut replay_context = BaseProviderContext::new();
+    replay_context
+        .register_base(
+            "baseSaberLength",
+            WrapBaseValueType::Float,
+            BaseValue::Float(0.0),
+        )
+        .unwrap();
+    let (mut holder, track, event) = setup(&mut replay_context);
+    let mut manager = CoroutineManager::default();
+    let mut player = ReplayPlayer::new(player.recording().clone());
+    manager.start_event_coroutine(120.0, 0.0, &replay_context, &mut holder, event);
+
+    let mut replayed = Vec::new();
+    while let Some(frame) = player.step(&mut replay_context).unwrap() {
+        assert!(frame.delta.is_some());
+        let time = replay_context
+            .get_values("baseSongTime")
+            .as_float()
+            .unwrap();
+        manager.poll_events(time, &replay_context, &mut holder);
+        replayed.push(position(&holder, track));
+    }
+    assert!(player.is_finished());
+    assert_eq!(replayed, live);
+}
+
+#[test]
+fn events_are_tagged_with_frames() {
+    let mut context = BaseProviderContext::new();
+    context.set_values("baseCombo", BaseValue::Float(1.0));
+    context.update_providers(0.1);
+
+    context.start_recording();
+    let snapshot = context.base_names().count();
+    context.set_values("baseCombo", BaseValue::Float(2.0));
+    context.update_providers(0.25);
+    context.update_providers(0.5);
+    context.set_values(
+        "baseHeadRotation",
+        BaseValue::Quaternion(Quat::from_rotation_y(1.0)),
+    );
+    let recording = context.stop_recording().unwrap();
+
+    let events = &recording.events()[snapshot..];
+    let base = |name: &str| recording.bases().iter().position(|b| b == name).unwrap();
+    assert_eq!(
+        events,
+        &[
+            RecordedEvent::Set {
+                frame: 0,
+                base: base("baseCombo"),
+                value: BaseValue::Float(2.0)
+            },
+            RecordedEvent::Update {
+                frame: 0,
+                delta: 0.25
+            },
+            RecordedEvent::Update {
+                frame: 1,
+                delta: 0.5
+            },
+            RecordedEvent::Set {
+                frame: 2,
+                base: base("baseHeadRotation"),
+                value: BaseValue::Quaternion(Quat::from_rotation_y(1.0))
+            },
+        ]
+    );
+    assert_eq!(Recording::decode(&recording.encode()).unwrap(), recording);
+
+    // The last frame never ended
+    let mut player = ReplayPlayer::new(recording);
+    let mut replay_context = BaseProviderContext::new();
+    let mut frames = vec![];
+    while let Some(frame) = player.step(&mut replay_context).unwrap() {
+        frames.push(frame);
+    }
+    assert_eq!(
+        frames.last(),
+        Some(&ReplayFrame {
+            frame: 2,
+            delta: None
+        })
+    );
+    assert_eq!(
+        replay_context.get_values("baseHeadRotation"),
+        BaseValue::Quaternion(Quat::from_rotation_y(1.0))
+    );
+}
+
+#[test]
+fn rejects_corrupt_recordings() {
+    let mut context = BaseProviderContext::new();
+    context.start_recording();
+    context.update_providers(0.1);
+    let bytes = context.stop_recording().unwrap().encode();
+
+    assert_eq!(
+        Recording::decode(b"TRPD").unwrap_err(),
+        RecordingError::BadMagic
+    );
+    let mut old = bytes.clone();
+    old[4..6].copy_from_slice(&(RECORDING_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        Recording::decode(&old).unwrap_err(),
+        RecordingError::UnsupportedVersion(RECORDING_VERSION + 1)
+    );
+    for len in 0..bytes.len() {
+        assert!(Recording::decode(&bytes[..len]).is_err(), "{len}");
+    }
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        Recording::decode(&trailing).unwrap_err(),
+        RecordingError::TrailingBytes
+    );
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_recordings_decode() {
+    let mut context = BaseProviderContext::new();
+    let mut len = 0;
+    unsafe {
+        assert!(base_provider_context_stop_recording(&mut context, &mut len).is_null());
+        base_provider_context_start_recording(&mut context);
+    }
+    context.set_values("baseCombo", BaseValue::Float(5.0));
+    context.update_providers(0.5);
+
+    let data = unsafe { base_provider_context_stop_recording(&mut context, &mut len) };
+    assert!(!data.is_null());
+    let recording = Recording::decode(unsafe { std::slice::from_raw_parts(data, len) }).unwrap();
+    unsafe { tracks_free_recording(data, len) };
+    assert_eq!(recording.frame_count(), 1);
+}
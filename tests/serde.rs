@@ -0,0 +1,215 @@
+use glam::{Quat, Vec3, Vec4};
+use serde::de::DeserializeSeed;
+use serde_json::json;
+use tracks_rs::{
+    animation::{
+        events::{EventData, EventDataSeed, EventType},
+        track::{PathPropertyHandle, PropertyNames, ValuePropertyHandle},
+        tracks_holder::TracksHolder,
+    },
+    base_provider_context::BaseProviderContext,
+    base_value::{BaseValue, WrapBaseValueType},
+    beatmap::load_custom_events,
+    easings::functions::Functions,
+    modifiers::operation::Operation,
+    point_definition::PointDefinitionLike,
+};
+
+fn round_trip<T>(value: &T) -> T
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    serde_json::from_value(serde_json::to_value(value).unwrap()).unwrap()
+}
+
+#[test]
+fn base_values_round_trip() {
+    let values = [
+        BaseValue::Float(0.5),
+        BaseValue::Vector3(Vec3::new(1.0, 2.0, 3.0)),
+        BaseValue::Vector4(Vec4::new(1.0, 0.0, 0.0, 1.0)),
+        BaseValue::Quaternion(Quat::from_rotation_y(1.0)),
+    ];
+    for value in values {
+        assert_eq!(round_trip(&value), value);
+    }
+
+    assert_eq!(
+        serde_json::to_value(BaseValue::Vector3(Vec3::new(1.0, 2.0, 3.0))).unwrap(),
+        json!({ "Vector3": [1.0, 2.0, 3.0] })
+    );
+
+    for ty in [
+        WrapBaseValueType::Vec3,
+        WrapBaseValueType::Quat,
+        WrapBaseValueType::Vec4,
+        WrapBaseValueType::Float,
+        WrapBaseValueType::Unknown,
+    ] {
+        assert_eq!(round_trip(&ty), ty);
+    }
+}
+
+#[test]
+fn easings_and_operations_use_heck_names() {
+    assert_eq!(
+        serde_json::to_value(Functions::EaseInOutSine).unwrap(),
+        json!("easeInOutSine")
+    );
+    assert_eq!(
+        serde_json::from_value::<Functions>(json!("easeOutBounce")).unwrap(),
+        Functions::EaseOutBounce
+    );
+    assert!(serde_json::from_value::<Functions>(json!("EaseOutBounce")).is_err());
+
+    assert_eq!(
+        serde_json::to_value(Operation::Mul).unwrap(),
+        json!("opMul")
+    );
+    assert_eq!(round_trip(&Operation::None), Operation::None);
+    let err = serde_json::from_value::<Operation>(json!("opBogus")).unwrap_err();
+    assert!(err.to_string().contains("unknown operation \"opBogus\""));
+}
+
+#[test]
+fn property_names_use_display_names() {
+    assert_eq!(
+        serde_json::to_value(PropertyNames::OffsetPosition).unwrap(),
+        json!("offsetPosition")
+    );
+    assert_eq!(
+        serde_json::from_value::<PropertyNames>(json!("localRotation")).unwrap(),
+        PropertyNames::LocalRotation
+    );
+    assert_eq!(
+        round_trip(&PropertyNames::Cuttable),
+        PropertyNames::Cuttable
+    );
+    assert_eq!(
+        round_trip(&PropertyNames::UnknownPropertyName),
+        PropertyNames::UnknownPropertyName
+    );
+
+    let handles = [
+        ValuePropertyHandle::ById(PropertyNames::Dissolve),
+        ValuePropertyHandle::ByName("myProperty".to_string()),
+    ];
+    for handle in handles {
+        assert_eq!(round_trip(&handle), handle);
+    }
+    assert_eq!(
+        serde_json::to_value(PathPropertyHandle::ById(PropertyNames::Position)).unwrap(),
+        json!({ "ById": "position" })
+    );
+}
+
+#[test]
+fn event_data_round_trips() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+
+    let custom_data = json!({
+        "customEvents": [
+            {
+                "b": 2,
+                "t": "AnimateTrack",
+                "d": {
+                    "track": "a",
+                    "duration": 4,
+                    "easing": "easeInQuad",
+                    "repeat": 1,
+                    "position": [[0, 0, 0, 0], [1, 2, 3, 1, "easeOutCubic"]],
+                    "dissolve": null
+                }
+            }
+        ]
+    });
+    let events = load_custom_events(&custom_data, 120.0, &mut tracks, &mut context)
+        .unwrap()
+        .events;
+
+    for event in &events {
+        let serialized = serde_json::to_value(event).unwrap();
+        let deserialized: EventData = serde_json::from_value(serialized.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&deserialized).unwrap(), serialized);
+        assert_eq!(deserialized.raw_duration, event.raw_duration);
+        assert_eq!(deserialized.easing, event.easing);
+        assert_eq!(deserialized.repeat, event.repeat);
+        assert_eq!(deserialized.start_song_time, event.start_song_time);
+        assert_eq!(deserialized.property, event.property);
+        assert_eq!(deserialized.track_key, event.track_key);
+        assert_eq!(
+            deserialized.point_data.is_some(),
+            event.point_data.is_some()
+        );
+        if let (Some(original), Some(copy)) = (&event.point_data, &deserialized.point_data) {
+            assert_eq!(copy.get_type(), original.get_type());
+            assert_eq!(
+                copy.interpolate(0.5, &context),
+                original.interpolate(0.5, &context)
+            );
+        }
+    }
+
+    let position = events
+        .iter()
+        .find(|e| {
+            e.property
+                == EventType::AnimateTrack(ValuePropertyHandle::ById(PropertyNames::Position))
+        })
+        .unwrap();
+    let serialized = serde_json::to_value(position).unwrap();
+    assert_eq!(serialized["easing"], json!("easeInQuad"));
+    assert_eq!(
+        serialized["point_data"],
+        json!({
+            "type": "Vec3",
+            "points": [[0.0, 0.0, 0.0, 0.0], [1.0, 2.0, 3.0, 1.0, "easeOutCubic"]]
+        })
+    );
+}
+
+#[test]
+fn event_data_seed_registers_providers_in_context() {
+    let mut tracks = TracksHolder::new();
+    let mut context = BaseProviderContext::new();
+    let custom_data = json!({
+        "customEvents": [
+            { "b": 0, "t": "AnimateTrack", "d": { "track": "a", "dissolve": [["baseCombo.s1", 0]] } }
+        ]
+    });
+    let event = load_custom_events(&custom_data, 60.0, &mut tracks, &mut context)
+        .unwrap()
+        .events
+        .remove(0);
+    let serialized = serde_json::to_string(&event).unwrap();
+
+    let mut host_context = BaseProviderContext::new();
+    host_context.set_values("baseCombo", BaseValue::Float(10.0));
+    let event = EventDataSeed(&mut host_context)
+        .deserialize(&mut serde_json::Deserializer::from_str(&serialized))
+        .unwrap();
+
+    let point_data = event.point_data.unwrap();
+    let (before, _) = point_data.interpolate(0.0, &host_context);
+    host_context.update_providers(0.5);
+    let (after, _) = point_data.interpolate(0.0, &host_context);
+    assert_ne!(before, after);
+}
+
+#[test]
+fn event_data_rejects_bad_point_definitions() {
+    let value = json!({
+        "raw_duration": 1.0,
+        "easing": "easeLinear",
+        "repeat": 0,
+        "start_song_time": 0.0,
+        "property": { "AnimateTrack": { "ById": "dissolve" } },
+        "track_key": { "idx": 0, "version": 1 },
+        "point_data": { "type": "Float", "points": [[0, [1, "opBogus"], 0]] }
+    });
+
+    let err = serde_json::from_value::<EventData>(value).unwrap_err();
+    assert!(err.to_string().contains("unknown operation"), "{err}");
+}
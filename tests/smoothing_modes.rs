@@ -0,0 +1,126 @@
+use glam::{Quat, Vec3};
+use serde_json::json;
+use tracks_rs::base_provider_context::BaseProviderContext;
+use tracks_rs::base_value::BaseValue;
+use tracks_rs::point_definition::validate::{DiagnosticKind, validate};
+use tracks_rs::prelude::AbstractValueProvider;
+use tracks_rs::quaternion_utils::QuaternionUtilsExt;
+
+/// Samples `provider` after following a jump of `baseCombo` to 10 for one second at `rate` Hz.
+fn run_float(provider: &str, rate: u32) -> f32 {
+    let mut ctx = BaseProviderContext::new();
+    let provider = ctx.get_value_provider(provider);
+    ctx.set_values("baseCombo", BaseValue::Float(10.0));
+    for _ in 0..rate {
+        ctx.update_providers(1.0 / rate as f32);
+    }
+    provider.values(&ctx)[0]
+}
+
+fn run_rotation(provider: &str, rate: u32, target: Quat) -> Quat {
+    let mut ctx = BaseProviderContext::new();
+    let provider = ctx.get_value_provider(provider);
+    ctx.set_values("baseHeadRotation", BaseValue::Quaternion(target));
+    for _ in 0..rate {
+        ctx.update_providers(1.0 / rate as f32);
+    }
+    let values = provider.values(&ctx);
+    Quat::from_unity_euler_degrees(Vec3::new(values[0], values[1], values[2]))
+}
+
+#[test]
+fn exponential_smoothing_ignores_frame_rate() {
+    let at_72 = run_float("baseCombo.e2", 72);
+    let at_144 = run_float("baseCombo.e2", 144);
+    let expected = 10.0 * (1.0 - (-2.0f32).exp());
+    assert!((at_72 - expected).abs() < 1e-3, "{at_72} {expected}");
+    assert!((at_144 - expected).abs() < 1e-3, "{at_144} {expected}");
+
+    // The legacy spec covers a different distance per second at each rate
+    assert!((run_float("baseCombo.s2", 144) - run_float("baseCombo.s2", 72)).abs() > 0.01);
+}
+
+#[test]
+fn spring_smoothing_ignores_frame_rate_and_does_not_overshoot() {
+    let at_72 = run_float("baseCombo.d4", 72);
+    let at_144 = run_float("baseCombo.d4", 144);
+    // x(t) = target * (1 - (1 + wt) e^-wt) for a spring starting at rest
+    let expected = 10.0 * (1.0 - 5.0 * (-4.0f32).exp());
+    assert!((at_72 - expected).abs() < 1e-3, "{at_72} {expected}");
+    assert!((at_144 - expected).abs() < 1e-3, "{at_144} {expected}");
+
+    let mut ctx = BaseProviderContext::new();
+    let provider = ctx.get_value_provider("baseCombo.d8");
+    ctx.set_values("baseCombo", BaseValue::Float(10.0));
+    let mut last = 0.0;
+    for _ in 0..144 {
+        ctx.update_providers(1.0 / 144.0);
+        let value = provider.values(&ctx)[0];
+        assert!(value >= last && value <= 10.0, "{last} -> {value}");
+        last = value;
+    }
+    assert!((last - 10.0).abs() < 0.05, "{last}");
+}
+
+#[test]
+fn rotation_smoothing_modes() {
+    let target = Quat::from_unity_euler_degrees(Vec3::new(20.0, 90.0, 0.0));
+
+    for spec in ["baseHeadRotation.e2", "baseHeadRotation.d4"] {
+        let at_72 = run_rotation(spec, 72, target);
+        let at_144 = run_rotation(spec, 144, target);
+        assert!(at_72.abs_diff_eq(at_144, 1e-3), "{spec}: {at_72} {at_144}");
+        // Partway there, on the path from identity to the target
+        let angle = at_72.angle_between(Quat::IDENTITY);
+        assert!(angle > 0.1 && angle < target.angle_between(Quat::IDENTITY));
+    }
+
+    let settled = run_rotation("baseHeadRotation.d20", 72, target);
+    assert!(settled.angle_between(target) < 1e-3, "{settled} {target}");
+}
+
+#[test]
+fn negative_and_non_finite_multipliers_fall_back_to_one() {
+    let expected = run_float("baseCombo.d1", 72);
+    for spec in ["baseCombo.d-1", "baseCombo.dinf", "baseCombo.eNaN"] {
+        let value = run_float(spec, 72);
+        assert!(value.is_finite(), "{spec}: {value}");
+    }
+    assert_eq!(run_float("baseCombo.d-1", 72), expected);
+    assert_eq!(run_float("baseCombo.dinf", 72), expected);
+
+    let target = Quat::from_unity_euler_degrees(Vec3::new(20.0, 90.0, 0.0));
+    let expected = run_rotation("baseHeadRotation.d1", 72, target);
+    for spec in ["baseHeadRotation.d-1", "baseHeadRotation.dinf"] {
+        let rotation = run_rotation(spec, 72, target);
+        assert!(rotation.is_finite(), "{spec}: {rotation}");
+        assert!(rotation.abs_diff_eq(expected, 1e-6), "{spec}: {rotation}");
+    }
+}
+
+#[test]
+fn validates_new_smoothing_specs() {
+    let diagnostics = validate(&json!([
+        ["baseCombo.e0_5", 0],
+        ["baseHeadPosition.x.d10", 0.5],
+        ["baseCombo.dfast", 1],
+        ["baseCombo.eNaN", 1],
+        ["baseCombo.d-1", 1],
+        ["baseCombo.dinf", 1]
+    ]));
+
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    let invalid = |base: &str, spec: &str| DiagnosticKind::InvalidSmoothing {
+        base: base.to_string(),
+        spec: spec.to_string(),
+    };
+    assert_eq!(
+        kinds,
+        vec![
+            invalid("baseCombo.dfast", "dfast"),
+            invalid("baseCombo.eNaN", "eNaN"),
+            invalid("baseCombo.d-1", "d-1"),
+            invalid("baseCombo.dinf", "dinf"),
+        ]
+    );
+}
@@ -0,0 +1,293 @@
+use glam::{Vec3, Vec4};
+use serde_json::json;
+use tracks_rs::{
+    base_provider_context::BaseProviderContext,
+    base_value::BaseValue,
+    point_definition::{
+        PointDefinitionLike,
+        basic_point_definition::BasicPointDefinition,
+        parse_error::ParseErrorKind,
+        quaternion_point_definition::QuaternionPointDefinition,
+        validate::{DiagnosticKind, validate},
+    },
+    test_helpers::{
+        interpolate_float_point_definition, interpolate_vector3_point_definition,
+        interpolate_vector4_point_definition, parse_float_point_definition,
+        parse_vector3_point_definition, parse_vector4_point_definition,
+    },
+};
+
+fn sample(json: serde_json::Value, time: f32) -> f32 {
+    let mut context = BaseProviderContext::new();
+    let definition = parse_float_point_definition(json, &mut context);
+    interpolate_float_point_definition(&definition, time, &context).0
+}
+
+#[test]
+fn bezier_uses_control_points() {
+    let definition = json!([
+        [0, 0],
+        [
+            1,
+            1,
+            [1, "splineControl"],
+            [1, "splineControl"],
+            "splineBezier"
+        ]
+    ]);
+
+    assert_eq!(sample(definition.clone(), 0.0), 0.0);
+    assert_eq!(sample(definition.clone(), 1.0), 1.0);
+    // (p1 + 3 c1 + 3 c2 + p2) / 8
+    assert!((sample(definition, 0.5) - 0.875).abs() < 1e-6);
+}
+
+#[test]
+fn bezier_controls_read_bases() {
+    let mut context = BaseProviderContext::new();
+    context.set_values(
+        "baseHeadPosition",
+        BaseValue::Vector3(Vec3::new(0.0, 4.0, 0.0)),
+    );
+    let definition = parse_vector3_point_definition(
+        json!([
+            [0, 0, 0, 0],
+            [
+                2,
+                0,
+                0,
+                1,
+                ["baseHeadPosition", "splineControl"],
+                [2, 0, 0, ["baseHeadPosition", "opAdd"], "splineControl"],
+                "splineBezier"
+            ]
+        ]),
+        &mut context,
+    );
+    assert!(definition.has_base_provider());
+    assert!(
+        definition
+            .base_dependencies()
+            .contains_base("baseHeadPosition")
+    );
+
+    let (mid, _) = interpolate_vector3_point_definition(&definition, 0.5, &context);
+    assert!(mid.abs_diff_eq(Vec3::new(1.0, 3.0, 0.0), 1e-5), "{mid}");
+
+    context.set_values("baseHeadPosition", BaseValue::Vector3(Vec3::ZERO));
+    let (mid, _) = interpolate_vector3_point_definition(&definition, 0.5, &context);
+    assert!(mid.abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-5), "{mid}");
+}
+
+#[test]
+fn hermite_follows_tangents() {
+    // Zero tangents give smoothstep
+    let flat = json!([
+        [0, 0],
+        [
+            1,
+            1,
+            [0, "splineControl"],
+            [0, "splineControl"],
+            "splineHermite"
+        ]
+    ]);
+    assert!((sample(flat, 0.25) - 0.15625).abs() < 1e-6);
+
+    // Tangents matching the segment give a straight line
+    let straight = json!([
+        [0, 0],
+        [
+            1,
+            1,
+            [1, "splineControl"],
+            [1, "splineControl"],
+            "splineHermite"
+        ]
+    ]);
+    for time in [0.1, 0.25, 0.5, 0.8] {
+        assert!((sample(straight.clone(), time) - time).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn centripetal_catmull_rom_matches_uniform_for_even_spacing() {
+    let points = |spline: &str| {
+        json!([
+            [0, 0, 0, 0],
+            [1, 1, 0, 0.33, spline],
+            [2, 0, 0, 0.66, spline],
+            [3, 1, 0, 1, spline]
+        ])
+    };
+    let mut context = BaseProviderContext::new();
+    let uniform = parse_vector3_point_definition(points("splineCatmullRom"), &mut context);
+    let centripetal =
+        parse_vector3_point_definition(points("splineCatmullRomCentripetal"), &mut context);
+
+    for time in [0.1, 0.2, 0.4, 0.5, 0.6, 0.8, 0.9] {
+        let (a, _) = interpolate_vector3_point_definition(&uniform, time, &context);
+        let (b, _) = interpolate_vector3_point_definition(&centripetal, time, &context);
+        assert!(a.abs_diff_eq(b, 1e-5), "{time}: {a} != {b}");
+    }
+}
+
+#[test]
+fn centripetal_catmull_rom_does_not_overshoot_close_points() {
+    let points = |spline: &str| {
+        json!([
+            [0, 0],
+            [10, 0.33, spline],
+            [10.1, 0.66, spline],
+            [20, 1, spline]
+        ])
+    };
+    let overshoots = |spline: &str| {
+        let mut context = BaseProviderContext::new();
+        let definition = parse_float_point_definition(points(spline), &mut context);
+        (1..10).map(|i| 0.33 + 0.033 * i as f32).any(|time| {
+            let (value, _) = interpolate_float_point_definition(&definition, time, &context);
+            !(10.0..=10.1).contains(&value)
+        })
+    };
+
+    assert!(overshoots("splineCatmullRom"));
+    assert!(!overshoots("splineCatmullRomCentripetal"));
+}
+
+#[test]
+fn b_spline_meets_run_ends_and_joins_smoothly() {
+    let mut context = BaseProviderContext::new();
+    let definition = parse_vector4_point_definition(
+        json!([
+            [0, 0, 0, 0, 0],
+            [1, 2, 0, 1, 0.25, "splineBSpline"],
+            [2, 0, 1, 1, 0.5, "splineBSpline"],
+            [3, 2, 1, 0, 0.75, "splineBSpline"],
+            [4, 0, 0, 0, 1]
+        ]),
+        &mut context,
+    );
+    let at = |time: f32| interpolate_vector4_point_definition(&definition, time, &context).0;
+
+    // The run starts at the first point and only passes near the points inside it
+    assert!(at(0.0).abs_diff_eq(Vec4::ZERO, 1e-6));
+    assert!(at(0.001).abs_diff_eq(Vec4::ZERO, 0.01));
+    let inside = (Vec4::new(0.0, 0.0, 0.0, 0.0)
+        + Vec4::new(1.0, 2.0, 0.0, 1.0) * 4.0
+        + Vec4::new(2.0, 0.0, 1.0, 1.0))
+        / 6.0;
+    assert!(at(0.25).abs_diff_eq(inside, 1e-5), "{}", at(0.25));
+    // The run ends on the point before the linear segment
+    assert!(at(0.75).abs_diff_eq(Vec4::new(3.0, 2.0, 1.0, 0.0), 1e-5));
+
+    // No jumps or kinks where segments meet
+    let step = 1e-4;
+    for join in [0.25, 0.5] {
+        let before = (at(join) - at(join - step)) / step;
+        let after = (at(join + step) - at(join)) / step;
+        assert!(
+            before.abs_diff_eq(after, 0.05),
+            "{join}: {before} != {after}"
+        );
+    }
+}
+
+#[test]
+fn control_points_round_trip_through_json() {
+    let mut context = BaseProviderContext::new();
+    let written = json!([
+        [0.0, 0.0],
+        [
+            1.0,
+            0.5,
+            [2.0, "opMul"],
+            ["baseCombo", "splineControl"],
+            [1.0, [2.0, "opAdd"], "splineControl"],
+            "easeInQuad",
+            "splineBezier"
+        ],
+        [0.0, 1.0, "splineCatmullRomCentripetal"]
+    ]);
+    let definition = BasicPointDefinition::<f32>::parse(written.clone(), &mut context);
+    assert_eq!(definition.to_json(), written);
+
+    let reparsed = BasicPointDefinition::<f32>::parse(definition.to_json(), &mut context);
+    for time in [0.1, 0.3, 0.5, 0.7, 0.9] {
+        assert_eq!(
+            definition.interpolate(time, &context),
+            reparsed.interpolate(time, &context)
+        );
+    }
+}
+
+#[test]
+fn reports_wrong_control_count_and_unsupported_splines() {
+    let mut context = BaseProviderContext::new();
+
+    let err = BasicPointDefinition::<f32>::try_parse(
+        &json!([[0, 0], [1, 1, [0, "splineControl"], "splineHermite"]]),
+        &mut context,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::WrongControlCount {
+            expected: 2,
+            found: 1
+        }
+    );
+    assert_eq!(err.path.indices(), &[1]);
+
+    let err = BasicPointDefinition::<f32>::try_parse(
+        &json!([[0, 0], [1, 1, [0, "splineControl"], "splineCatmullRom"]]),
+        &mut context,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::WrongControlCount {
+            expected: 0,
+            found: 1
+        }
+    );
+
+    let err = QuaternionPointDefinition::try_parse(
+        &json!([[0, 0, 0, 0], [0, 90, 0, 1, "splineBezier"]]),
+        &mut context,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::UnsupportedSpline("splineBezier".to_string())
+    );
+    assert_eq!(err.path.indices(), &[1]);
+}
+
+#[test]
+fn validate_checks_control_counts() {
+    let clean = validate(&json!([
+        [0, 0, 0, 0],
+        [
+            1,
+            1,
+            1,
+            0.5,
+            [0, 1, 0, "splineControl"],
+            ["baseHeadPosition", [0, 1, 0, "opAdd"], "splineControl"],
+            "splineBezier"
+        ],
+        [2, 2, 2, 1, "splineBSpline"]
+    ]));
+    assert!(clean.is_empty(), "{clean:?}");
+
+    let diagnostics = validate(&json!([[0, 0], [1, 1, "splineHermite"]]));
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![DiagnosticKind::Parse(ParseErrorKind::WrongControlCount {
+            expected: 2,
+            found: 0
+        })]
+    );
+}
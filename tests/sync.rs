@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use glam::Vec3;
+use serde_json::json;
+use tracks_rs::animation::track::Track;
+use tracks_rs::base_provider_context::BaseProviderContext;
+use tracks_rs::base_value::{BaseValue, WrapBaseValueType};
+use tracks_rs::point_definition::PointDefinitionLike;
+use tracks_rs::point_definition::base_point_definition::BasePointDefinition;
+use tracks_rs::providers::ValueProvider;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn runtime_types_are_send_and_sync() {
+    assert_send_sync::<BaseProviderContext>();
+    assert_send_sync::<ValueProvider>();
+    assert_send_sync::<BasePointDefinition>();
+    assert_send_sync::<Track>();
+}
+
+#[test]
+fn worker_threads_sample_a_shared_context() {
+    let mut context = BaseProviderContext::new();
+    context
+        .register_custom_provider("baseBeat", 1, false, |ctx: &BaseProviderContext| {
+            let time = ctx.get_values("baseSongTime").as_float().unwrap();
+            smallvec::smallvec![time.floor()]
+        })
+        .unwrap();
+    let definitions: Vec<_> = (0..16)
+        .map(|i| {
+            let json = json!([
+                [0, 0, 0, 0],
+                ["baseHeadPosition.s2", [i, "baseBeat", 0, "opAdd"], 0.5],
+                ["baseNoise3.seed1", 1, "easeOutQuad"]
+            ]);
+            BasePointDefinition::try_parse_typed(&json, WrapBaseValueType::Vec3, &mut context)
+                .unwrap()
+        })
+        .collect();
+
+    context.set_values("baseSongTime", BaseValue::Float(3.5));
+    context.set_values(
+        "baseHeadPosition",
+        BaseValue::Vector3(Vec3::new(1.0, 2.0, 3.0)),
+    );
+    context.update_providers(0.25);
+
+    let sample = |definition: &BasePointDefinition| {
+        (0..=100)
+            .map(|i| definition.interpolate(i as f32 / 100.0, &context).0)
+            .collect::<Vec<_>>()
+    };
+    let expected: Vec<_> = definitions.iter().map(sample).collect();
+
+    let results: Vec<_> = thread::scope(|scope| {
+        let workers: Vec<_> = definitions
+            .chunks(4)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(sample).collect::<Vec<_>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn workers_sample_while_providers_update() {
+    let mut context = BaseProviderContext::new();
+    let definition = BasePointDefinition::try_parse_typed(
+        &json!([["baseCombo.e4", 0], ["baseCombo.d8.s2", 1]]),
+        WrapBaseValueType::Float,
+        &mut context,
+    )
+    .unwrap();
+    context.set_values("baseCombo", BaseValue::Float(10.0));
+
+    let sample = |time: f32| definition.interpolate(time, &context).0.as_float().unwrap();
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                loop {
+                    for i in 0..=10 {
+                        // Both smoothing providers move from 0 towards 10 without overshooting
+                        let value = sample(i as f32 / 10.0);
+                        assert!((0.0..=10.0).contains(&value), "{value}");
+                    }
+                    if done.load(Ordering::Relaxed) {
+                        break;
+                    }
+                }
+            });
+        }
+        for _ in 0..1000 {
+            context.update_providers(1.0 / 144.0);
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+
+    // Sampling didn't disturb the updates
+    let mut serial = BaseProviderContext::new();
+    let expected = BasePointDefinition::try_parse_typed(
+        &json!([["baseCombo.e4", 0], ["baseCombo.d8.s2", 1]]),
+        WrapBaseValueType::Float,
+        &mut serial,
+    )
+    .unwrap();
+    serial.set_values("baseCombo", BaseValue::Float(10.0));
+    for _ in 0..1000 {
+        serial.update_providers(1.0 / 144.0);
+    }
+    for i in 0..=10 {
+        let time = i as f32 / 10.0;
+        assert_eq!(
+            sample(time),
+            expected.interpolate(time, &serial).0.as_float().unwrap()
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use serde_json::json;
+use tracks_rs::base_provider_context::BaseProviderContext;
+use tracks_rs::point_definition::{
+    PointDefinitionLike,
+    parse_error::ParseErrorKind,
+    validate::{DiagnosticKind, Severity, validate},
+    vector3_point_definition::Vector3PointDefinition,
+};
+
+#[test]
+fn clean_definition_has_no_diagnostics() {
+    let diagnostics = validate(&json!([
+        [0.0, 0.0, 0.0, 0.0],
+        [1.0, 2.0, 3.0, 0.5, "easeInOutQuad", "splineCatmullRom"],
+        ["baseHeadPosition.zxy.s0_5", [1.0, 1.0, 1.0, "opMul"], 1.0]
+    ]));
+
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn reports_times_out_of_order_and_range() {
+    let diagnostics = validate(&json!([[0.0, 0.5], [1.0, 0.2], [2.0, 1.5]]));
+
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::TimeNotAscending {
+                previous: 0.5,
+                time: 0.2
+            },
+            DiagnosticKind::TimeOutOfRange(1.5),
+        ]
+    );
+    assert_eq!(diagnostics[0].path.indices(), &[1, 1]);
+    assert_eq!(diagnostics[0].severity(), Severity::Error);
+    assert_eq!(diagnostics[1].severity(), Severity::Warning);
+}
+
+#[test]
+fn reports_all_flag_and_provider_problems_in_one_pass() {
+    let diagnostics = validate(&json!([
+        [0.0, 0.0, 0.0, 0.0, "easeInQaud", "splineCatmulRom"],
+        ["baseHeadPosition.xq.sfast", [1.0, "opAddd"], 1.0]
+    ]));
+
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::UnknownEasing("easeInQaud".to_string()),
+            DiagnosticKind::UnknownFlag("splineCatmulRom".to_string()),
+            DiagnosticKind::InvalidSwizzle {
+                base: "baseHeadPosition.xq.sfast".to_string(),
+                swizzle: 'q'
+            },
+            DiagnosticKind::InvalidSmoothing {
+                base: "baseHeadPosition.xq.sfast".to_string(),
+                spec: "sfast".to_string()
+            },
+            DiagnosticKind::Parse(ParseErrorKind::UnknownOperation("opAddd".to_string())),
+        ]
+    );
+    assert_eq!(diagnostics[4].path.indices(), &[1, 1, 1]);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "error: unknown easing \"easeInQaud\" at [0][4]"
+    );
+}
+
+#[test]
+fn reports_structural_problems() {
+    assert_eq!(
+        validate(&json!([])),
+        vec![tracks_rs::point_definition::validate::Diagnostic::new(
+            DiagnosticKind::Parse(ParseErrorKind::EmptyArray),
+            Default::default()
+        )]
+    );
+
+    let diagnostics = validate(&json!([[0.0, ["opAdd"], 0.0], [1.0, [1.0], 1.0]]));
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::Parse(ParseErrorKind::MissingValues),
+            DiagnosticKind::Parse(ParseErrorKind::MissingOperation),
+        ]
+    );
+}
+
+#[test]
+fn reports_flags_the_parser_ignores() {
+    let diagnostics = validate(&json!([
+        [0.0, 0.0, 0.0, 0.0, "opAdd", "splineControl"],
+        [
+            1.0,
+            1.0,
+            1.0,
+            [1.0, 1.0, 1.0, "opMul", "easeInQuad", "splineCatmullRom"],
+            1.0
+        ]
+    ]));
+
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::IgnoredFlag("opAdd".to_string()),
+            DiagnosticKind::IgnoredFlag("splineControl".to_string()),
+            DiagnosticKind::IgnoredFlag("easeInQuad".to_string()),
+            DiagnosticKind::IgnoredFlag("splineCatmullRom".to_string()),
+        ]
+    );
+    assert_eq!(diagnostics[2].path.indices(), &[1, 3, 4]);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| d.severity() == Severity::Warning)
+    );
+}
+
+#[test]
+fn modifier_operation_must_be_the_first_flag() {
+    let diagnostics = validate(&json!([[0.0, [1.0, "easeInQuad", "opAdd"], 0.0]]));
+
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::Parse(ParseErrorKind::UnknownOperation("easeInQuad".to_string())),
+            DiagnosticKind::IgnoredFlag("opAdd".to_string()),
+        ]
+    );
+    assert!(diagnostics[0].is_error());
+}
+
+#[test]
+fn control_points_take_splinecontrol_as_their_flag() {
+    assert!(
+        validate(&json!([
+            [0.0, 0.0],
+            [
+                1.0,
+                [0.5, "splineControl"],
+                [0.5, "splineControl"],
+                1.0,
+                "splineBezier"
+            ]
+        ]))
+        .is_empty()
+    );
+}
+
+#[test]
+fn type_dependent_problems_are_left_to_the_parser() {
+    // Two components are fine for a float modifier but not for a Vec3 one
+    let definition = json!([[0.0, 0.0, 0.0, [1.0, 1.0, "opAdd"], 0.0]]);
+    assert!(validate(&definition).is_empty());
+
+    let error = Vector3PointDefinition::try_parse(&definition, &mut BaseProviderContext::new())
+        .unwrap_err();
+    assert_eq!(
+        error.kind,
+        ParseErrorKind::WrongComponentCount {
+            expected: 3,
+            found: 2
+        }
+    );
+}
+
+#[test]
+fn shorthand_point_is_not_checked_for_time() {
+    assert!(validate(&json!([5.0])).is_empty());
+    assert!(validate(&json!(["baseHeadRotation.s14"])).is_empty());
+}
+
+#[test]
+fn reports_empty_and_long_swizzles() {
+    let diagnostics = validate(&json!([
+        ["baseHeadPosition.", 0.0],
+        ["baseHeadPosition.xyzwx", 1.0]
+    ]));
+
+    let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DiagnosticKind::EmptySwizzle {
+                base: "baseHeadPosition.".to_string()
+            },
+            DiagnosticKind::LongSwizzle {
+                base: "baseHeadPosition.xyzwx".to_string(),
+                swizzle: "xyzwx".to_string()
+            },
+        ]
+    );
+    assert!(diagnostics[0].is_error());
+    assert_eq!(diagnostics[1].severity(), Severity::Warning);
+}
+
+#[test]
+fn malformed_control_points_are_not_counted() {
+    for point in [
+        json!([0.0, ["splineControl"], 0.0]),
+        json!([
+            0.0,
+            ["splineControl"],
+            [0.5, "splineControl"],
+            0.0,
+            "splineBezier"
+        ]),
+    ] {
+        let kinds: Vec<_> = validate(&json!([point]))
+            .into_iter()
+            .map(|d| d.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![DiagnosticKind::Parse(ParseErrorKind::MissingValues)]
+        );
+    }
+}
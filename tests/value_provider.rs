@@ -2,7 +2,6 @@ use glam::{Quat, Vec3, Vec4};
 use tracks_rs::base_provider_context::BaseProviderContext;
 use tracks_rs::base_value::BaseValue;
 use tracks_rs::prelude::AbstractValueProvider;
-use tracks_rs::providers::ValueProvider;
 
 // Helper to check is_rotation for a provider string
 fn check_is_rotation(expr: &str, expected: bool) {
@@ -66,3 +65,35 @@ fn is_rotation_color_swizzle_false() {
     // color swizzle should not be rotation
     check_is_rotation("baseNote0Color.xyz", false);
 }
+
+#[test]
+fn hand_built_providers_derive_their_keys() {
+    use tracks_rs::providers::{
+        ValueProvider, partial::PartialProviderValues, smooth::SmoothProvidersValues,
+        smooth_rot::SmoothRotationProvidersValues,
+    };
+    use tracks_rs::shared::{Shared, StateCell};
+
+    let mut ctx = BaseProviderContext::new();
+    let position = ctx.get_value_provider("baseHeadPosition");
+    let rotation = ctx.get_value_provider("baseHeadRotation");
+
+    let partial = PartialProviderValues::new(position.clone(), vec![2, 0]);
+    let smooth = SmoothProvidersValues::new(position, 0.5, &ctx);
+    let smooth_rot = SmoothRotationProvidersValues::new(rotation, 14.0);
+
+    let providers = [
+        ValueProvider::PartialProvider(partial),
+        ValueProvider::SmoothProviders(Shared::new(StateCell::new(smooth))),
+        ValueProvider::SmoothRotationProviders(Shared::new(StateCell::new(smooth_rot))),
+    ];
+    let keys: Vec<_> = providers.iter().map(|p| p.key().unwrap()).collect();
+    assert_eq!(
+        keys,
+        [
+            "baseHeadPosition.zx",
+            "baseHeadPosition.s0_5",
+            "baseHeadRotation.s14"
+        ]
+    );
+}
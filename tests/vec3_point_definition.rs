@@ -122,3 +122,23 @@ fn base_provider_updates_with_smoothing_swizzle_and_operator_for_vec3() {
     assert!(approx_eq(v_after.y, expected_half(new_base).y, 1e-6));
     assert!(approx_eq(v_after.z, expected_half(new_base).z, 1e-6));
 }
+
+#[test]
+fn smooth_first_segment_uses_the_point_as_its_neighbour() {
+    let mut ctx = BaseProviderContext::new();
+    let def = Vector3PointDefinition::parse(
+        json!([
+            [0.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 1.0, "splineCatmullRom"],
+            [1.0, 1.0, 0.0, 2.0, "splineCatmullRom"]
+        ]),
+        &mut ctx,
+    );
+
+    let (start, _) = def.interpolate(0.0, &ctx);
+    assert_eq!(start, Vec3::ZERO);
+    let (mid, _) = def.interpolate(0.5, &ctx);
+    // Catmull-Rom weights at t = 0.5 are -0.125, 1.125, 1.125 and -0.125, with p0 = p1
+    assert!(approx_eq(mid.x, 0.5, 1e-5), "{mid}");
+    assert!(approx_eq(mid.y, -0.0625, 1e-5), "{mid}");
+}
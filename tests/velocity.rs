@@ -0,0 +1,101 @@
+use glam::{Quat, Vec3};
+use serde_json::json;
+use tracks_rs::base_provider_context::BaseProviderContext;
+use tracks_rs::base_value::BaseValue;
+use tracks_rs::point_definition::validate::validate;
+use tracks_rs::point_definition::{FloatPointDefinition, PointDefinitionLike};
+use tracks_rs::prelude::AbstractValueProvider;
+use tracks_rs::providers::ValueProvider;
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len(), "{actual:?} {expected:?}");
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-2, "{actual:?} {expected:?}");
+    }
+}
+
+#[test]
+fn position_velocity_is_per_second() {
+    let mut ctx = BaseProviderContext::new();
+    let velocity = ctx.get_value_provider("baseRightHandPosition.v");
+    assert!(matches!(velocity, ValueProvider::Velocity(_)));
+
+    // No previous sample yet
+    ctx.update_providers(0.5);
+    assert_close(&velocity.values(&ctx), &[0.0, 0.0, 0.0]);
+
+    ctx.set_values(
+        "baseRightHandPosition",
+        BaseValue::Vector3(Vec3::new(1.0, 0.0, -2.0)),
+    );
+    ctx.update_providers(0.5);
+    assert_close(&velocity.values(&ctx), &[2.0, 0.0, -4.0]);
+
+    // Same movement in a shorter frame is faster
+    ctx.set_values(
+        "baseRightHandPosition",
+        BaseValue::Vector3(Vec3::new(2.0, 0.0, -4.0)),
+    );
+    ctx.update_providers(0.25);
+    assert_close(&velocity.values(&ctx), &[4.0, 0.0, -8.0]);
+
+    // A zero delta keeps the last rate
+    ctx.update_providers(0.0);
+    assert_close(&velocity.values(&ctx), &[4.0, 0.0, -8.0]);
+}
+
+#[test]
+fn angular_velocity_of_rotations() {
+    let mut ctx = BaseProviderContext::new();
+    let velocity = ctx.get_value_provider("baseRightHandRotation.av");
+    assert!(!velocity.is_rotation(&ctx));
+
+    ctx.update_providers(0.1);
+    // 9 degrees around Y in a tenth of a second
+    ctx.set_values(
+        "baseRightHandRotation",
+        BaseValue::Quaternion(Quat::from_rotation_y(9f32.to_radians())),
+    );
+    ctx.update_providers(0.1);
+    assert_close(&velocity.values(&ctx), &[0.0, 90.0, 0.0]);
+
+    // Crossing 180 degrees takes the short way round
+    ctx.set_values(
+        "baseRightHandRotation",
+        BaseValue::Quaternion(Quat::from_rotation_y(179f32.to_radians())),
+    );
+    ctx.update_providers(1.0);
+    ctx.set_values(
+        "baseRightHandRotation",
+        BaseValue::Quaternion(Quat::from_rotation_y(-179f32.to_radians())),
+    );
+    ctx.update_providers(1.0);
+    assert_close(&velocity.values(&ctx), &[0.0, 2.0, 0.0]);
+}
+
+#[test]
+fn velocities_compose_with_swizzles_and_smoothing() {
+    let mut ctx = BaseProviderContext::new();
+    let speed = ctx.get_value_provider("baseRightHandPosition.v.x");
+    let smoothed = ctx.get_value_provider("baseRightHandPosition.v.s1");
+    let definition =
+        FloatPointDefinition::try_parse(&json!([["baseRightHandPosition.v.x", 0]]), &mut ctx)
+            .unwrap();
+
+    ctx.update_providers(0.5);
+    ctx.set_values(
+        "baseRightHandPosition",
+        BaseValue::Vector3(Vec3::new(3.0, 0.0, 0.0)),
+    );
+    ctx.update_providers(0.5);
+    assert_close(&speed.values(&ctx), &[6.0]);
+    assert_eq!(definition.interpolate(0.0, &ctx).0, 6.0);
+    // Providers update in the order they were created, so smoothing sees this tick's velocity
+    assert_close(&smoothed.values(&ctx), &[3.0, 0.0, 0.0]);
+    // Hand stopped
+    ctx.update_providers(1.0);
+    assert_close(&speed.values(&ctx), &[0.0]);
+    assert_close(&smoothed.values(&ctx), &[0.0, 0.0, 0.0]);
+
+    assert!(validate(&json!([["baseRightHandRotation.av.y.s0_5", 0]])).is_empty());
+}